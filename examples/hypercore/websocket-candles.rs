@@ -39,7 +39,7 @@
 use futures::StreamExt;
 use hypersdk::hypercore::{
     self,
-    types::{Incoming, Subscription},
+    types::{CandleInterval, Incoming, Subscription},
     ws::Event,
 };
 
@@ -55,7 +55,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Subscribe to 1-minute BTC candles
     ws.subscribe(Subscription::Candle {
         coin: "BTC".to_string(),
-        interval: "1m".to_string(),
+        interval: CandleInterval::OneMinute,
     });
 
     log::info!("Subscribed to BTC 1m candles. Waiting for updates...\n");
@@ -69,6 +69,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             Event::Disconnected => {
                 println!("WebSocket disconnected");
             }
+            Event::SubscriptionError { error, .. } => {
+                eprintln!("Subscription error: {error}");
+            }
             Event::Message(msg) => match msg {
                 Incoming::Candle(candle) => {
                     // Calculate some metrics
@@ -50,7 +50,10 @@ async fn main() -> anyhow::Result<()> {
         user: args.user,
         coin: args.coin.clone(),
     });
-    ws.subscribe(Subscription::WebData2 { user: args.user, dex: None });
+    ws.subscribe(Subscription::WebData2 {
+        user: args.user,
+        dex: None,
+    });
 
     log::info!(
         "Subscribed for user={} coin={}. Waiting for events...",
@@ -62,6 +65,7 @@ async fn main() -> anyhow::Result<()> {
         match event {
             Event::Connected => println!("Connected"),
             Event::Disconnected => println!("Disconnected, reconnecting..."),
+            Event::SubscriptionError { error, .. } => eprintln!("Subscription error: {error}"),
             Event::Message(msg) => match msg {
                 Incoming::UserEvents(user_event) => match user_event {
                     UserEvent::Fills { fills } => {
@@ -132,9 +136,14 @@ async fn main() -> anyhow::Result<()> {
                         data.coin, data.leverage.leverage_type, data.leverage.value, max_sz, avail
                     );
                 }
-                Incoming::WebData2 { data: payload, .. } => {
-                    let keys = payload.as_object().map(|m| m.len()).unwrap_or(0);
-                    println!("webData2: object_keys={}", keys);
+                Incoming::WebData2(data) => {
+                    println!(
+                        "webData2: accountValue={} withdrawable={} positions={} openOrders={}",
+                        data.account_value(),
+                        data.withdrawable(),
+                        data.positions().count(),
+                        data.open_orders.len()
+                    );
                 }
                 Incoming::SubscriptionResponse(resp) => {
                     println!("subscriptionResponse: {:?}", resp);
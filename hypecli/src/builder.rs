@@ -0,0 +1,95 @@
+//! Builder fee commands.
+//!
+//! This module provides commands for approving the fee a builder may charge on
+//! orders and querying the approved fee.
+
+use alloy::primitives::Address;
+use clap::{Args, Subcommand};
+use hypersdk::hypercore::{Chain, HttpClient, NonceHandler};
+
+use crate::SignerArgs;
+use crate::utils::find_signer_sync;
+
+/// Builder fee approval and query commands.
+#[derive(Subcommand)]
+pub enum BuilderCmd {
+    /// Approve the maximum fee a builder may charge
+    Approve(ApproveBuilderFeeCmd),
+    /// Query the maximum fee approved for a builder
+    MaxFee(MaxBuilderFeeCmd),
+}
+
+impl BuilderCmd {
+    pub async fn run(self) -> anyhow::Result<()> {
+        match self {
+            BuilderCmd::Approve(cmd) => cmd.run().await,
+            BuilderCmd::MaxFee(cmd) => cmd.run().await,
+        }
+    }
+}
+
+/// Arguments for builder fee approval.
+#[derive(Args, derive_more::Deref)]
+pub struct ApproveBuilderFeeCmd {
+    #[deref]
+    #[command(flatten)]
+    pub signer: SignerArgs,
+
+    /// Builder address
+    #[arg(long)]
+    pub builder: Address,
+
+    /// Maximum fee in tenths of a basis point (e.g. 10 for 0.01%), 0 to revoke
+    #[arg(long)]
+    pub max_fee: u64,
+}
+
+impl ApproveBuilderFeeCmd {
+    pub async fn run(self) -> anyhow::Result<()> {
+        let client = HttpClient::new(self.chain);
+        let signer = find_signer_sync(&self.signer)?;
+
+        println!(
+            "Approving builder {} for up to {} bp with signer {}",
+            self.builder,
+            self.max_fee as f64 / 10.0,
+            signer.address()
+        );
+
+        let nonce = NonceHandler::default().next();
+        client
+            .approve_builder_fee(&signer, self.builder, self.max_fee, nonce)
+            .await?;
+
+        println!("Builder fee approved successfully.");
+
+        Ok(())
+    }
+}
+
+/// Arguments for the approved builder fee query.
+#[derive(Args)]
+pub struct MaxBuilderFeeCmd {
+    /// User address
+    #[arg(long)]
+    pub user: Address,
+
+    /// Builder address
+    #[arg(long)]
+    pub builder: Address,
+
+    /// Target chain
+    #[arg(long, default_value = "mainnet")]
+    pub chain: Chain,
+}
+
+impl MaxBuilderFeeCmd {
+    pub async fn run(self) -> anyhow::Result<()> {
+        let client = HttpClient::new(self.chain);
+        let fee = client.max_builder_fee(self.user, self.builder).await?;
+
+        println!("Max builder fee: {} ({} bp)", fee, fee as f64 / 10.0);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,64 @@
+//! Leverage update command.
+//!
+//! Sets the leverage and margin mode (cross or isolated) for a perpetual asset.
+
+use clap::Args;
+use hypersdk::hypercore::{HttpClient, NonceHandler, api::UpdateLeverage};
+
+use crate::SignerArgs;
+use crate::utils::{find_signer_sync, resolve_asset};
+
+/// Update the leverage of a perpetual asset.
+#[derive(Args, derive_more::Deref)]
+pub struct LeverageCmd {
+    #[deref]
+    #[command(flatten)]
+    pub signer: SignerArgs,
+
+    /// Asset name. Formats:
+    /// - "BTC" for BTC perpetual
+    /// - "xyz:BTC" for BTC perpetual on xyz HIP3 DEX
+    #[arg(long)]
+    pub asset: String,
+
+    /// New leverage (e.g. 10 for 10x)
+    #[arg(long)]
+    pub leverage: u32,
+
+    /// Use isolated margin instead of cross margin
+    #[arg(long, default_value = "false")]
+    pub isolated: bool,
+}
+
+impl LeverageCmd {
+    pub async fn run(self) -> anyhow::Result<()> {
+        let client = HttpClient::new(self.chain);
+        let signer = find_signer_sync(&self.signer)?;
+
+        let asset_index = resolve_asset(&client, &self.asset).await?;
+
+        println!(
+            "Setting {} (index {}) to {}x {} with signer {}",
+            self.asset,
+            asset_index,
+            self.leverage,
+            if self.isolated { "isolated" } else { "cross" },
+            signer.address()
+        );
+
+        let nonce = NonceHandler::default().next();
+        let update = UpdateLeverage {
+            asset: asset_index,
+            is_cross: !self.isolated,
+            leverage: self.leverage,
+        };
+
+        client
+            .update_leverage(&signer, update, nonce, None, None)
+            .await?;
+
+        println!("Leverage updated successfully.");
+
+        Ok(())
+    }
+}
@@ -1,5 +1,8 @@
 mod account;
 mod balances;
+mod builder;
+mod leverage;
+mod margin;
 mod markets;
 mod morpho;
 mod multisig;
@@ -12,8 +15,11 @@ mod vault;
 
 use account::AccountCmd;
 use balances::BalanceCmd;
+use builder::BuilderCmd;
 use clap::{Args, Parser};
 use hypersdk::hypercore::Chain;
+use leverage::LeverageCmd;
+use margin::MarginCmd;
 use markets::{DexesCmd, PerpsCmd, SpotCmd};
 use morpho::{MorphoApyCmd, MorphoPositionCmd, MorphoVaultApyCmd};
 use multisig::MultiSigCmd;
@@ -44,8 +50,16 @@ enum Command {
     Account(AccountCmd),
     /// Query all balances (spot, perp, and DEX) for a user
     Balance(BalanceCmd),
+    /// Approve and query builder fees
+    #[command(subcommand)]
+    Builder(BuilderCmd),
     /// List HIP-3 DEXes
     Dexes(DexesCmd),
+    /// Update leverage and margin mode for a perpetual asset
+    Leverage(LeverageCmd),
+    /// Add or remove margin from isolated positions
+    #[command(subcommand)]
+    Margin(MarginCmd),
     /// List perpetual markets
     Perps(PerpsCmd),
     /// List spot markets
@@ -79,7 +93,10 @@ impl Command {
         match self {
             Self::Account(cmd) => cmd.run().await,
             Self::Balance(cmd) => cmd.run().await,
+            Self::Builder(cmd) => cmd.run().await,
             Self::Dexes(cmd) => cmd.run().await,
+            Self::Leverage(cmd) => cmd.run().await,
+            Self::Margin(cmd) => cmd.run().await,
             Self::Perps(cmd) => cmd.run().await,
             Self::Spot(cmd) => cmd.run().await,
             Self::MorphoPosition(cmd) => cmd.run().await,
@@ -272,10 +289,22 @@ Place a Market Order:
     --asset <NAME>              Asset name
     --side <buy|sell>           Order direction
     --size <DECIMAL>            Order size
-    --slippage-price <DECIMAL>  Worst acceptable fill price
-    --reduce-only               Optional flag
+    --slippage-price <DECIMAL>  Optional worst acceptable fill price
+    --slippage-bps <INT>        Slippage from the mid price when no price is given (default: 500)
+    --reduce-only               Optional flag (requires --slippage-price)
     --cloid <HEX>               Optional client order ID
 
+Close a Position at Market:
+  hypecli order close \
+    --chain mainnet \
+    --private-key <HEX> \
+    --asset ETH \
+    --slippage-bps 50
+
+  Arguments:
+    --asset <NAME>              Perpetual asset name (e.g. ETH, xyz:BTC)
+    --slippage-bps <INT>        Slippage from the mid price (default: 500)
+
 Cancel Order (by OID or CLOID):
   # Cancel by OID (exchange-assigned order ID)
   hypecli order cancel \
@@ -296,6 +325,89 @@ Cancel Order (by OID or CLOID):
     --oid <NUMBER>    Exchange-assigned order ID (use this OR --cloid)
     --cloid <HEX>     Client-assigned order ID, 32 hex chars (use this OR --oid)
 
+Place a TWAP Order:
+  hypecli order twap \
+    --chain mainnet \
+    --private-key <HEX> \
+    --asset BTC \
+    --side buy \
+    --size 1 \
+    --minutes 30
+
+  Arguments:
+    --asset <NAME>       Asset name
+    --side <buy|sell>    Order direction
+    --size <DECIMAL>     Total order size
+    --minutes <NUMBER>   Duration in minutes (slices are executed every 30 seconds)
+    --reduce-only        Optional flag
+    --randomize          Optional flag to randomize slice sizes
+
+  Returns the TWAP ID used for cancellation.
+
+Cancel a TWAP Order:
+  hypecli order twap-cancel \
+    --chain mainnet \
+    --private-key <HEX> \
+    --asset BTC \
+    --twap-id <TWAP_ID>
+
+LEVERAGE AND MARGIN COMMANDS
+----------------------------
+
+Update Leverage:
+  hypecli leverage \
+    --chain mainnet \
+    --private-key <HEX> \
+    --asset BTC \
+    --leverage 10
+
+  Arguments:
+    --asset <NAME>       Perpetual asset name (e.g. BTC, xyz:BTC)
+    --leverage <NUMBER>  New leverage (e.g. 10 for 10x)
+    --isolated           Optional flag to use isolated margin (default: cross)
+
+Add Margin to an Isolated Position:
+  hypecli margin add \
+    --chain mainnet \
+    --private-key <HEX> \
+    --asset BTC \
+    --amount 25
+
+Remove Margin from an Isolated Position:
+  hypecli margin remove \
+    --chain mainnet \
+    --private-key <HEX> \
+    --asset BTC \
+    --amount 25
+
+  Arguments:
+    --asset <NAME>       Perpetual asset name (e.g. BTC, xyz:BTC)
+    --amount <DECIMAL>   Amount of USDC
+
+  Margin cannot be removed from strictly isolated markets.
+
+BUILDER FEE COMMANDS
+--------------------
+
+Approve a Builder Fee:
+  hypecli builder approve \
+    --chain mainnet \
+    --private-key <HEX> \
+    --builder <ADDRESS> \
+    --max-fee 10
+
+  Arguments:
+    --builder <ADDRESS>  Builder address
+    --max-fee <NUMBER>   Maximum fee in tenths of a basis point (10 = 0.01%), 0 to revoke
+
+  Must be signed by the account owner, not an agent.
+
+Query the Approved Builder Fee:
+  hypecli builder max-fee \
+    --chain mainnet \
+    --user <ADDRESS> \
+    --builder <ADDRESS>
+
 MULTI-SIG COMMANDS
 ------------------
 
@@ -331,6 +443,15 @@ Multi-Sig Update Configuration:
     --authorized-user <ADDR2> \
     --threshold 2
 
+Multi-Sig Update Leverage:
+  hypecli multisig update-leverage \
+    --chain mainnet \
+    --private-key <HEX> \
+    --multi-sig-addr <MULTISIG_ADDRESS> \
+    --asset BTC \
+    --leverage 5 \
+    --isolated
+
 Convert Multi-Sig to Normal User:
   hypecli multisig convert-to-normal-user \
     --chain mainnet \
@@ -0,0 +1,68 @@
+//! Isolated margin commands.
+//!
+//! This module provides commands for adding and removing margin
+//! from isolated perpetual positions.
+
+use clap::{Args, Subcommand};
+use hypersdk::{
+    Decimal,
+    hypercore::{HttpClient, NonceHandler},
+};
+
+use crate::SignerArgs;
+use crate::utils::{find_signer_sync, resolve_perp_market};
+
+/// Isolated margin commands.
+#[derive(Subcommand)]
+pub enum MarginCmd {
+    /// Add USDC margin to an isolated position
+    Add(MarginUpdateCmd),
+    /// Remove USDC margin from an isolated position
+    Remove(MarginUpdateCmd),
+}
+
+impl MarginCmd {
+    pub async fn run(self) -> anyhow::Result<()> {
+        match self {
+            MarginCmd::Add(cmd) => execute_update(cmd, true).await,
+            MarginCmd::Remove(cmd) => execute_update(cmd, false).await,
+        }
+    }
+}
+
+async fn execute_update(cmd: MarginUpdateCmd, is_add: bool) -> anyhow::Result<()> {
+    let (verb, past) = if is_add {
+        ("Adding", "Added")
+    } else {
+        ("Removing", "Removed")
+    };
+    let signer = find_signer_sync(&cmd.signer)?;
+    let client = HttpClient::new(cmd.signer.chain);
+    let market = resolve_perp_market(&client, &cmd.asset).await?;
+
+    let usd = if is_add { cmd.amount } else { -cmd.amount };
+    let nonce = NonceHandler::default().next();
+
+    println!("{} ${} margin on {}", verb, cmd.amount, market.name);
+    client
+        .update_isolated_margin(&signer, &market, usd, nonce, None, None)
+        .await?;
+    println!("{} successfully.", past);
+    Ok(())
+}
+
+/// Arguments for adding or removing isolated margin.
+#[derive(Args, derive_more::Deref)]
+pub struct MarginUpdateCmd {
+    #[deref]
+    #[command(flatten)]
+    pub signer: SignerArgs,
+
+    /// Perpetual asset name (e.g. "BTC", "xyz:BTC")
+    #[arg(long)]
+    pub asset: String,
+
+    /// Amount of USDC
+    #[arg(long)]
+    pub amount: Decimal,
+}
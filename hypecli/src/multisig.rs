@@ -12,6 +12,7 @@ use hypersdk::{
         self, AssetTarget, HttpClient, NonceHandler, SendAsset, SendToken, Signature,
         api::{
             self, Action, ConvertToMultiSigUser, MultiSigAction, MultiSigPayload, SignersConfig,
+            UpdateLeverage,
         },
     },
 };
@@ -40,6 +41,7 @@ pub enum MultiSigCmd {
     Sign(MultiSigSign),
     Update(UpdateMultiSigCmd),
     SendAsset(MultiSigSendAsset),
+    UpdateLeverage(MultiSigUpdateLeverage),
     ConvertToNormalUser(MultiSigConvertToNormalUser),
 }
 
@@ -48,6 +50,7 @@ impl MultiSigCmd {
         match self {
             MultiSigCmd::Sign(cmd) => cmd.run().await,
             MultiSigCmd::SendAsset(cmd) => cmd.run().await,
+            MultiSigCmd::UpdateLeverage(cmd) => cmd.run().await,
             MultiSigCmd::ConvertToNormalUser(cmd) => cmd.run().await,
             MultiSigCmd::Update(cmd) => cmd.run().await,
         }
@@ -90,6 +93,35 @@ impl MultiSigSendAsset {
     }
 }
 
+/// Command to update the leverage of an asset via multi-sig.
+///
+/// This command creates a multi-sig transaction proposal to change the leverage
+/// and margin mode of a perpetual asset for the multi-sig wallet.
+#[derive(Args, derive_more::Deref)]
+pub struct MultiSigUpdateLeverage {
+    #[deref]
+    #[command(flatten)]
+    pub common: SignerArgs,
+    /// Multi-sig wallet address.
+    #[arg(long)]
+    pub multi_sig_addr: Address,
+    /// Perpetual asset name (e.g. "BTC", "xyz:BTC").
+    #[arg(long)]
+    pub asset: String,
+    /// New leverage (e.g. 10 for 10x).
+    #[arg(long)]
+    pub leverage: u32,
+    /// Use isolated margin instead of cross margin.
+    #[arg(long, default_value = "false")]
+    pub isolated: bool,
+}
+
+impl MultiSigUpdateLeverage {
+    pub async fn run(self) -> anyhow::Result<()> {
+        update_leverage(self).await
+    }
+}
+
 /// Command to sign a multi-sig transaction proposal.
 ///
 /// This command connects to a peer who initiated a multi-sig transaction
@@ -256,6 +288,33 @@ async fn update(cmd: UpdateMultiSigCmd) -> anyhow::Result<()> {
     .await
 }
 
+async fn update_leverage(cmd: MultiSigUpdateLeverage) -> anyhow::Result<()> {
+    let hl = HttpClient::new(cmd.chain);
+    let multisig_config = hl.multi_sig_config(cmd.multi_sig_addr).await?;
+    let signer = find_signer(&cmd.common, Some(&multisig_config.authorized_users)).await?;
+
+    println!("Using signer {}", signer.address());
+
+    let asset = utils::resolve_asset(&hl, &cmd.asset).await?;
+    let nonce = NonceHandler::default().next();
+
+    let action = Action::UpdateLeverage(UpdateLeverage {
+        asset,
+        is_cross: !cmd.isolated,
+        leverage: cmd.leverage,
+    });
+
+    execute_multisig_action(
+        cmd.multi_sig_addr,
+        hl,
+        signer,
+        action,
+        nonce,
+        &multisig_config,
+    )
+    .await
+}
+
 async fn convert_to_normal_user(cmd: MultiSigConvertToNormalUser) -> anyhow::Result<()> {
     let hl = HttpClient::new(cmd.chain);
     let multisig_config = hl.multi_sig_config(cmd.multi_sig_addr).await?;
@@ -3,7 +3,9 @@
 //! This module provides CLI commands for:
 //! - Placing limit orders
 //! - Placing market orders
+//! - Closing positions at market
 //! - Canceling orders (by OID or CLOID)
+//! - Placing and canceling TWAP orders
 //!
 //! ## Asset Name Formats
 //!
@@ -16,12 +18,13 @@ use alloy::primitives::B128;
 use clap::{Args, Subcommand, ValueEnum};
 use hypersdk::hypercore::{
     BatchCancel, BatchCancelCloid, BatchOrder, Cancel, CancelByCloid, Cloid, HttpClient,
-    OrderGrouping, OrderRequest, OrderTypePlacement, TimeInForce,
+    MarketOrder, NonceHandler, OrderGrouping, OrderRequest, OrderTypePlacement, Slippage,
+    TimeInForce, TwapCancel, TwapOrder, TwapRequest, order_builder::DEFAULT_SLIPPAGE_BPS,
 };
 use rust_decimal::Decimal;
 
 use crate::SignerArgs;
-use crate::utils::{find_signer_sync, resolve_asset};
+use crate::utils::{find_signer_sync, resolve_asset, resolve_perp_market};
 
 /// Order management commands.
 #[derive(Subcommand)]
@@ -30,8 +33,14 @@ pub enum OrderCmd {
     Limit(LimitOrderCmd),
     /// Place a market order
     Market(MarketOrderCmd),
+    /// Close a perpetual position at market
+    Close(ClosePositionCmd),
     /// Cancel an order by OID or CLOID
     Cancel(CancelOrderCmd),
+    /// Place a TWAP order
+    Twap(TwapOrderCmd),
+    /// Cancel a running TWAP order
+    TwapCancel(TwapCancelCmd),
 }
 
 impl OrderCmd {
@@ -39,7 +48,10 @@ impl OrderCmd {
         match self {
             Self::Limit(cmd) => cmd.run().await,
             Self::Market(cmd) => cmd.run().await,
+            Self::Close(cmd) => cmd.run().await,
             Self::Cancel(cmd) => cmd.run().await,
+            Self::Twap(cmd) => cmd.run().await,
+            Self::TwapCancel(cmd) => cmd.run().await,
         }
     }
 }
@@ -150,6 +162,7 @@ impl LimitOrderCmd {
         let batch = BatchOrder {
             orders: vec![order],
             grouping: OrderGrouping::Na,
+            builder: None,
         };
 
         let nonce = std::time::SystemTime::now()
@@ -196,9 +209,16 @@ pub struct MarketOrderCmd {
     #[arg(long)]
     pub size: Decimal,
 
-    /// Slippage price (worst acceptable price for the market order)
+    /// Slippage price (worst acceptable price for the market order).
+    ///
+    /// When omitted, the price is derived from the mid price and `--slippage-bps`
+    /// (perpetual markets only).
     #[arg(long)]
-    pub slippage_price: Decimal,
+    pub slippage_price: Option<Decimal>,
+
+    /// Slippage from the mid price in basis points, used without `--slippage-price`
+    #[arg(long, default_value_t = DEFAULT_SLIPPAGE_BPS)]
+    pub slippage_bps: u32,
 
     /// Reduce-only order (can only reduce existing position)
     #[arg(long, default_value = "false")]
@@ -214,10 +234,45 @@ impl MarketOrderCmd {
         let client = HttpClient::new(self.chain);
         let signer = find_signer_sync(&self.signer)?;
 
-        let asset_index = resolve_asset(&client, &self.asset).await?;
-
         let cloid = parse_cloid(self.cloid.as_deref())?;
 
+        let Some(slippage_price) = self.slippage_price else {
+            anyhow::ensure!(
+                !self.reduce_only,
+                "--reduce-only requires --slippage-price, use `order close` to close a position"
+            );
+
+            let market = resolve_perp_market(&client, &self.asset).await?;
+            let order = if self.side.is_buy() {
+                MarketOrder::buy(&market, self.size)
+            } else {
+                MarketOrder::sell(&market, self.size)
+            }
+            .with_slippage(Slippage::bps(self.slippage_bps))
+            .with_cloid(cloid);
+
+            println!(
+                "Placing market order for {} (index {}) with {} bps slippage and signer {}",
+                market.name,
+                market.index,
+                self.slippage_bps,
+                signer.address()
+            );
+            println!("CLOID: 0x{}", hex::encode(cloid.as_slice()));
+
+            let nonce = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)?
+                .as_millis() as u64;
+            let status = client
+                .market_open(&signer, order, nonce, None, None)
+                .await?;
+            println!("Market order placed successfully:");
+            println!("  Order 0: {:?}", status);
+            return Ok(());
+        };
+
+        let asset_index = resolve_asset(&client, &self.asset).await?;
+
         println!(
             "Placing market order for {} (index {}) with signer {}",
             self.asset,
@@ -230,7 +285,7 @@ impl MarketOrderCmd {
         let order = OrderRequest {
             asset: asset_index,
             is_buy: self.side.is_buy(),
-            limit_px: self.slippage_price,
+            limit_px: slippage_price,
             sz: self.size,
             reduce_only: self.reduce_only,
             order_type: OrderTypePlacement::Limit {
@@ -242,6 +297,7 @@ impl MarketOrderCmd {
         let batch = BatchOrder {
             orders: vec![order],
             grouping: OrderGrouping::Na,
+            builder: None,
         };
 
         let nonce = std::time::SystemTime::now()
@@ -266,6 +322,62 @@ impl MarketOrderCmd {
     }
 }
 
+/// Close a perpetual position at market.
+///
+/// The side and size are taken from the current position.
+#[derive(Args, derive_more::Deref)]
+pub struct ClosePositionCmd {
+    #[deref]
+    #[command(flatten)]
+    pub signer: SignerArgs,
+
+    /// Asset name. Formats:
+    /// - "BTC" for BTC perpetual
+    /// - "xyz:BTC" for BTC perpetual on xyz HIP3 DEX
+    #[arg(long)]
+    pub asset: String,
+
+    /// Slippage from the mid price in basis points
+    #[arg(long, default_value_t = DEFAULT_SLIPPAGE_BPS)]
+    pub slippage_bps: u32,
+}
+
+impl ClosePositionCmd {
+    pub async fn run(self) -> anyhow::Result<()> {
+        let client = HttpClient::new(self.chain);
+        let signer = find_signer_sync(&self.signer)?;
+
+        let market = resolve_perp_market(&client, &self.asset).await?;
+
+        println!(
+            "Closing {} position (index {}) with {} bps slippage and signer {}",
+            market.name,
+            market.index,
+            self.slippage_bps,
+            signer.address()
+        );
+
+        let nonce = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_millis() as u64;
+        let status = client
+            .market_close(
+                &signer,
+                &market,
+                Slippage::bps(self.slippage_bps),
+                nonce,
+                None,
+                None,
+            )
+            .await?;
+
+        println!("Close order placed successfully:");
+        println!("  Order 0: {:?}", status);
+
+        Ok(())
+    }
+}
+
 /// Cancel an order by OID or CLOID.
 ///
 /// Specify either `--oid` for exchange-assigned order ID or `--cloid` for client-assigned order ID.
@@ -379,6 +491,126 @@ impl CancelOrderCmd {
     }
 }
 
+/// Place a TWAP order.
+///
+/// The order is split into slices executed every 30 seconds over the given duration.
+#[derive(Args, derive_more::Deref)]
+pub struct TwapOrderCmd {
+    #[deref]
+    #[command(flatten)]
+    pub signer: SignerArgs,
+
+    /// Asset name. Formats:
+    /// - "BTC" for BTC perpetual
+    /// - "PURR/USDC" for PURR spot market
+    /// - "xyz:BTC" for BTC perpetual on xyz HIP3 DEX
+    #[arg(long)]
+    pub asset: String,
+
+    /// Order side (buy or sell)
+    #[arg(long)]
+    pub side: Side,
+
+    /// Total order size
+    #[arg(long)]
+    pub size: Decimal,
+
+    /// Duration in minutes
+    #[arg(long)]
+    pub minutes: u64,
+
+    /// Reduce-only order (can only reduce existing position)
+    #[arg(long, default_value = "false")]
+    pub reduce_only: bool,
+
+    /// Randomize slice sizes
+    #[arg(long, default_value = "false")]
+    pub randomize: bool,
+}
+
+impl TwapOrderCmd {
+    pub async fn run(self) -> anyhow::Result<()> {
+        let client = HttpClient::new(self.chain);
+        let signer = find_signer_sync(&self.signer)?;
+
+        let asset_index = resolve_asset(&client, &self.asset).await?;
+
+        println!(
+            "Placing TWAP order for {} (index {}) over {} minutes with signer {}",
+            self.asset,
+            asset_index,
+            self.minutes,
+            signer.address()
+        );
+
+        let twap = TwapOrder {
+            twap: TwapRequest {
+                asset: asset_index,
+                is_buy: self.side.is_buy(),
+                sz: self.size,
+                reduce_only: self.reduce_only,
+                minutes: self.minutes,
+                randomize: self.randomize,
+            },
+        };
+
+        let nonce = NonceHandler::default().next();
+        let twap_id = client.twap_order(&signer, twap, nonce, None, None).await?;
+
+        println!("TWAP order placed successfully:");
+        println!("  TWAP ID: {}", twap_id);
+
+        Ok(())
+    }
+}
+
+/// Cancel a running TWAP order.
+#[derive(Args, derive_more::Deref)]
+pub struct TwapCancelCmd {
+    #[deref]
+    #[command(flatten)]
+    pub signer: SignerArgs,
+
+    /// Asset name the TWAP belongs to
+    #[arg(long)]
+    pub asset: String,
+
+    /// TWAP ID returned when the TWAP was placed
+    #[arg(long)]
+    pub twap_id: u64,
+}
+
+impl TwapCancelCmd {
+    pub async fn run(self) -> anyhow::Result<()> {
+        let client = HttpClient::new(self.chain);
+        let signer = find_signer_sync(&self.signer)?;
+
+        let asset_index = resolve_asset(&client, &self.asset).await?;
+
+        println!(
+            "Canceling TWAP {} for {} (index {}) with signer {}",
+            self.twap_id,
+            self.asset,
+            asset_index,
+            signer.address()
+        );
+
+        let cancel = TwapCancel {
+            asset: asset_index,
+            twap_id: self.twap_id,
+        };
+
+        let nonce = NonceHandler::default().next();
+        client
+            .twap_cancel(&signer, cancel, nonce, None, None)
+            .await?;
+
+        println!("TWAP canceled successfully.");
+
+        Ok(())
+    }
+}
+
 /// Parse an optional CLOID string into a B128.
 /// If None is provided, generates a random CLOID.
 fn parse_cloid(cloid: Option<&str>) -> anyhow::Result<Cloid> {
@@ -51,6 +51,8 @@ pub enum SubscribeCmd {
     OrderUpdates(OrderUpdatesCmd),
     /// Subscribe to fill events for a user
     Fills(FillsCmd),
+    /// Subscribe to deposits, withdrawals, transfers and funding payments for a user
+    Ledger(LedgerCmd),
 }
 
 impl SubscribeCmd {
@@ -63,6 +65,7 @@ impl SubscribeCmd {
             Self::AllMids(cmd) => cmd.run().await,
             Self::OrderUpdates(cmd) => cmd.run().await,
             Self::Fills(cmd) => cmd.run().await,
+            Self::Ledger(cmd) => cmd.run().await,
         }
     }
 }
@@ -113,6 +116,7 @@ impl TradesCmd {
             match event {
                 Event::Connected => eprintln!("Connected"),
                 Event::Disconnected => eprintln!("Disconnected, reconnecting..."),
+                Event::SubscriptionError { error, .. } => eprintln!("Subscription error: {error}"),
                 Event::Message(msg) => match msg {
                     Incoming::Trades(trades) => {
                         for trade in trades {
@@ -189,6 +193,7 @@ impl BboCmd {
             match event {
                 Event::Connected => eprintln!("Connected"),
                 Event::Disconnected => eprintln!("Disconnected, reconnecting..."),
+                Event::SubscriptionError { error, .. } => eprintln!("Subscription error: {error}"),
                 Event::Message(msg) => match msg {
                     Incoming::Bbo(bbo) => match self.format {
                         OutputFormat::Pretty => {
@@ -262,9 +267,7 @@ impl OrderbookCmd {
         };
 
         let mut ws = core.websocket();
-        ws.subscribe(Subscription::L2Book {
-            coin: resolved.coin.clone(),
-        });
+        ws.subscribe(Subscription::l2_book(resolved.coin.clone()));
 
         eprintln!("Subscribing to {} orderbook...", self.asset);
 
@@ -272,6 +275,7 @@ impl OrderbookCmd {
             match event {
                 Event::Connected => eprintln!("Connected"),
                 Event::Disconnected => eprintln!("Disconnected, reconnecting..."),
+                Event::SubscriptionError { error, .. } => eprintln!("Subscription error: {error}"),
                 Event::Message(msg) => match msg {
                     Incoming::L2Book(book) => match self.format {
                         OutputFormat::Pretty => {
@@ -348,7 +352,7 @@ impl CandlesCmd {
         let mut ws = core.websocket();
         ws.subscribe(Subscription::Candle {
             coin: self.coin.clone(),
-            interval: self.interval.clone(),
+            interval: self.interval.parse()?,
         });
 
         eprintln!("Subscribing to {} {} candles...", self.coin, self.interval);
@@ -357,6 +361,7 @@ impl CandlesCmd {
             match event {
                 Event::Connected => eprintln!("Connected"),
                 Event::Disconnected => eprintln!("Disconnected, reconnecting..."),
+                Event::SubscriptionError { error, .. } => eprintln!("Subscription error: {error}"),
                 Event::Message(msg) => match msg {
                     Incoming::Candle(candle) => match self.format {
                         OutputFormat::Pretty => {
@@ -444,6 +449,7 @@ impl AllMidsCmd {
             match event {
                 Event::Connected => eprintln!("Connected"),
                 Event::Disconnected => eprintln!("Disconnected, reconnecting..."),
+                Event::SubscriptionError { error, .. } => eprintln!("Subscription error: {error}"),
                 Event::Message(msg) => match msg {
                     Incoming::AllMids { dex, mids } => match self.format {
                         OutputFormat::Pretty => {
@@ -519,6 +525,7 @@ impl OrderUpdatesCmd {
             match event {
                 Event::Connected => eprintln!("Connected"),
                 Event::Disconnected => eprintln!("Disconnected, reconnecting..."),
+                Event::SubscriptionError { error, .. } => eprintln!("Subscription error: {error}"),
                 Event::Message(msg) => match msg {
                     Incoming::OrderUpdates(updates) => {
                         for update in updates {
@@ -587,6 +594,7 @@ impl FillsCmd {
             match event {
                 Event::Connected => eprintln!("Connected"),
                 Event::Disconnected => eprintln!("Disconnected, reconnecting..."),
+                Event::SubscriptionError { error, .. } => eprintln!("Subscription error: {error}"),
                 Event::Message(msg) => match msg {
                     Incoming::UserFills { user, fills, .. } => {
                         for fill in fills {
@@ -622,3 +630,91 @@ impl FillsCmd {
         Ok(())
     }
 }
+
+/// Subscribe to ledger updates for a user.
+///
+/// Streams deposits, withdrawals, transfers, vault moves and funding payments.
+///
+/// # Example
+///
+/// ```bash
+/// hypecli subscribe ledger --user 0x1234...
+/// ```
+#[derive(Args)]
+pub struct LedgerCmd {
+    /// User address to monitor
+    #[arg(long)]
+    pub user: Address,
+    /// Target chain
+    #[arg(long, default_value = "Mainnet")]
+    pub chain: Chain,
+    /// Output format
+    #[arg(long, default_value = "pretty")]
+    pub format: OutputFormat,
+}
+
+impl LedgerCmd {
+    pub async fn run(self) -> anyhow::Result<()> {
+        let core = match self.chain {
+            Chain::Mainnet => hypercore::mainnet(),
+            Chain::Testnet => hypercore::testnet(),
+        };
+
+        let mut ws = core.websocket();
+        ws.subscribe(Subscription::UserNonFundingLedgerUpdates { user: self.user });
+        ws.subscribe(Subscription::UserFundings { user: self.user });
+
+        eprintln!("Subscribing to ledger updates for {}...", self.user);
+
+        while let Some(event) = ws.next().await {
+            match event {
+                Event::Connected => eprintln!("Connected"),
+                Event::Disconnected => eprintln!("Disconnected, reconnecting..."),
+                Event::SubscriptionError { error, .. } => eprintln!("Subscription error: {error}"),
+                Event::Message(msg) => match msg {
+                    Incoming::UserNonFundingLedgerUpdates {
+                        non_funding_ledger_updates,
+                        ..
+                    } => {
+                        for update in non_funding_ledger_updates {
+                            match self.format {
+                                OutputFormat::Pretty => {
+                                    println!(
+                                        "[{}] {:?} | hash: {}",
+                                        update.time, update.delta, update.hash
+                                    );
+                                }
+                                OutputFormat::Json => {
+                                    println!("{}", serde_json::to_string(&update)?);
+                                }
+                            }
+                        }
+                    }
+                    Incoming::UserFundings { fundings, .. } => {
+                        for funding in fundings {
+                            match self.format {
+                                OutputFormat::Pretty => {
+                                    println!(
+                                        "[{}] funding {} {} | szi: {} | rate: {}",
+                                        funding.time,
+                                        funding.coin,
+                                        funding.usdc,
+                                        funding.szi,
+                                        funding.funding_rate
+                                    );
+                                }
+                                OutputFormat::Json => {
+                                    println!("{}", serde_json::to_string(&funding)?);
+                                }
+                            }
+                        }
+                    }
+                    Incoming::SubscriptionResponse(_) => eprintln!("Subscription confirmed"),
+                    _ => {}
+                },
+            }
+        }
+
+        Ok(())
+    }
+}
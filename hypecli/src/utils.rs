@@ -332,6 +332,38 @@ pub async fn resolve_asset(client: &HttpClient, asset: &str) -> anyhow::Result<u
     }
 }
 
+/// Resolve a perpetual asset name to its market.
+///
+/// Same as [`resolve_asset`] but returns the full [`PerpMarket`], spot markets
+/// are rejected.
+pub async fn resolve_perp_market(client: &HttpClient, asset: &str) -> anyhow::Result<PerpMarket> {
+    let spec = parse_asset_spec(asset)?;
+
+    let (mut perps, index) = match spec {
+        AssetSpec::Perp(symbol) => {
+            let perps = client.perps().await?;
+            let index = find_perp_index(&perps, symbol)?;
+            (perps, index)
+        }
+        AssetSpec::Spot(..) => {
+            anyhow::bail!("'{}' is a spot market, expected a perpetual market", asset)
+        }
+        AssetSpec::Hip3Perp(dex_name, symbol) => {
+            let dexs = client.perp_dexs().await?;
+            let dex = dexs
+                .iter()
+                .find(|d| d.name().eq_ignore_ascii_case(dex_name))
+                .ok_or_else(|| anyhow::anyhow!("HIP3 DEX '{}' not found", dex_name))?;
+
+            let perps = client.perps_from(dex.clone()).await?;
+            let index = find_perp_index_with_dex(&perps, symbol, Some(dex_name))?;
+            (perps, index)
+        }
+    };
+
+    Ok(perps.swap_remove(index))
+}
+
 /// Find a perpetual market index by symbol with fuzzy matching suggestions.
 fn find_perp_index(perps: &[PerpMarket], symbol: &str) -> anyhow::Result<usize> {
     // First try exact match
@@ -0,0 +1,430 @@
+//! Locally maintained L2 order book.
+//!
+//! [`OrderBook`] keeps the levels of a single market up to date from the
+//! [`Incoming::L2Book`] websocket feed and provides common depth calculations.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use hypersdk::hypercore::{self, OrderBook, PriceTick, types::*};
+//! use futures::StreamExt;
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let mut ws = hypercore::mainnet_ws();
+//! ws.subscribe(Subscription::l2_book("BTC"));
+//!
+//! let mut book = OrderBook::new("BTC").with_tick(PriceTick::for_perp(5));
+//!
+//! while let Some(event) = ws.next().await {
+//!     if !book.handle(&event) {
+//!         continue;
+//!     }
+//!
+//!     println!("mid {:?} imbalance {:?}", book.mid(), book.imbalance(5));
+//!     println!("vwap to buy 1 BTC: {:?}", book.vwap(Side::Bid, 1.into()));
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::BTreeMap;
+
+use rust_decimal::Decimal;
+
+use crate::hypercore::{
+    PriceTick,
+    types::{BookLevel, Incoming, L2Book, Side},
+    ws::Event,
+};
+
+/// L2 order book for a single market.
+///
+/// Hyperliquid publishes the top levels of the book as full snapshots, so each
+/// [`L2Book`] replaces the stored levels. Messages older than the last applied
+/// one (by `time`) are ignored.
+///
+/// The book is cleared on [`Event::Connected`] and [`Event::Disconnected`] so that
+/// stale levels are never used after a reconnection; it becomes valid again once the
+/// next snapshot arrives (see [`is_synced`](Self::is_synced)).
+///
+/// # Sides
+///
+/// Methods that inspect resting liquidity ([`depth_at`](Self::depth_at),
+/// [`cumulative_depth`](Self::cumulative_depth)) take the **book side**: [`Side::Bid`]
+/// for bids and [`Side::Ask`] for asks.
+///
+/// Methods that simulate execution ([`vwap`](Self::vwap), [`impact_price`](Self::impact_price))
+/// take the **taker side**: [`Side::Bid`] buys from the asks, [`Side::Ask`] sells into the bids.
+#[derive(Debug, Clone)]
+pub struct OrderBook {
+    coin: String,
+    time: u64,
+    synced: bool,
+    tick: Option<PriceTick>,
+    bids: BTreeMap<Decimal, BookLevel>,
+    asks: BTreeMap<Decimal, BookLevel>,
+}
+
+impl OrderBook {
+    /// Creates an empty book for `coin`.
+    pub fn new(coin: impl Into<String>) -> Self {
+        Self {
+            coin: coin.into(),
+            time: 0,
+            synced: false,
+            tick: None,
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the price tick table used to round query prices and impact prices.
+    ///
+    /// Use [`PerpMarket::tick_table`](crate::hypercore::PerpMarket::tick_table) or
+    /// [`SpotMarket::tick_table`](crate::hypercore::SpotMarket::tick_table).
+    #[must_use]
+    pub fn with_tick(mut self, tick: PriceTick) -> Self {
+        self.tick = Some(tick);
+        self
+    }
+
+    /// Returns the market symbol of this book.
+    #[must_use]
+    pub fn coin(&self) -> &str {
+        &self.coin
+    }
+
+    /// Returns the timestamp in milliseconds of the last applied update.
+    #[must_use]
+    pub fn time(&self) -> u64 {
+        self.time
+    }
+
+    /// Returns true if a snapshot has been applied since the last reset.
+    #[must_use]
+    pub fn is_synced(&self) -> bool {
+        self.synced
+    }
+
+    /// Clears all levels and waits for the next snapshot.
+    pub fn reset(&mut self) {
+        self.time = 0;
+        self.synced = false;
+        self.bids.clear();
+        self.asks.clear();
+    }
+
+    /// Processes a websocket event.
+    ///
+    /// Resets the book on connection changes and applies [`Incoming::L2Book`] messages
+    /// for this book's coin. Returns true if the book changed.
+    pub fn handle(&mut self, event: &Event) -> bool {
+        match event {
+            Event::Connected | Event::Disconnected => {
+                let changed = self.synced;
+                self.reset();
+                changed
+            }
+            Event::Message(Incoming::L2Book(book)) => self.apply(book),
+            Event::Message(_) | Event::SubscriptionError { .. } => false,
+        }
+    }
+
+    /// Applies an L2 book update.
+    ///
+    /// Returns false if the update belongs to another coin or is older than the current state.
+    pub fn apply(&mut self, book: &L2Book) -> bool {
+        if book.coin != self.coin || (self.synced && book.time < self.time) {
+            return false;
+        }
+
+        self.bids.clear();
+        self.asks.clear();
+        for level in book.bids().iter().filter(|level| !level.sz.is_zero()) {
+            self.bids.insert(level.px, *level);
+        }
+        for level in book.asks().iter().filter(|level| !level.sz.is_zero()) {
+            self.asks.insert(level.px, *level);
+        }
+
+        self.time = book.time;
+        self.synced = true;
+        true
+    }
+
+    /// Returns the bid levels, from highest to lowest price.
+    pub fn bids(&self) -> impl Iterator<Item = &BookLevel> {
+        self.bids.values().rev()
+    }
+
+    /// Returns the ask levels, from lowest to highest price.
+    pub fn asks(&self) -> impl Iterator<Item = &BookLevel> {
+        self.asks.values()
+    }
+
+    /// Returns the levels of a book side, from best to worst price.
+    pub fn levels(&self, side: Side) -> Box<dyn Iterator<Item = &BookLevel> + '_> {
+        match side {
+            Side::Bid => Box::new(self.bids()),
+            Side::Ask => Box::new(self.asks()),
+        }
+    }
+
+    /// Returns the best bid level, if available.
+    #[must_use]
+    pub fn best_bid(&self) -> Option<&BookLevel> {
+        self.bids().next()
+    }
+
+    /// Returns the best ask level, if available.
+    #[must_use]
+    pub fn best_ask(&self) -> Option<&BookLevel> {
+        self.asks().next()
+    }
+
+    /// Returns the mid price (average of best bid and ask), if both are available.
+    #[must_use]
+    pub fn mid(&self) -> Option<Decimal> {
+        let bid = self.best_bid()?;
+        let ask = self.best_ask()?;
+        Some((bid.px + ask.px) / Decimal::TWO)
+    }
+
+    /// Returns the spread (best ask - best bid), if both are available.
+    #[must_use]
+    pub fn spread(&self) -> Option<Decimal> {
+        let bid = self.best_bid()?;
+        let ask = self.best_ask()?;
+        Some(ask.px - bid.px)
+    }
+
+    /// Returns the size resting at `px` on the given book side.
+    ///
+    /// The price is rounded to a valid tick first when a tick table is set.
+    #[must_use]
+    pub fn depth_at(&self, side: Side, px: Decimal) -> Decimal {
+        let px = self.round(px);
+        let levels = match side {
+            Side::Bid => &self.bids,
+            Side::Ask => &self.asks,
+        };
+        levels.get(&px).map(|level| level.sz).unwrap_or_default()
+    }
+
+    /// Returns the total size resting at `px` or better on the given book side.
+    ///
+    /// For bids this is every level priced at or above `px`, for asks every level
+    /// priced at or below `px`.
+    #[must_use]
+    pub fn cumulative_depth(&self, side: Side, px: Decimal) -> Decimal {
+        let px = self.round(px);
+        match side {
+            Side::Bid => self.bids.range(px..).map(|(_, level)| level.sz).sum(),
+            Side::Ask => self.asks.range(..=px).map(|(_, level)| level.sz).sum(),
+        }
+    }
+
+    /// Returns the volume-weighted average price of taking `sz` from the book.
+    ///
+    /// `side` is the taker side. Returns `None` if `sz` is not positive or the
+    /// visible book is not deep enough.
+    #[must_use]
+    pub fn vwap(&self, side: Side, sz: Decimal) -> Option<Decimal> {
+        let (filled, notional, _) = self.sweep(side, sz, |level| level.sz)?;
+        Some(notional / filled)
+    }
+
+    /// Returns the price a taker pays on average to trade `notional` (in quote units).
+    ///
+    /// `side` is the taker side. The result is rounded to a valid tick away from
+    /// the taker when a tick table is set, so it can be used as a limit price.
+    /// Returns `None` if `notional` is not positive or the visible book is not deep enough.
+    #[must_use]
+    pub fn impact_price(&self, side: Side, notional: Decimal) -> Option<Decimal> {
+        let (filled, notional, _) = self.sweep(side, notional, |level| level.px * level.sz)?;
+        let px = notional / filled;
+        match self.tick {
+            Some(tick) => tick.round_by_side(side, px, false),
+            None => Some(px),
+        }
+    }
+
+    /// Returns the order book imbalance over the top `depth` levels.
+    ///
+    /// The result is `(bid_sz - ask_sz) / (bid_sz + ask_sz)`, ranging from `-1`
+    /// (only asks) to `1` (only bids). Returns `None` if the book is empty.
+    #[must_use]
+    pub fn imbalance(&self, depth: usize) -> Option<Decimal> {
+        let bid_sz: Decimal = self.bids().take(depth).map(|level| level.sz).sum();
+        let ask_sz: Decimal = self.asks().take(depth).map(|level| level.sz).sum();
+        let total = bid_sz + ask_sz;
+        if total.is_zero() {
+            return None;
+        }
+        Some((bid_sz - ask_sz) / total)
+    }
+
+    /// Walks the opposite book side until `amount` is consumed.
+    ///
+    /// `measure` converts a level into the unit of `amount` (size or notional).
+    /// Returns the filled size, the filled notional and the worst price touched.
+    fn sweep(
+        &self,
+        side: Side,
+        amount: Decimal,
+        measure: impl Fn(&BookLevel) -> Decimal,
+    ) -> Option<(Decimal, Decimal, Decimal)> {
+        if amount <= Decimal::ZERO {
+            return None;
+        }
+
+        let opposite = match side {
+            Side::Bid => Side::Ask,
+            Side::Ask => Side::Bid,
+        };
+
+        let mut remaining = amount;
+        let mut filled = Decimal::ZERO;
+        let mut notional = Decimal::ZERO;
+        for level in self.levels(opposite) {
+            let available = measure(level);
+            let ratio = if available <= remaining {
+                Decimal::ONE
+            } else {
+                remaining / available
+            };
+            let sz = level.sz * ratio;
+            filled += sz;
+            notional += sz * level.px;
+            remaining -= available.min(remaining);
+
+            if remaining.is_zero() {
+                return Some((filled, notional, level.px));
+            }
+        }
+
+        None
+    }
+
+    fn round(&self, px: Decimal) -> Decimal {
+        self.tick
+            .and_then(|tick| tick.round(px))
+            .unwrap_or(px)
+            .normalize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::dec;
+
+    use super::*;
+
+    fn level(px: Decimal, sz: Decimal) -> BookLevel {
+        BookLevel { px, sz, n: 1 }
+    }
+
+    fn snapshot(time: u64) -> L2Book {
+        L2Book {
+            coin: "BTC".into(),
+            time,
+            snapshot: false,
+            levels: [
+                vec![
+                    level(dec!(100), dec!(1)),
+                    level(dec!(99), dec!(2)),
+                    level(dec!(98), dec!(3)),
+                ],
+                vec![
+                    level(dec!(101), dec!(1)),
+                    level(dec!(102), dec!(1)),
+                    level(dec!(103), dec!(4)),
+                ],
+            ],
+        }
+    }
+
+    #[test]
+    fn test_apply_and_sequencing() {
+        let mut book = OrderBook::new("BTC");
+        assert!(!book.is_synced());
+        assert!(book.apply(&snapshot(10)));
+        assert_eq!(book.best_bid().unwrap().px, dec!(100));
+        assert_eq!(book.best_ask().unwrap().px, dec!(101));
+        assert_eq!(book.mid(), Some(dec!(100.5)));
+        assert_eq!(book.spread(), Some(dec!(1)));
+
+        // stale update is ignored
+        let mut stale = snapshot(5);
+        stale.levels[0].clear();
+        assert!(!book.apply(&stale));
+        assert_eq!(book.bids().count(), 3);
+
+        // other coins are ignored
+        let mut other = snapshot(20);
+        other.coin = "ETH".into();
+        assert!(!book.apply(&other));
+        assert_eq!(book.time(), 10);
+    }
+
+    #[test]
+    fn test_reset_on_connection_events() {
+        let mut book = OrderBook::new("BTC");
+        assert!(book.handle(&Event::Message(Incoming::L2Book(snapshot(10)))));
+        assert!(book.handle(&Event::Disconnected));
+        assert!(!book.is_synced());
+        assert!(book.best_bid().is_none());
+
+        // a snapshot older than the pre-disconnect state is accepted after a reset
+        assert!(book.handle(&Event::Message(Incoming::L2Book(snapshot(3)))));
+        assert!(book.handle(&Event::Connected));
+        assert!(!book.handle(&Event::Connected));
+    }
+
+    #[test]
+    fn test_depth() {
+        let mut book = OrderBook::new("BTC").with_tick(PriceTick::for_perp(5));
+        book.apply(&snapshot(1));
+
+        assert_eq!(book.depth_at(Side::Bid, dec!(99)), dec!(2));
+        // rounded to the nearest tick
+        assert_eq!(book.depth_at(Side::Bid, dec!(99.04)), dec!(2));
+        assert_eq!(book.depth_at(Side::Ask, dec!(99)), dec!(0));
+
+        assert_eq!(book.cumulative_depth(Side::Bid, dec!(99)), dec!(3));
+        assert_eq!(book.cumulative_depth(Side::Ask, dec!(102)), dec!(2));
+        assert_eq!(book.cumulative_depth(Side::Ask, dec!(50)), dec!(0));
+    }
+
+    #[test]
+    fn test_vwap_and_impact() {
+        let mut book = OrderBook::new("BTC").with_tick(PriceTick::for_perp(5));
+        book.apply(&snapshot(1));
+
+        // buy 3: 1@101 + 1@102 + 1@103
+        assert_eq!(book.vwap(Side::Bid, dec!(3)), Some(dec!(102)));
+        // sell 2: 1@100 + 1@99
+        assert_eq!(book.vwap(Side::Ask, dec!(2)), Some(dec!(99.5)));
+        assert_eq!(book.vwap(Side::Bid, dec!(7)), None);
+        assert_eq!(book.vwap(Side::Bid, dec!(0)), None);
+
+        // buy 203 notional: 1@101 + 1@102
+        assert_eq!(book.impact_price(Side::Bid, dec!(203)), Some(dec!(101.5)));
+        // sell 149.5 notional: 1@100 + 0.5@99 = 1.5 for 149.5, rounded down to a 0.1 tick
+        assert_eq!(book.impact_price(Side::Ask, dec!(149.5)), Some(dec!(99.6)));
+    }
+
+    #[test]
+    fn test_imbalance() {
+        let mut book = OrderBook::new("BTC");
+        assert_eq!(book.imbalance(5), None);
+        book.apply(&snapshot(1));
+
+        // top 1: 1 vs 1
+        assert_eq!(book.imbalance(1), Some(dec!(0)));
+        // all: 6 bids vs 6 asks
+        assert_eq!(book.imbalance(3), Some(dec!(0)));
+        // top 2: 3 bids vs 2 asks
+        assert_eq!(book.imbalance(2), Some(dec!(0.2)));
+    }
+}
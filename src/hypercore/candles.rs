@@ -0,0 +1,312 @@
+//! Candle aggregation.
+//!
+//! Hyperliquid streams the candle in progress: the same [`Candle`] is sent again
+//! every time it changes, and there is no message when it closes. [`CandleAggregator`]
+//! builds candles of a larger interval from a candle feed (e.g. 4h candles from the
+//! 1m feed) and reports when a candle closes.
+//!
+//! A candle is closed when a candle of the next period arrives, or locally with
+//! [`CandleAggregator::tick`] once the end of its period, computed with
+//! [`CandleInterval::to_duration`], has passed.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use futures::StreamExt;
+//! use hypersdk::hypercore::{self, CandleAggregator, CandleEvent, types::CandleInterval};
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let ws = hypercore::mainnet_ws();
+//! let mut aggregator =
+//!     CandleAggregator::new("BTC", CandleInterval::OneMinute, CandleInterval::FourHours)?;
+//!
+//! let mut candles = ws.candles("BTC", CandleInterval::OneMinute);
+//! while let Some(candle) = candles.next().await {
+//!     for event in aggregator.update(&candle) {
+//!         if let CandleEvent::Closed(candle) = event {
+//!             println!("4h candle closed at {}", candle.close);
+//!         }
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::BTreeMap;
+
+use crate::hypercore::{
+    Error,
+    error::Result,
+    types::{Candle, CandleInterval, Incoming, Subscription},
+    ws::Event,
+};
+
+/// Change reported by a [`CandleAggregator`].
+#[derive(Debug, Clone)]
+pub enum CandleEvent {
+    /// The candle in progress changed.
+    Update(Candle),
+    /// A candle closed and won't change anymore.
+    Closed(Candle),
+}
+
+/// Builds candles of a target interval from a feed of smaller candles.
+///
+/// Periods are aligned on the Unix epoch, like the exchange does for intervals up
+/// to one day; larger intervals are not supported.
+#[derive(Debug, Clone)]
+pub struct CandleAggregator {
+    coin: String,
+    source: CandleInterval,
+    target: CandleInterval,
+    period: u64,
+    // open time of the current period
+    start: Option<u64>,
+    // candles of the current period by open time
+    parts: BTreeMap<u64, Candle>,
+    // everything before is closed
+    closed_until: u64,
+}
+
+impl CandleAggregator {
+    /// Creates an aggregator building `target` candles of `coin` from `source` candles.
+    ///
+    /// `target` must be a multiple of `source` and at most one day.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidParameter`] if the intervals are not supported.
+    pub fn new(
+        coin: impl Into<String>,
+        source: CandleInterval,
+        target: CandleInterval,
+    ) -> Result<Self> {
+        let source_ms = source.to_duration().as_millis() as u64;
+        let period = target.to_duration().as_millis() as u64;
+        if period > CandleInterval::OneDay.to_duration().as_millis() as u64 {
+            return Err(Error::InvalidParameter(format!(
+                "intervals larger than 1d are not supported, got {target}"
+            )));
+        }
+        if period % source_ms != 0 {
+            return Err(Error::InvalidParameter(format!(
+                "{target} is not a multiple of {source}"
+            )));
+        }
+
+        Ok(Self {
+            coin: coin.into(),
+            source,
+            target,
+            period,
+            start: None,
+            parts: BTreeMap::new(),
+            closed_until: 0,
+        })
+    }
+
+    /// Returns the coin of the candles.
+    #[must_use]
+    pub fn coin(&self) -> &str {
+        &self.coin
+    }
+
+    /// Returns the interval of the candles built.
+    #[must_use]
+    pub fn interval(&self) -> CandleInterval {
+        self.target
+    }
+
+    /// Returns the subscription feeding the aggregator.
+    #[must_use]
+    pub fn subscription(&self) -> Subscription {
+        Subscription::Candle {
+            coin: self.coin.clone(),
+            interval: self.source,
+        }
+    }
+
+    /// Returns the candle in progress.
+    #[must_use]
+    pub fn current(&self) -> Option<Candle> {
+        let start = self.start?;
+        let mut parts = self.parts.values();
+        let first = parts.next()?;
+
+        let mut candle = Candle {
+            open_time: start,
+            close_time: start + self.period - 1,
+            coin: self.coin.clone(),
+            interval: self.target.to_string(),
+            open: first.open,
+            high: first.high,
+            low: first.low,
+            close: first.close,
+            volume: first.volume,
+            num_trades: first.num_trades,
+        };
+        for part in parts {
+            candle.high = candle.high.max(part.high);
+            candle.low = candle.low.min(part.low);
+            candle.close = part.close;
+            candle.volume += part.volume;
+            candle.num_trades += part.num_trades;
+        }
+
+        Some(candle)
+    }
+
+    /// Applies a source candle.
+    ///
+    /// Candles of other coins or intervals and candles of closed periods are ignored.
+    /// Returns the candle closed by this update, if any, followed by the candle in progress.
+    pub fn update(&mut self, candle: &Candle) -> Vec<CandleEvent> {
+        if candle.coin != self.coin
+            || candle.interval != self.source.to_string()
+            || candle.open_time < self.closed_until
+        {
+            return vec![];
+        }
+
+        let start = candle.open_time - candle.open_time % self.period;
+        let mut events = vec![];
+        if self.start.is_some_and(|current| current != start) {
+            events.extend(self.close().map(CandleEvent::Closed));
+        }
+
+        self.start = Some(start);
+        self.parts.insert(candle.open_time, candle.clone());
+        events.extend(self.current().map(CandleEvent::Update));
+        events
+    }
+
+    /// Closes the candle in progress if its period ended before `now` (in milliseconds).
+    pub fn tick(&mut self, now: u64) -> Option<Candle> {
+        let start = self.start?;
+        if now < start + self.period {
+            return None;
+        }
+        self.close()
+    }
+
+    /// Processes a websocket event.
+    ///
+    /// Applies [`Incoming::Candle`] messages for this aggregator's source candles.
+    pub fn handle(&mut self, event: &Event) -> Vec<CandleEvent> {
+        match event {
+            Event::Message(Incoming::Candle(candle)) => self.update(candle),
+            _ => vec![],
+        }
+    }
+
+    fn close(&mut self) -> Option<Candle> {
+        let candle = self.current();
+        if let Some(start) = self.start.take() {
+            self.closed_until = start + self.period;
+        }
+        self.parts.clear();
+        candle
+    }
+}
+
+impl CandleEvent {
+    /// Returns the candle of the event.
+    #[must_use]
+    pub fn candle(&self) -> &Candle {
+        match self {
+            CandleEvent::Update(candle) | CandleEvent::Closed(candle) => candle,
+        }
+    }
+
+    /// Returns true if the candle closed.
+    #[must_use]
+    pub fn is_closed(&self) -> bool {
+        matches!(self, CandleEvent::Closed(_))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::{Decimal, dec};
+
+    use super::*;
+
+    const MINUTE: u64 = 60_000;
+
+    fn candle(open_time: u64, open: Decimal, close: Decimal, volume: Decimal) -> Candle {
+        Candle {
+            open_time,
+            close_time: open_time + MINUTE - 1,
+            coin: "BTC".into(),
+            interval: "1m".into(),
+            open,
+            high: open.max(close),
+            low: open.min(close),
+            close,
+            volume,
+            num_trades: 1,
+        }
+    }
+
+    #[test]
+    fn test_aggregate() {
+        assert!(
+            CandleAggregator::new(
+                "BTC",
+                CandleInterval::FifteenMinutes,
+                CandleInterval::OneHour
+            )
+            .is_ok()
+        );
+        assert!(matches!(
+            CandleAggregator::new("BTC", CandleInterval::OneMinute, CandleInterval::OneWeek),
+            Err(Error::InvalidParameter(_))
+        ));
+
+        let mut aggregator = CandleAggregator::new(
+            "BTC",
+            CandleInterval::OneMinute,
+            CandleInterval::FiveMinutes,
+        )
+        .unwrap();
+        let base = 1_700_000_100_000 - 1_700_000_100_000 % (5 * MINUTE);
+
+        aggregator.update(&candle(base, dec!(100), dec!(101), dec!(1)));
+        // the same minute again, updated
+        aggregator.update(&candle(base, dec!(100), dec!(102), dec!(2)));
+        let events = aggregator.update(&candle(base + MINUTE, dec!(102), dec!(98), dec!(3)));
+        assert_eq!(events.len(), 1);
+        let current = events[0].candle();
+        assert!(!events[0].is_closed());
+        assert_eq!(current.open_time, base);
+        assert_eq!(current.close_time, base + 5 * MINUTE - 1);
+        assert_eq!(current.interval, "5m");
+        assert_eq!(current.open, dec!(100));
+        assert_eq!(current.high, dec!(102));
+        assert_eq!(current.low, dec!(98));
+        assert_eq!(current.close, dec!(98));
+        assert_eq!(current.volume, dec!(5));
+        assert_eq!(current.num_trades, 2);
+
+        // next period closes the candle
+        let events = aggregator.update(&candle(base + 5 * MINUTE, dec!(98), dec!(99), dec!(1)));
+        assert_eq!(events.len(), 2);
+        assert!(events[0].is_closed());
+        assert_eq!(events[0].candle().close, dec!(98));
+        assert_eq!(events[1].candle().open_time, base + 5 * MINUTE);
+
+        // late updates of a closed period are ignored
+        assert!(
+            aggregator
+                .update(&candle(base + 4 * MINUTE, dec!(1), dec!(1), dec!(1)))
+                .is_empty()
+        );
+
+        // closed locally once the period is over
+        assert!(aggregator.tick(base + 10 * MINUTE - 1).is_none());
+        let closed = aggregator.tick(base + 10 * MINUTE).unwrap();
+        assert_eq!(closed.open_time, base + 5 * MINUTE);
+        assert!(aggregator.current().is_none());
+        assert!(aggregator.tick(base + 20 * MINUTE).is_none());
+    }
+}
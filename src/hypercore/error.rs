@@ -7,6 +7,9 @@ use std::fmt;
 
 use alloy::signers::Error as SignerError;
 
+/// Result type for HyperCore operations.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
 /// Error type for HyperCore operations.
 ///
 /// Covers all error cases that can occur when interacting with the Hyperliquid API,
@@ -54,11 +57,37 @@ pub enum Error {
     /// The provided Ethereum address is not valid hex or has wrong checksum.
     InvalidAddress(String),
 
+    /// Invalid request parameters, rejected before anything is sent.
+    ///
+    /// Common causes: unsupported candle intervals or book aggregation settings.
+    InvalidParameter(String),
+
     /// Timeout waiting for a response.
     ///
     /// The operation took too long and was cancelled.
     Timeout,
 
+    /// The server rejected the request because of rate limiting.
+    ///
+    /// Returned on HTTP 429 and when the exchange rejects an action because the
+    /// address ran out of requests.
+    /// See [`RateLimiter`](crate::hypercore::RateLimiter) to stay within the limits.
+    RateLimited,
+
+    /// The exchange rejected the action because the account doesn't have enough margin.
+    InsufficientMargin(String),
+
+    /// The exchange rejected the order because its price is not a multiple of the tick size.
+    InvalidTick(String),
+
+    /// The exchange rejected the order because its notional is below the minimum value.
+    MinNotional(String),
+
+    /// The exchange rejected the action because of its nonce.
+    ///
+    /// Nonces must be unique and within a time window of the exchange time.
+    InvalidNonce(String),
+
     /// Other error not covered by specific variants.
     ///
     /// This is a catch-all for unexpected errors. If you see this frequently,
@@ -67,6 +96,38 @@ pub enum Error {
 }
 
 impl Error {
+    /// Classifies an error message returned by the exchange.
+    ///
+    /// Known rejections are mapped to their own variant, everything else
+    /// becomes [`Error::Api`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hypersdk::hypercore::Error;
+    ///
+    /// let err = Error::from_api("Insufficient margin to place order. asset=0");
+    /// assert!(matches!(err, Error::InsufficientMargin(_)));
+    /// ```
+    #[must_use]
+    pub fn from_api(msg: impl Into<String>) -> Self {
+        let msg = msg.into();
+        let lower = msg.to_lowercase();
+        if lower.contains("insufficient margin") {
+            Error::InsufficientMargin(msg)
+        } else if lower.contains("divisible by tick size") {
+            Error::InvalidTick(msg)
+        } else if lower.contains("minimum value") {
+            Error::MinNotional(msg)
+        } else if lower.contains("too many") || lower.contains("rate limit") {
+            Error::RateLimited
+        } else if lower.contains("nonce") {
+            Error::InvalidNonce(msg)
+        } else {
+            Error::Api(msg)
+        }
+    }
+
     /// Returns true if this error is retryable.
     ///
     /// Network timeouts and transient errors may succeed on retry.
@@ -88,7 +149,7 @@ impl Error {
     pub fn is_retryable(&self) -> bool {
         matches!(
             self,
-            Error::Network(_) | Error::Timeout | Error::WebSocket(_)
+            Error::Network(_) | Error::Timeout | Error::WebSocket(_) | Error::RateLimited
         )
     }
 
@@ -101,7 +162,14 @@ impl Error {
     /// Returns true if this is an API rejection.
     #[must_use]
     pub fn is_api_error(&self) -> bool {
-        matches!(self, Error::Api(_))
+        matches!(
+            self,
+            Error::Api(_)
+                | Error::InsufficientMargin(_)
+                | Error::InvalidTick(_)
+                | Error::MinNotional(_)
+                | Error::InvalidNonce(_)
+        )
     }
 }
 
@@ -115,7 +183,13 @@ impl fmt::Display for Error {
             Error::InvalidOrder { message } => write!(f, "Invalid order: {}", message),
             Error::WebSocket(e) => write!(f, "WebSocket error: {}", e),
             Error::InvalidAddress(e) => write!(f, "Invalid address: {}", e),
+            Error::InvalidParameter(e) => write!(f, "Invalid parameter: {}", e),
             Error::Timeout => write!(f, "Operation timed out"),
+            Error::RateLimited => write!(f, "Rate limited"),
+            Error::InsufficientMargin(e) => write!(f, "Insufficient margin: {}", e),
+            Error::InvalidTick(e) => write!(f, "Invalid tick: {}", e),
+            Error::MinNotional(e) => write!(f, "Below minimum notional: {}", e),
+            Error::InvalidNonce(e) => write!(f, "Invalid nonce: {}", e),
             Error::Other(e) => write!(f, "{}", e),
         }
     }
@@ -221,6 +295,11 @@ impl<T> ActionError<T> {
     pub fn into_ids(self) -> Vec<T> {
         self.ids
     }
+
+    /// Classifies the error message, see [`Error::from_api`].
+    pub fn kind(&self) -> Error {
+        Error::from_api(self.err.as_str())
+    }
 }
 
 impl<T> fmt::Display for ActionError<T>
@@ -233,3 +312,47 @@ where
 }
 
 impl<T> std::error::Error for ActionError<T> where T: fmt::Display + fmt::Debug {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_rejections() {
+        assert!(matches!(
+            Error::from_api("Insufficient margin to place order. asset=0"),
+            Error::InsufficientMargin(_)
+        ));
+        assert!(matches!(
+            Error::from_api("Price must be divisible by tick size. asset=0"),
+            Error::InvalidTick(_)
+        ));
+        assert!(matches!(
+            Error::from_api("Order must have minimum value of $10. asset=0"),
+            Error::MinNotional(_)
+        ));
+        assert!(matches!(
+            Error::from_api(
+                "Too many cumulative requests sent (10010) for cumulative volume traded $1000.0"
+            ),
+            Error::RateLimited
+        ));
+        assert!(matches!(
+            Error::from_api("Invalid nonce: duplicate nonce"),
+            Error::InvalidNonce(_)
+        ));
+        // prices too far from the oracle are not tick errors
+        assert!(matches!(
+            Error::from_api("Order has invalid price."),
+            Error::Api(_)
+        ));
+        assert!(matches!(
+            Error::from_api("Vault not registered"),
+            Error::Api(_)
+        ));
+
+        let err = ActionError::new(vec![1u64], "Insufficient margin to place order.".into());
+        assert!(err.kind().is_api_error());
+        assert!(Error::from_api("Too many requests").is_retryable());
+    }
+}
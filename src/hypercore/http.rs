@@ -38,9 +38,33 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! ## Handle Rejections
+//!
+//! Methods return the typed [`Error`](super::Error); known exchange rejections
+//! have their own variant.
+//!
+//! ```no_run
+//! use hypersdk::hypercore::{self, Error, PrivateKeySigner, api::UpdateLeverage};
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let client = hypercore::mainnet();
+//! let signer: PrivateKeySigner = "your_key".parse()?;
+//! let update = UpdateLeverage { asset: 0, is_cross: true, leverage: 10 };
+//!
+//! match client.update_leverage(&signer, update, 0, None, None).await {
+//!     Ok(()) => println!("updated"),
+//!     Err(Error::InsufficientMargin(msg)) => println!("not enough margin: {msg}"),
+//!     Err(err) if err.is_retryable() => println!("try again: {err}"),
+//!     Err(err) => return Err(err.into()),
+//! }
+//! # Ok(())
+//! # }
+//! ```
 
 use std::{
     collections::{HashMap, VecDeque},
+    future::Future,
     time::Duration,
 };
 
@@ -48,30 +72,158 @@ use alloy::{
     primitives::Address,
     signers::{Signer, SignerSync},
 };
-use anyhow::{Result, anyhow};
 use chrono::{DateTime, Utc};
+use futures::{Stream, StreamExt, TryStreamExt, stream};
+use reqwest::StatusCode;
 use rust_decimal::Decimal;
 use rust_decimal::prelude::ToPrimitive;
-use serde::Deserialize;
+use serde::{Deserialize, de::DeserializeOwned};
+use tokio::time::sleep;
 use url::Url;
 
-use super::{AssetTarget, signing::*};
+use super::{
+    AssetTarget, Error, RateLimiter, RetryPolicy, error::Result, ratelimit::action_weight,
+    signing::*,
+};
 use crate::hypercore::{
     ActionError, ApiAgent, CandleInterval, Chain, Cloid, Dex, MultiSigConfig, OidOrCloid,
     PerpMarket, Signature, SpotMarket, SpotToken,
     api::{
-        Action, ActionRequest, ApproveAgent, ConvertToMultiSigUser, OkResponse, Response,
-        SignersConfig, VaultTransfer,
+        Action, ActionRequest, ApproveAgent, ApproveBuilderFee, ConvertToMultiSigUser, OkResponse,
+        Response, SignersConfig, UpdateIsolatedMargin, UpdateLeverage, VaultTransfer,
     },
-    mainnet_url, testnet_url,
+    mainnet_url,
+    order_builder::{
+        Bracket, BracketStatus, MarketOrder, MarketPrice, OrderBuilder, PositionTpsl, Slippage,
+    },
+    testnet_url,
     types::{
-        BasicOrder, BatchCancel, BatchCancelCloid, BatchModify, BatchOrder, ClearinghouseState,
-        Fill, FundingRate, InfoRequest, OrderResponseStatus, OrderUpdate, ScheduleCancel,
-        SendAsset, SendToken, SpotSend, SubAccount, UsdSend, UserBalance, UserFees, UserRole,
-        UserVaultEquity, VaultDetails,
+        AssetContext, BasicOrder, BatchCancel, BatchCancelCloid, BatchModify, BatchOrder,
+        BookAggregation, Candle, ClearinghouseState, Fill, FundingRate, InfoRequest, L2Book,
+        LedgerUpdate, OrderGrouping, OrderRequest, OrderResponseStatus, OrderUpdate, PositionData,
+        ScheduleCancel, SendAsset, SendToken, Side, SpotAssetContext, SpotSend, SubAccount,
+        TwapCancel, TwapOrder, TwapOrderResponseStatus, UsdSend, UserBalance, UserFees,
+        UserFunding, UserRateLimit, UserRole, UserVaultEquity, VaultDetails,
     },
 };
 
+/// Maximum number of fills returned by the `userFillsByTime` info request.
+const FILLS_PAGE_SIZE: usize = 2000;
+
+/// Maximum number of records returned by the `fundingHistory`, `userFunding` and
+/// `userNonFundingLedgerUpdates` info requests.
+const LEDGER_PAGE_SIZE: usize = 500;
+
+/// Maximum number of candles returned by the `candleSnapshot` info request.
+const CANDLE_PAGE_SIZE: u64 = 5000;
+
+/// Streams every record from `start_time` up to `end_time` (excluded) of a time-ranged endpoint.
+///
+/// `fetch` requests the records from a start time and returns at most `page_size`
+/// of them. Full pages are followed from the time of their last record, so the
+/// records at that time are requested twice; `key` identifies the copies to drop.
+fn paginate<'a, T, K, F, Fut>(
+    start_time: u64,
+    end_time: Option<u64>,
+    page_size: usize,
+    time: fn(&T) -> u64,
+    key: fn(&T) -> K,
+    fetch: F,
+) -> impl Stream<Item = Result<T>> + 'a
+where
+    T: 'a,
+    K: PartialEq + 'a,
+    F: FnMut(u64) -> Fut + 'a,
+    Fut: Future<Output = Result<Vec<T>>> + 'a,
+{
+    // start of the next page and keys of the records already yielded at that time
+    let state = (Some(start_time), Vec::new(), fetch);
+    stream::try_unfold(state, move |(start, mut seen, mut fetch)| async move {
+        let Some(start) = start else {
+            return Ok::<_, Error>(None);
+        };
+
+        let mut page = fetch(start).await?;
+        let len = page.len();
+        page.sort_by_key(time);
+        page.retain(|record| end_time.is_none_or(|end| time(record) < end));
+        let Some(last) = page.last().map(time) else {
+            return Ok(None);
+        };
+        // the range is over when the page isn't full or goes past the end
+        let done = len < page_size || page.len() < len;
+
+        page.retain(|record| time(record) != start || !seen.contains(&key(record)));
+        if last != start {
+            seen.clear();
+        }
+        seen.extend(page.iter().filter(|record| time(record) == last).map(key));
+
+        let next = if done {
+            None
+        } else if last == start {
+            log::warn!("More than {page_size} records at {start}, some may be missing");
+            Some(start + 1)
+        } else {
+            Some(last)
+        };
+
+        Ok(Some((page, (next, seen, fetch))))
+    })
+    .map_ok(|page| stream::iter(page.into_iter().map(Ok)))
+    .try_flatten()
+}
+
+/// Posts a signed action to `/exchange`, applying the rate limiter and retry policy.
+///
+/// Actions are only retried when the exchange can't have received them: on
+/// connection errors and `429 Too Many Requests`. A request that times out or
+/// fails after being sent may have been executed, and resending it with the same
+/// nonce would be rejected as [`Error::InvalidNonce`] even though the action is
+/// live, so these errors are returned without retrying.
+async fn post_action(
+    http_client: &reqwest::Client,
+    mut url: Url,
+    limiter: Option<&RateLimiter>,
+    retry: Option<RetryPolicy>,
+    chain: Chain,
+    req: &ActionRequest,
+) -> Result<Response> {
+    url.set_path("/exchange");
+    if let Some(limiter) = limiter {
+        limiter.acquire_action(req, chain).await;
+    }
+
+    let mut attempt = 0;
+    loop {
+        if let Some(limiter) = limiter {
+            limiter.acquire(action_weight(&req.action)).await;
+        }
+
+        let err = match http_client.post(url.clone()).json(req).send().await {
+            Ok(res) if res.status() == StatusCode::TOO_MANY_REQUESTS => Error::RateLimited,
+            Ok(res) => {
+                let status = res.status();
+                let text = res.text().await?;
+                if !status.is_success() {
+                    return Err(Error::Api(format!("HTTP {status} body={text}")));
+                }
+                return Ok(serde_json::from_str(&text)?);
+            }
+            Err(err) if err.is_connect() => Error::from(err),
+            Err(err) => return Err(err.into()),
+        };
+
+        match retry.filter(|retry| retry.should_retry(&err, attempt)) {
+            Some(retry) => {
+                sleep(retry.backoff(attempt)).await;
+                attempt += 1;
+            }
+            None => return Err(err),
+        }
+    }
+}
+
 /// HTTP client for HyperCore API.
 ///
 /// Provides methods for trading, querying market data, managing positions,
@@ -89,6 +241,8 @@ pub struct Client {
     http_client: reqwest::Client,
     base_url: Url,
     chain: Chain,
+    limiter: Option<RateLimiter>,
+    retry: Option<RetryPolicy>,
 }
 
 impl Client {
@@ -130,6 +284,8 @@ impl Client {
             http_client,
             base_url,
             chain,
+            limiter: None,
+            retry: None,
         }
     }
 
@@ -157,6 +313,56 @@ impl Client {
         Self { http_client, ..self }
     }
 
+    /// Enables client-side rate limiting.
+    ///
+    /// Every request waits for its IP weight, and actions for the address budget
+    /// once it has been synced with [`sync_rate_limit`](Self::sync_rate_limit).
+    /// The limiter can be shared between clients running from the same IP.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hypersdk::hypercore::{self, RateLimiter};
+    ///
+    /// let client = hypercore::mainnet().with_rate_limiter(RateLimiter::default());
+    /// ```
+    #[must_use]
+    pub fn with_rate_limiter(self, limiter: RateLimiter) -> Self {
+        Self {
+            limiter: Some(limiter),
+            ..self
+        }
+    }
+
+    /// Enables retries of requests that fail with a retryable [`Error`](super::Error).
+    ///
+    /// Info requests are retried on any retryable error. Actions are only retried
+    /// when the request didn't reach the exchange, on connection errors and rate
+    /// limiting: after a timeout the action may have been executed, so the error is
+    /// returned and the caller has to check the outcome (e.g. with
+    /// [`order_status`](Self::order_status)) before sending it again.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hypersdk::hypercore::{self, RetryPolicy};
+    ///
+    /// let client = hypercore::mainnet().with_retry(RetryPolicy::default());
+    /// ```
+    #[must_use]
+    pub fn with_retry(self, retry: RetryPolicy) -> Self {
+        Self {
+            retry: Some(retry),
+            ..self
+        }
+    }
+
+    /// Returns the rate limiter of this client, if enabled.
+    #[must_use]
+    pub fn rate_limiter(&self) -> Option<&RateLimiter> {
+        self.limiter.as_ref()
+    }
+
     /// Returns the chain this client is configured for.
     #[must_use]
     pub const fn chain(&self) -> Chain {
@@ -213,7 +419,7 @@ impl Client {
     /// ```
     #[inline(always)]
     pub async fn perps(&self) -> Result<Vec<PerpMarket>> {
-        super::perp_markets(self.base_url.clone(), self.http_client.clone(), None).await
+        self.perp_markets(None).await
     }
 
     /// Fetches perpetual markets from a specific DEX.
@@ -248,7 +454,7 @@ impl Client {
     /// ```
     #[inline(always)]
     pub async fn perps_from(&self, dex: Dex) -> Result<Vec<PerpMarket>> {
-        super::perp_markets(self.base_url.clone(), self.http_client.clone(), Some(dex)).await
+        self.perp_markets(Some(dex)).await
     }
 
     /// Fetches all available perpetual futures DEXes.
@@ -272,7 +478,8 @@ impl Client {
     /// ```
     #[inline(always)]
     pub async fn perp_dexs(&self) -> Result<Vec<Dex>> {
-        super::perp_dexs(self.base_url.clone(), self.http_client.clone()).await
+        let dexes = self.info(&InfoRequest::PerpDexs).await?;
+        Ok(super::build_dexes(dexes))
     }
 
     /// Fetches all available spot markets.
@@ -294,7 +501,8 @@ impl Client {
     /// ```
     #[inline(always)]
     pub async fn spot(&self) -> Result<Vec<SpotMarket>> {
-        super::spot_markets(self.base_url.clone(), self.http_client.clone()).await
+        let data = self.info(&InfoRequest::SpotMeta).await?;
+        Ok(super::build_spot_markets(data))
     }
 
     /// Fetches all available spot tokens.
@@ -316,7 +524,104 @@ impl Client {
     /// ```
     #[inline(always)]
     pub async fn spot_tokens(&self) -> Result<Vec<SpotToken>> {
-        super::spot_tokens(self.base_url.clone(), self.http_client.clone()).await
+        let data: super::SpotTokens = self.info(&InfoRequest::SpotMeta).await?;
+        Ok(data.tokens.into_iter().map(SpotToken::from).collect())
+    }
+
+    /// Fetches all perpetual markets along with their asset contexts.
+    ///
+    /// Each market is joined with its current funding, open interest, mark/oracle
+    /// prices, premium and 24h volume in a single request.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hypersdk::hypercore;
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let client = hypercore::mainnet();
+    /// let perps = client.perps_with_ctxs().await?;
+    ///
+    /// for (market, ctx) in perps {
+    ///     println!("{}: mark {:?} funding {}", market.name, ctx.mark_px, ctx.funding);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub async fn perps_with_ctxs(&self) -> Result<Vec<(PerpMarket, AssetContext)>> {
+        self.perp_markets_with_ctxs(None).await
+    }
+
+    /// Fetches perpetual markets from a specific DEX along with their asset contexts.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use hypersdk::hypercore;
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let client = hypercore::mainnet();
+    ///
+    /// for dex in client.perp_dexs().await? {
+    ///     for (market, ctx) in client.perps_with_ctxs_from(dex).await? {
+    ///         println!("{}: OI {}", market.name, ctx.open_interest);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub async fn perps_with_ctxs_from(&self, dex: Dex) -> Result<Vec<(PerpMarket, AssetContext)>> {
+        self.perp_markets_with_ctxs(Some(dex)).await
+    }
+
+    /// Fetches all spot markets along with their asset contexts.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hypersdk::hypercore;
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let client = hypercore::mainnet();
+    /// let spots = client.spot_with_ctxs().await?;
+    ///
+    /// for (market, ctx) in spots {
+    ///     println!("{}: mid {:?}", market.symbol(), ctx.mid_px);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub async fn spot_with_ctxs(&self) -> Result<Vec<(SpotMarket, SpotAssetContext)>> {
+        let (data, ctxs) = self.info(&InfoRequest::SpotMetaAndAssetCtxs).await?;
+        Ok(super::join_spot_ctxs(super::build_spot_markets(data), ctxs))
+    }
+
+    /// Fetches the perpetual markets of `dex`, with the spot tokens to resolve the collateral.
+    async fn perp_markets(&self, dex: Option<Dex>) -> Result<Vec<PerpMarket>> {
+        let spot = self.info(&InfoRequest::SpotMeta).await?;
+        let data = self
+            .info(&InfoRequest::Meta {
+                dex: dex.as_ref().map(|dex| dex.name.clone()),
+            })
+            .await?;
+        Ok(super::build_perp_markets(data, &spot, dex.as_ref()))
+    }
+
+    /// Fetches the perpetual markets of `dex` along with their asset contexts.
+    async fn perp_markets_with_ctxs(
+        &self,
+        dex: Option<Dex>,
+    ) -> Result<Vec<(PerpMarket, AssetContext)>> {
+        let spot = self.info(&InfoRequest::SpotMeta).await?;
+        let (data, ctxs) = self
+            .info(&InfoRequest::MetaAndAssetCtxs {
+                dex: dex.as_ref().map(|dex| dex.name.clone()),
+            })
+            .await?;
+        let perps = super::build_perp_markets(data, &spot, dex.as_ref());
+        Ok(super::join_perp_ctxs(perps, ctxs)?)
     }
 
     /// Returns all open orders for a user.
@@ -343,19 +648,11 @@ impl Client {
         user: Address,
         dex_name: Option<String>,
     ) -> Result<Vec<BasicOrder>> {
-        let mut api_url = self.base_url.clone();
-        api_url.set_path("/info");
-
         let data = self
-            .http_client
-            .post(api_url)
-            .json(&InfoRequest::FrontendOpenOrders {
+            .info(&InfoRequest::FrontendOpenOrders {
                 user,
                 dex: dex_name,
             })
-            .send()
-            .await?
-            .json()
             .await?;
 
         Ok(data)
@@ -381,16 +678,51 @@ impl Client {
     /// # }
     /// ```
     pub async fn all_mids(&self, dex_name: Option<String>) -> Result<HashMap<String, Decimal>> {
-        let mut api_url = self.base_url.clone();
-        api_url.set_path("/info");
+        let data = self.info(&InfoRequest::AllMids { dex: dex_name }).await?;
+
+        Ok(data)
+    }
+
+    /// Returns an L2 book snapshot for a market.
+    ///
+    /// Pass a [`BookAggregation`] to have the levels aggregated by the server.
+    /// The parameters are checked before sending the request; use
+    /// [`BookAggregation::validate`] to also check them against the market's price tick.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hypersdk::hypercore::{self, types::BookAggregation};
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let client = hypercore::mainnet();
+    ///
+    /// let book = client.l2_book("BTC", None).await?;
+    /// println!("BTC mid: {:?}", book.mid());
+    ///
+    /// let aggregation = BookAggregation::sig_figs(5).with_mantissa(5);
+    /// let book = client.l2_book("BTC", Some(aggregation)).await?;
+    /// for bid in book.bids() {
+    ///     println!("{} @ {}", bid.sz, bid.px);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn l2_book(
+        &self,
+        coin: impl Into<String>,
+        aggregation: Option<BookAggregation>,
+    ) -> Result<L2Book> {
+        if let Some(aggregation) = &aggregation {
+            aggregation.check()?;
+        }
 
         let data = self
-            .http_client
-            .post(api_url)
-            .json(&InfoRequest::AllMids { dex: dex_name })
-            .send()
-            .await?
-            .json()
+            .info(&InfoRequest::L2Book {
+                coin: coin.into(),
+                n_sig_figs: aggregation.map(|aggregation| aggregation.n_sig_figs),
+                mantissa: aggregation.and_then(|aggregation| aggregation.mantissa),
+            })
             .await?;
 
         Ok(data)
@@ -398,73 +730,215 @@ impl Client {
 
     /// Returns the user's historical orders.
     pub async fn historical_orders(&self, user: Address) -> Result<Vec<BasicOrder>> {
-        let mut api_url = self.base_url.clone();
-        api_url.set_path("/info");
-
-        let data = self
-            .http_client
-            .post(api_url)
-            .json(&InfoRequest::HistoricalOrders { user })
-            .send()
-            .await?
-            .json()
-            .await?;
+        let data = self.info(&InfoRequest::HistoricalOrders { user }).await?;
 
         Ok(data)
     }
 
     /// Returns the user's fills.
     pub async fn user_fills(&self, user: Address) -> Result<Vec<Fill>> {
-        let mut api_url = self.base_url.clone();
-        api_url.set_path("/info");
-
-        let data = self
-            .http_client
-            .post(api_url)
-            .json(&InfoRequest::UserFills { user })
-            .send()
-            .await?
-            .json()
-            .await?;
+        let data = self.info(&InfoRequest::UserFills { user }).await?;
 
         Ok(data)
     }
 
     /// Returns the user's fills by time.
+    ///
+    /// At most 2000 fills are returned; use [`user_fills_by_time_stream`](Self::user_fills_by_time_stream)
+    /// to get every fill of a longer range.
     pub async fn user_fills_by_time(
         &self,
         user: Address,
         start_time: u64,
         end_time: Option<u64>,
     ) -> Result<Vec<Fill>> {
-        let mut api_url = self.base_url.clone();
-        api_url.set_path("/info");
-
         let data = self
-            .http_client
-            .post(api_url)
-            .json(&InfoRequest::UserFillsByTime {
+            .info(&InfoRequest::UserFillsByTime {
                 user,
                 start_time,
                 end_time,
             })
-            .send()
-            .await?
-            .json()
             .await?;
 
         Ok(data)
     }
 
+    /// Streams the user's fills from `start_time` to `end_time` (excluded), oldest first.
+    ///
+    /// Pages of [`user_fills_by_time`](Self::user_fills_by_time) are requested as
+    /// the stream is consumed. Fills repeated at page boundaries are yielded once.
+    /// Only the 10000 most recent fills of a user are available.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use futures::TryStreamExt;
+    /// use hypersdk::hypercore;
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let client = hypercore::mainnet();
+    /// let user = "0x...".parse()?;
+    ///
+    /// let fills: Vec<_> = client.user_fills_by_time_stream(user, 0, None).try_collect().await?;
+    /// println!("{} fills", fills.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn user_fills_by_time_stream(
+        &self,
+        user: Address,
+        start_time: u64,
+        end_time: Option<u64>,
+    ) -> impl Stream<Item = Result<Fill>> + '_ {
+        paginate(
+            start_time,
+            end_time,
+            FILLS_PAGE_SIZE,
+            |fill: &Fill| fill.time,
+            |fill| (fill.tid, fill.hash.clone()),
+            move |start_time| self.user_fills_by_time(user, start_time, end_time),
+        )
+    }
+
+    /// Returns the funding payments of a user from `start_time` to `end_time` (excluded), oldest first.
+    ///
+    /// The endpoint returns at most 500 payments per request; this method follows
+    /// the time window until the whole range is covered, dropping the payments
+    /// repeated at page boundaries.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hypersdk::hypercore;
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let client = hypercore::mainnet();
+    /// let user = "0x...".parse()?;
+    ///
+    /// let end_time = chrono::Utc::now().timestamp_millis() as u64;
+    /// let start_time = end_time - 30 * 24 * 60 * 60 * 1000; // 30 days ago
+    ///
+    /// let payments = client.user_funding(user, start_time, Some(end_time)).await?;
+    /// let total: rust_decimal::Decimal = payments.iter().map(|payment| payment.usdc).sum();
+    /// println!("Net funding over 30 days: {total} USDC");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn user_funding(
+        &self,
+        user: Address,
+        start_time: u64,
+        end_time: Option<u64>,
+    ) -> Result<Vec<UserFunding>> {
+        #[derive(Deserialize)]
+        struct Record {
+            time: u64,
+            hash: String,
+            delta: Delta,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Delta {
+            coin: String,
+            usdc: Decimal,
+            szi: Decimal,
+            funding_rate: Decimal,
+            #[serde(default)]
+            n_samples: Option<u64>,
+        }
+
+        paginate(
+            start_time,
+            end_time,
+            LEDGER_PAGE_SIZE,
+            |funding: &UserFunding| funding.time,
+            |funding| funding.coin.clone(),
+            |start_time| async move {
+                let records: Vec<Record> = self
+                    .info(&InfoRequest::UserFunding {
+                        user,
+                        start_time,
+                        end_time,
+                    })
+                    .await?;
+                Ok(records
+                    .into_iter()
+                    .map(|record| UserFunding {
+                        time: record.time,
+                        hash: Some(record.hash),
+                        coin: record.delta.coin,
+                        usdc: record.delta.usdc,
+                        szi: record.delta.szi,
+                        funding_rate: record.delta.funding_rate,
+                        n_samples: record.delta.n_samples,
+                    })
+                    .collect())
+            },
+        )
+        .try_collect()
+        .await
+    }
+
+    /// Returns the non-funding ledger updates of a user from `start_time` to `end_time` (excluded), oldest first.
+    ///
+    /// Ledger updates are deposits, withdrawals, internal transfers, spot sends,
+    /// vault moves and liquidations; see [`LedgerDelta`](crate::hypercore::types::LedgerDelta).
+    /// Like [`user_funding`](Self::user_funding), the whole range is fetched
+    /// in pages of 500 updates.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hypersdk::hypercore::{self, types::LedgerDelta};
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let client = hypercore::mainnet();
+    /// let user = "0x...".parse()?;
+    ///
+    /// for update in client.user_non_funding_ledger_updates(user, 0, None).await? {
+    ///     match update.delta {
+    ///         LedgerDelta::Deposit { usdc } => println!("[{}] deposit {usdc}", update.time),
+    ///         LedgerDelta::Withdraw { usdc, fee, .. } => {
+    ///             println!("[{}] withdraw {usdc} (fee {fee})", update.time)
+    ///         }
+    ///         other => println!("[{}] {other:?}", update.time),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn user_non_funding_ledger_updates(
+        &self,
+        user: Address,
+        start_time: u64,
+        end_time: Option<u64>,
+    ) -> Result<Vec<LedgerUpdate>> {
+        paginate(
+            start_time,
+            end_time,
+            LEDGER_PAGE_SIZE,
+            |update: &LedgerUpdate| update.time,
+            LedgerUpdate::clone,
+            |start_time| async move {
+                self.info(&InfoRequest::UserNonFundingLedgerUpdates {
+                    user,
+                    start_time,
+                    end_time,
+                })
+                .await
+            },
+        )
+        .try_collect()
+        .await
+    }
+
     /// Returns the status of an order.
     pub async fn order_status(
         &self,
         user: Address,
         oid: OidOrCloid,
     ) -> Result<Option<OrderUpdate<BasicOrder>>> {
-        let mut api_url = self.base_url.clone();
-        api_url.set_path("/info");
-
         #[derive(Deserialize)]
         #[serde(rename_all = "camelCase")]
         #[serde(tag = "status")]
@@ -473,14 +947,7 @@ impl Client {
             UnknownOid,
         }
 
-        let data: Response = self
-            .http_client
-            .post(api_url)
-            .json(&InfoRequest::OrderStatus { user, oid })
-            .send()
-            .await?
-            .json()
-            .await?;
+        let data: Response = self.info(&InfoRequest::OrderStatus { user, oid }).await?;
 
         Ok(match data {
             Response::Order { order } => Some(order),
@@ -537,9 +1004,6 @@ impl Client {
         start_time: u64,
         end_time: u64,
     ) -> Result<Vec<super::types::Candle>> {
-        let mut api_url = self.base_url.clone();
-        api_url.set_path("/info");
-
         let req = super::types::CandleSnapshotRequest {
             coin: coin.into(),
             interval,
@@ -547,28 +1011,81 @@ impl Client {
             end_time,
         };
 
-        let data = self
-            .http_client
-            .post(api_url)
-            .json(&InfoRequest::CandleSnapshot { req })
-            .send()
-            .await?
-            .json()
-            .await?;
+        let data = self.info(&InfoRequest::CandleSnapshot { req }).await?;
 
         Ok(data)
     }
 
-    /// Retrieves spot token balances for a user.
+    /// Streams the candles opened from `start_time` to `end_time` (excluded), oldest first.
     ///
-    /// Returns all tokens the user holds on the spot market, including held (locked) and total amounts.
+    /// The range is split in windows of 5000 candles, the most [`candle_snapshot`](Self::candle_snapshot)
+    /// returns, sized with [`CandleInterval::to_duration`]. Windows are requested
+    /// as the stream is consumed and each candle is yielded once.
+    /// Only the 5000 most recent candles of each interval are available.
     ///
     /// # Example
     ///
     /// ```no_run
-    /// use hypersdk::hypercore;
-    /// use hypersdk::Address;
-    ///
+    /// use futures::StreamExt;
+    /// use hypersdk::hypercore::{self, CandleInterval};
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let client = hypercore::mainnet();
+    ///
+    /// // 90 days of 1h candles
+    /// let end_time = chrono::Utc::now().timestamp_millis() as u64;
+    /// let start_time = end_time - 90 * 24 * 60 * 60 * 1000;
+    ///
+    /// let mut candles = std::pin::pin!(client.candle_snapshot_stream(
+    ///     "BTC",
+    ///     CandleInterval::OneHour,
+    ///     start_time,
+    ///     end_time
+    /// ));
+    /// while let Some(candle) = candles.next().await {
+    ///     let candle = candle?;
+    ///     println!("{}: {}", candle.open_time, candle.close);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn candle_snapshot_stream(
+        &self,
+        coin: impl Into<String>,
+        interval: CandleInterval,
+        start_time: u64,
+        end_time: u64,
+    ) -> impl Stream<Item = Result<Candle>> + '_ {
+        let coin = coin.into();
+        let window = interval.to_duration().as_millis() as u64 * CANDLE_PAGE_SIZE;
+        let windows = (start_time..end_time)
+            .step_by(window as usize)
+            .map(move |start| (start, start.saturating_add(window).min(end_time)));
+
+        stream::iter(windows)
+            .then(move |(start, end)| {
+                let coin = coin.clone();
+                async move {
+                    let mut candles = self.candle_snapshot(coin, interval, start, end).await?;
+                    // windows are half-open so boundary candles belong to a single one
+                    candles.retain(|candle| (start..end).contains(&candle.open_time));
+                    candles.sort_by_key(|candle| candle.open_time);
+                    Ok::<_, Error>(stream::iter(candles.into_iter().map(Ok)))
+                }
+            })
+            .try_flatten()
+    }
+
+    /// Retrieves spot token balances for a user.
+    ///
+    /// Returns all tokens the user holds on the spot market, including held (locked) and total amounts.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hypersdk::hypercore;
+    /// use hypersdk::Address;
+    ///
     /// # async fn example() -> anyhow::Result<()> {
     /// let client = hypercore::mainnet();
     /// let user: Address = "0x...".parse()?;
@@ -581,21 +1098,13 @@ impl Client {
     /// # }
     /// ```
     pub async fn user_balances(&self, user: Address) -> Result<Vec<UserBalance>> {
-        let mut api_url = self.base_url.clone();
-        api_url.set_path("/info");
-
         #[derive(Deserialize)]
         struct Balances {
             balances: Vec<UserBalance>,
         }
 
         let data: Balances = self
-            .http_client
-            .post(api_url)
-            .json(&InfoRequest::SpotClearinghouseState { user })
-            .send()
-            .await?
-            .json()
+            .info(&InfoRequest::SpotClearinghouseState { user })
             .await?;
 
         Ok(data.balances)
@@ -625,17 +1134,7 @@ impl Client {
     /// # }
     /// ```
     pub async fn user_fees(&self, user: Address) -> Result<UserFees> {
-        let mut api_url = self.base_url.clone();
-        api_url.set_path("/info");
-
-        let data = self
-            .http_client
-            .post(api_url)
-            .json(&InfoRequest::UserFees { user })
-            .send()
-            .await?
-            .json()
-            .await?;
+        let data = self.info(&InfoRequest::UserFees { user }).await?;
 
         Ok(data)
     }
@@ -683,19 +1182,11 @@ impl Client {
         user: Address,
         dex_name: Option<String>,
     ) -> Result<ClearinghouseState> {
-        let mut api_url = self.base_url.clone();
-        api_url.set_path("/info");
-
         let data = self
-            .http_client
-            .post(api_url)
-            .json(&InfoRequest::ClearinghouseState {
+            .info(&InfoRequest::ClearinghouseState {
                 user,
                 dex: dex_name,
             })
-            .send()
-            .await?
-            .json()
             .await?;
         Ok(data)
     }
@@ -714,7 +1205,8 @@ impl Client {
     /// # Notes
     ///
     /// - Only the most recent 500 records are returned per request
-    /// - To paginate, use the last returned timestamp as the next `start_time`
+    /// - To paginate, use the last returned timestamp as the next `start_time`,
+    ///   or use [`funding_history_stream`](Self::funding_history_stream)
     ///
     /// # Example
     ///
@@ -743,25 +1235,59 @@ impl Client {
         start_time: u64,
         end_time: Option<u64>,
     ) -> Result<Vec<FundingRate>> {
-        let mut api_url = self.base_url.clone();
-        api_url.set_path("/info");
-
         let data = self
-            .http_client
-            .post(api_url)
-            .json(&InfoRequest::FundingHistory {
+            .info(&InfoRequest::FundingHistory {
                 coin: coin.into(),
                 start_time,
                 end_time,
             })
-            .send()
-            .await?
-            .json()
             .await?;
 
         Ok(data)
     }
 
+    /// Streams the funding rates of `coin` from `start_time` to `end_time` (excluded), oldest first.
+    ///
+    /// Pages of [`funding_history`](Self::funding_history) are requested as the
+    /// stream is consumed. Rates repeated at page boundaries are yielded once.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use futures::TryStreamExt;
+    /// use hypersdk::hypercore;
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let client = hypercore::mainnet();
+    ///
+    /// let end_time = chrono::Utc::now().timestamp_millis() as u64;
+    /// let start_time = end_time - 90 * 24 * 60 * 60 * 1000; // 90 days ago
+    ///
+    /// let rates: Vec<_> = client
+    ///     .funding_history_stream("BTC", start_time, Some(end_time))
+    ///     .try_collect()
+    ///     .await?;
+    /// println!("{} hourly rates", rates.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn funding_history_stream(
+        &self,
+        coin: impl Into<String>,
+        start_time: u64,
+        end_time: Option<u64>,
+    ) -> impl Stream<Item = Result<FundingRate>> + '_ {
+        let coin = coin.into();
+        paginate(
+            start_time,
+            end_time,
+            LEDGER_PAGE_SIZE,
+            |rate: &FundingRate| rate.time,
+            |rate| rate.time,
+            move |start_time| self.funding_history(coin.clone(), start_time, end_time),
+        )
+    }
+
     /// Retrieves the multi-signature wallet configuration for a user.
     ///
     /// Returns the list of authorized signers and the signature threshold required
@@ -795,16 +1321,8 @@ impl Client {
     /// # }
     /// ```
     pub async fn multi_sig_config(&self, user: Address) -> Result<MultiSigConfig> {
-        let mut api_url = self.base_url.clone();
-        api_url.set_path("/info");
-
         let resp = self
-            .http_client
-            .post(api_url)
-            .json(&InfoRequest::UserToMultiSigSigners { user })
-            .send()
-            .await?
-            .json()
+            .info(&InfoRequest::UserToMultiSigSigners { user })
             .await?;
         Ok(resp)
     }
@@ -831,16 +1349,33 @@ impl Client {
     /// }
     /// ```
     pub async fn api_agents(&self, user: Address) -> Result<Vec<ApiAgent>> {
-        let mut api_url = self.base_url.clone();
-        api_url.set_path("/info");
+        let resp = self.info(&InfoRequest::ExtraAgents { user }).await?;
+        Ok(resp)
+    }
 
+    /// Get the maximum builder fee approved by a user.
+    ///
+    /// Returns the fee in tenths of a basis point (10 is 0.01%), 0 if `builder`
+    /// was never approved. See [`approve_builder_fee`](Self::approve_builder_fee).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hypersdk::hypercore;
+    /// use alloy::primitives::address;
+    /// async fn example() -> anyhow::Result<()> {
+    ///     let client = hypercore::mainnet();
+    ///     let user = address!("0000000000000000000000000000000000000000");
+    ///     let builder = address!("0x97271b6b7f3b23a2f4700ae671b05515ae5c3319");
+    ///     let fee = client.max_builder_fee(user, builder).await?;
+    ///
+    ///     println!("Approved up to {} bp", fee as f64 / 10.0);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn max_builder_fee(&self, user: Address, builder: Address) -> Result<u64> {
         let resp = self
-            .http_client
-            .post(api_url)
-            .json(&InfoRequest::ExtraAgents { user })
-            .send()
-            .await?
-            .json()
+            .info(&InfoRequest::MaxBuilderFee { user, builder })
             .await?;
         Ok(resp)
     }
@@ -885,19 +1420,11 @@ impl Client {
         vault_address: Address,
         user: Option<Address>,
     ) -> Result<VaultDetails> {
-        let mut api_url = self.base_url.clone();
-        api_url.set_path("/info");
-
         let resp = self
-            .http_client
-            .post(api_url)
-            .json(&InfoRequest::VaultDetails {
+            .info(&InfoRequest::VaultDetails {
                 vault_address,
                 user,
             })
-            .send()
-            .await?
-            .json()
             .await?;
         Ok(resp)
     }
@@ -931,17 +1458,7 @@ impl Client {
     ///
     /// <https://hyperliquid.gitbook.io/hyperliquid-docs/for-developers/api/info-endpoint#retrieve-a-users-vault-deposits>
     pub async fn user_vault_equities(&self, user: Address) -> Result<Vec<UserVaultEquity>> {
-        let mut api_url = self.base_url.clone();
-        api_url.set_path("/info");
-
-        let resp = self
-            .http_client
-            .post(api_url)
-            .json(&InfoRequest::UserVaultEquities { user })
-            .send()
-            .await?
-            .json()
-            .await?;
+        let resp = self.info(&InfoRequest::UserVaultEquities { user }).await?;
         Ok(resp)
     }
 
@@ -980,17 +1497,7 @@ impl Client {
     ///
     /// <https://hyperliquid.gitbook.io/hyperliquid-docs/for-developers/api/info-endpoint#query-a-users-role>
     pub async fn user_role(&self, user: Address) -> Result<UserRole> {
-        let mut api_url = self.base_url.clone();
-        api_url.set_path("/info");
-
-        let resp = self
-            .http_client
-            .post(api_url)
-            .json(&InfoRequest::UserRole { user })
-            .send()
-            .await?
-            .json()
-            .await?;
+        let resp = self.info(&InfoRequest::UserRole { user }).await?;
         Ok(resp)
     }
 
@@ -1036,20 +1543,45 @@ impl Client {
     ///
     /// <https://hyperliquid.gitbook.io/hyperliquid-docs/for-developers/api/info-endpoint#retrieve-a-users-subaccounts>
     pub async fn subaccounts(&self, user: Address) -> Result<Vec<SubAccount>> {
-        let mut api_url = self.base_url.clone();
-        api_url.set_path("/info");
+        let resp = self.info(&InfoRequest::SubAccounts { user }).await?;
+        Ok(resp)
+    }
 
-        let resp = self
-            .http_client
-            .post(api_url)
-            .json(&InfoRequest::SubAccounts { user })
-            .send()
-            .await?
-            .json()
-            .await?;
+    /// Retrieves the address-based request budget of a user.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hypersdk::hypercore;
+    /// use hypersdk::Address;
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let client = hypercore::mainnet();
+    /// let user: Address = "0x...".parse()?;
+    /// let limit = client.user_rate_limit(user).await?;
+    ///
+    /// println!("{} of {} requests used", limit.n_requests_used, limit.n_requests_cap);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// <https://hyperliquid.gitbook.io/hyperliquid-docs/for-developers/api/info-endpoint#query-user-rate-limits>
+    pub async fn user_rate_limit(&self, user: Address) -> Result<UserRateLimit> {
+        let resp = self.info(&InfoRequest::UserRateLimit { user }).await?;
         Ok(resp)
     }
 
+    /// Syncs the address budget of the rate limiter with the exchange.
+    ///
+    /// Does nothing but return the budget if the client has no rate limiter.
+    pub async fn sync_rate_limit(&self, user: Address) -> Result<UserRateLimit> {
+        let limit = self.user_rate_limit(user).await?;
+        if let Some(limiter) = &self.limiter {
+            limiter.sync(user, &limit);
+        }
+        Ok(limit)
+    }
+
     /// Schedule cancellation.
     pub async fn schedule_cancel<S: SignerSync>(
         &self,
@@ -1073,10 +1605,10 @@ impl Client {
 
         match resp {
             Response::Ok(OkResponse::Default) => Ok(()),
-            Response::Err(err) => {
-                anyhow::bail!("schedule_cancel: {err}")
-            }
-            _ => anyhow::bail!("schedule_cancel: unexpected response type: {resp:?}"),
+            Response::Err(err) => Err(Error::from_api(err)),
+            _ => Err(Error::Other(format!(
+                "schedule_cancel: unexpected response type: {resp:?}"
+            ))),
         }
     }
 
@@ -1137,6 +1669,289 @@ impl Client {
         }
     }
 
+    /// Places an entry order with its take-profit and stop-loss legs.
+    ///
+    /// The bracket is validated and its legs rounded to the ticks of `market` with
+    /// [`Bracket::to_batch`] before signing, and sent
+    /// as a [`OrderGrouping::NormalTpsl`](crate::hypercore::types::OrderGrouping::NormalTpsl)
+    /// group. The returned [`BracketStatus`] holds the status of each leg.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hypersdk::hypercore::{self, Bracket, OrderBuilder, PrivateKeySigner, TpSlLeg};
+    /// use rust_decimal::dec;
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let client = hypercore::mainnet();
+    /// let signer: PrivateKeySigner = "your_key".parse()?;
+    /// let perps = client.perps().await?;
+    /// let btc = perps.iter().find(|m| m.name == "BTC").unwrap();
+    ///
+    /// let entry = OrderBuilder::perp(btc)
+    ///     .buy(dec!(0.01))
+    ///     .limit_px(dec!(50000))
+    ///     .build()?;
+    /// let bracket = Bracket::new(entry)
+    ///     .with_take_profit(TpSlLeg::market(dec!(55000)))
+    ///     .with_stop_loss(TpSlLeg::market(dec!(48000)));
+    ///
+    /// let nonce = chrono::Utc::now().timestamp_millis() as u64;
+    /// let status = client
+    ///     .place_bracket(&signer, btc, &bracket, nonce, None, None)
+    ///     .await?;
+    /// println!("entry: {:?}", status.entry);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn place_bracket<S: SignerSync>(
+        &self,
+        signer: &S,
+        market: &PerpMarket,
+        bracket: &Bracket,
+        nonce: u64,
+        vault_address: Option<Address>,
+        expires_after: Option<DateTime<Utc>>,
+    ) -> Result<BracketStatus> {
+        let batch = bracket.to_batch(market)?;
+        self.place_grouped(signer, batch, nonce, vault_address, expires_after)
+            .await
+    }
+
+    /// Attaches take-profit and stop-loss orders to the open position on `market`.
+    ///
+    /// The position of `vault_address`, or of the signer, is fetched with
+    /// [`clearinghouse_state`](Self::clearinghouse_state), including HIP-3 dex markets.
+    /// The legs are validated with [`PositionTpsl::to_batch`] before signing and sent as a
+    /// [`OrderGrouping::PositionTpsl`](crate::hypercore::types::OrderGrouping::PositionTpsl)
+    /// group that closes the whole position.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hypersdk::hypercore::{self, PositionTpsl, PrivateKeySigner, TpSlLeg};
+    /// use rust_decimal::dec;
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let client = hypercore::mainnet();
+    /// let signer: PrivateKeySigner = "your_key".parse()?;
+    /// let perps = client.perps().await?;
+    /// let btc = perps.iter().find(|m| m.name == "BTC").unwrap();
+    ///
+    /// let tpsl = PositionTpsl::default().with_stop_loss(TpSlLeg::market(dec!(48000)));
+    /// let nonce = chrono::Utc::now().timestamp_millis() as u64;
+    /// let status = client
+    ///     .attach_position_tpsl(&signer, btc, tpsl, nonce, None, None)
+    ///     .await?;
+    /// println!("stop-loss: {:?}", status.stop_loss);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn attach_position_tpsl<S: Signer + SignerSync>(
+        &self,
+        signer: &S,
+        market: &PerpMarket,
+        tpsl: PositionTpsl,
+        nonce: u64,
+        vault_address: Option<Address>,
+        expires_after: Option<DateTime<Utc>>,
+    ) -> Result<BracketStatus> {
+        let user = vault_address.unwrap_or(signer.address());
+        let position = self.open_position(user, market).await?;
+
+        let batch = tpsl.to_batch(market, &position)?;
+        self.place_grouped(signer, batch, nonce, vault_address, expires_after)
+            .await
+    }
+
+    /// Opens or increases a position with a market order.
+    ///
+    /// Hyperliquid has no native market orders: the order is sent as an IOC limit order
+    /// priced at the order's [`Slippage`] from the mid price, rounded towards the mid with
+    /// [`PerpMarket::round_by_side`]. The order is built with [`OrderBuilder`], so it is
+    /// validated before signing. HIP-3 dex markets use the mid prices of their dex.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hypersdk::hypercore::{self, MarketOrder, PrivateKeySigner, Slippage};
+    /// use rust_decimal::dec;
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let client = hypercore::mainnet();
+    /// let signer: PrivateKeySigner = "your_key".parse()?;
+    /// let perps = client.perps().await?;
+    /// let eth = perps.iter().find(|m| m.name == "ETH").unwrap();
+    ///
+    /// let order = MarketOrder::buy(eth, dec!(0.1)).with_slippage(Slippage::bps(50));
+    /// let nonce = chrono::Utc::now().timestamp_millis() as u64;
+    /// let status = client.market_open(&signer, order, nonce, None, None).await?;
+    /// println!("{status:?}");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn market_open<S: SignerSync>(
+        &self,
+        signer: &S,
+        order: MarketOrder<'_>,
+        nonce: u64,
+        vault_address: Option<Address>,
+        expires_after: Option<DateTime<Utc>>,
+    ) -> Result<OrderResponseStatus> {
+        let mid = self.market_mid(order.market, &order.slippage).await?;
+        let request = OrderBuilder::perp(order.market)
+            .side(order.side)
+            .size(order.sz)
+            .slippage(mid, order.slippage.fraction())
+            .cloid(order.cloid)
+            .build()?;
+
+        self.place_market(signer, request, nonce, vault_address, expires_after)
+            .await
+    }
+
+    /// Closes the whole position on `market` with a market order.
+    ///
+    /// The position of `vault_address`, or of the signer, is fetched with
+    /// [`clearinghouse_state`](Self::clearinghouse_state) and closed with a reduce-only
+    /// order of exactly its size, priced like in [`market_open`](Self::market_open).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hypersdk::hypercore::{self, PrivateKeySigner, Slippage};
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let client = hypercore::mainnet();
+    /// let signer: PrivateKeySigner = "your_key".parse()?;
+    /// let perps = client.perps().await?;
+    /// let eth = perps.iter().find(|m| m.name == "ETH").unwrap();
+    ///
+    /// let nonce = chrono::Utc::now().timestamp_millis() as u64;
+    /// let status = client
+    ///     .market_close(&signer, eth, Slippage::bps(50), nonce, None, None)
+    ///     .await?;
+    /// println!("{status:?}");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn market_close<S: Signer + SignerSync>(
+        &self,
+        signer: &S,
+        market: &PerpMarket,
+        slippage: Slippage<'_>,
+        nonce: u64,
+        vault_address: Option<Address>,
+        expires_after: Option<DateTime<Utc>>,
+    ) -> Result<OrderResponseStatus> {
+        let user = vault_address.unwrap_or(signer.address());
+        let position = self.open_position(user, market).await?;
+        let side = if position.is_long() {
+            Side::Ask
+        } else {
+            Side::Bid
+        };
+
+        let mid = self.market_mid(market, &slippage).await?;
+        let request = OrderBuilder::perp(market)
+            .side(side)
+            .size(position.abs_size())
+            .slippage(mid, slippage.fraction())
+            .reduce_only(true)
+            .build()?;
+
+        self.place_market(signer, request, nonce, vault_address, expires_after)
+            .await
+    }
+
+    /// Returns the user's non-empty position on `market`.
+    async fn open_position(&self, user: Address, market: &PerpMarket) -> Result<PositionData> {
+        let state = self
+            .clearinghouse_state(user, market.dex().map(str::to_owned))
+            .await?;
+
+        state
+            .asset_positions
+            .into_iter()
+            .map(|asset| asset.position)
+            .find(|position| position.coin == market.name && !position.szi.is_zero())
+            .ok_or_else(|| Error::InvalidOrder {
+                message: format!("{}: no open position", market.name),
+            })
+    }
+
+    /// Returns the mid price the slippage of a market order applies to.
+    async fn market_mid(&self, market: &PerpMarket, slippage: &Slippage<'_>) -> Result<Decimal> {
+        let (coin, mid) = match slippage.price {
+            MarketPrice::Mids => {
+                let mut mids = self.all_mids(market.dex().map(str::to_owned)).await?;
+                (market.name.as_str(), mids.remove(&market.name))
+            }
+            MarketPrice::Mid(mid) => (market.name.as_str(), Some(mid)),
+            MarketPrice::Bbo(bbo) => (bbo.coin.as_str(), bbo.mid()),
+            MarketPrice::Book(book) => (book.coin.as_str(), book.mid()),
+        };
+
+        if coin != market.name {
+            return Err(Error::InvalidOrder {
+                message: format!("{}: market data is for {coin}", market.name),
+            });
+        }
+        mid.ok_or_else(|| Error::InvalidOrder {
+            message: format!("{}: no mid price", market.name),
+        })
+    }
+
+    async fn place_market<S: SignerSync>(
+        &self,
+        signer: &S,
+        request: OrderRequest,
+        nonce: u64,
+        vault_address: Option<Address>,
+        expires_after: Option<DateTime<Utc>>,
+    ) -> Result<OrderResponseStatus> {
+        let batch = BatchOrder {
+            orders: vec![request],
+            grouping: OrderGrouping::Na,
+            builder: None,
+        };
+        let resp = self
+            .sign_and_send_sync(signer, batch, nonce, vault_address, expires_after)
+            .await?;
+
+        match resp {
+            Response::Ok(OkResponse::Order { statuses }) => statuses
+                .into_iter()
+                .next()
+                .ok_or_else(|| Error::Other("place: missing order status".into())),
+            Response::Err(err) => Err(Error::from_api(err)),
+            _ => Err(Error::Other(format!(
+                "place: unexpected response type: {resp:?}"
+            ))),
+        }
+    }
+
+    async fn place_grouped<S: SignerSync>(
+        &self,
+        signer: &S,
+        batch: BatchOrder,
+        nonce: u64,
+        vault_address: Option<Address>,
+        expires_after: Option<DateTime<Utc>>,
+    ) -> Result<BracketStatus> {
+        let resp = self
+            .sign_and_send_sync(signer, batch.clone(), nonce, vault_address, expires_after)
+            .await?;
+
+        match resp {
+            Response::Ok(OkResponse::Order { statuses }) => BracketStatus::new(&batch, statuses),
+            Response::Err(err) => Err(Error::from_api(err)),
+            _ => Err(Error::Other(format!(
+                "place: unexpected response type: {resp:?}"
+            ))),
+        }
+    }
+
     /// Cancel a batch of orders.
     pub fn cancel<S: SignerSync>(
         &self,
@@ -1219,29 +2034,275 @@ impl Client {
                 err: err.to_string(),
             })?;
 
-            match resp {
-                Response::Ok(OkResponse::Order { statuses }) => Ok(statuses),
-                Response::Err(err) => Err(ActionError { ids: cloids, err }),
-                _ => Err(ActionError {
-                    ids: cloids,
-                    err: format!("unexpected response type: {resp:?}"),
-                }),
-            }
+            match resp {
+                Response::Ok(OkResponse::Order { statuses }) => Ok(statuses),
+                Response::Err(err) => Err(ActionError { ids: cloids, err }),
+                _ => Err(ActionError {
+                    ids: cloids,
+                    err: format!("unexpected response type: {resp:?}"),
+                }),
+            }
+        }
+    }
+
+    /// Places a TWAP order.
+    ///
+    /// Returns the TWAP ID on success, which can be used with [`twap_cancel`](Self::twap_cancel).
+    /// Slice fills are streamed through `Subscription::UserTwapSliceFills`.
+    ///
+    /// # Parameters
+    ///
+    /// - `signer`: Private key signer for EIP-712 signatures
+    /// - `twap`: The TWAP order to place
+    /// - `nonce`: Unique nonce (typically current timestamp in milliseconds)
+    /// - `vault_address`: Optional vault address if trading on behalf of a vault
+    /// - `expires_after`: Optional expiration timestamp for the request
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hypersdk::hypercore::{self, types::*, NonceHandler, PrivateKeySigner};
+    /// use rust_decimal::dec;
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let client = hypercore::mainnet();
+    /// let signer: PrivateKeySigner = "your_key".parse()?;
+    ///
+    /// let twap = TwapOrder {
+    ///     twap: TwapRequest {
+    ///         asset: 0,
+    ///         is_buy: true,
+    ///         sz: dec!(0.5),
+    ///         reduce_only: false,
+    ///         minutes: 60,
+    ///         randomize: true,
+    ///     },
+    /// };
+    /// let nonce = NonceHandler::default().next();
+    /// let twap_id = client.twap_order(&signer, twap, nonce, None, None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// <https://hyperliquid.gitbook.io/hyperliquid-docs/for-developers/api/exchange-endpoint#place-a-twap-order>
+    pub fn twap_order<S: SignerSync>(
+        &self,
+        signer: &S,
+        twap: TwapOrder,
+        nonce: u64,
+        vault_address: Option<Address>,
+        expires_after: Option<DateTime<Utc>>,
+    ) -> impl Future<Output = Result<u64>> + Send + 'static {
+        let future = self.sign_and_send_sync(signer, twap, nonce, vault_address, expires_after);
+
+        async move {
+            let resp = future.await?;
+            match resp {
+                Response::Ok(OkResponse::TwapOrder {
+                    status: TwapOrderResponseStatus::Running { twap_id },
+                }) => Ok(twap_id),
+                Response::Ok(OkResponse::TwapOrder {
+                    status: TwapOrderResponseStatus::Error(err),
+                })
+                | Response::Err(err) => Err(Error::from_api(err)),
+                _ => Err(Error::Other(format!(
+                    "twap_order: unexpected response type: {resp:?}"
+                ))),
+            }
+        }
+    }
+
+    /// Cancels a running TWAP order.
+    ///
+    /// <https://hyperliquid.gitbook.io/hyperliquid-docs/for-developers/api/exchange-endpoint#cancel-a-twap-order>
+    pub fn twap_cancel<S: SignerSync>(
+        &self,
+        signer: &S,
+        cancel: TwapCancel,
+        nonce: u64,
+        vault_address: Option<Address>,
+        expires_after: Option<DateTime<Utc>>,
+    ) -> impl Future<Output = Result<()>> + Send + 'static {
+        let future = self.sign_and_send_sync(signer, cancel, nonce, vault_address, expires_after);
+
+        async move {
+            let resp = future.await?;
+            match resp {
+                Response::Ok(OkResponse::TwapCancel {
+                    status: OrderResponseStatus::Error(err),
+                })
+                | Response::Err(err) => Err(Error::from_api(err)),
+                Response::Ok(OkResponse::TwapCancel { .. }) => Ok(()),
+                _ => Err(Error::Other(format!(
+                    "twap_cancel: unexpected response type: {resp:?}"
+                ))),
+            }
+        }
+    }
+
+    /// Update the leverage and margin mode of an asset.
+    ///
+    /// # Parameters
+    ///
+    /// - `signer`: The signer for signing the action
+    /// - `update`: Asset index (see [`PerpMarket::index`]), margin mode and new leverage
+    /// - `nonce`: Unique nonce (typically current timestamp in milliseconds)
+    /// - `vault_address`: Optional vault address if trading on behalf of a vault
+    /// - `expires_after`: Optional expiration timestamp for the request
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hypersdk::hypercore::{self, NonceHandler, PrivateKeySigner, api::UpdateLeverage};
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let client = hypercore::mainnet();
+    /// let signer: PrivateKeySigner = "your_key".parse()?;
+    /// let nonce = NonceHandler::default().next();
+    ///
+    /// // 10x isolated on BTC
+    /// let update = UpdateLeverage {
+    ///     asset: 0,
+    ///     is_cross: false,
+    ///     leverage: 10,
+    /// };
+    /// client.update_leverage(&signer, update, nonce, None, None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// <https://hyperliquid.gitbook.io/hyperliquid-docs/for-developers/api/exchange-endpoint#update-leverage>
+    pub async fn update_leverage<S: SignerSync>(
+        &self,
+        signer: &S,
+        update: UpdateLeverage,
+        nonce: u64,
+        vault_address: Option<Address>,
+        expires_after: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        let resp = self
+            .sign_and_send_sync(signer, update, nonce, vault_address, expires_after)
+            .await?;
+
+        match resp {
+            Response::Ok(OkResponse::Default) => Ok(()),
+            Response::Err(err) => Err(Error::from_api(err)),
+            _ => Err(Error::Other(format!(
+                "update_leverage: unexpected response type: {resp:?}"
+            ))),
+        }
+    }
+
+    /// Add or remove margin from an isolated position.
+    ///
+    /// # Parameters
+    ///
+    /// - `signer`: The signer for signing the action
+    /// - `market`: The perpetual market of the position
+    /// - `usd`: Amount of USDC, positive to add and negative to remove (e.g. `dec!(-10)` removes $10)
+    /// - `nonce`: Unique nonce (typically current timestamp in milliseconds)
+    /// - `vault_address`: Optional vault address if trading on behalf of a vault
+    /// - `expires_after`: Optional expiration timestamp for the request
+    ///
+    /// The amount is validated with [`UpdateIsolatedMargin::from_usd`] before signing.
+    ///
+    /// <https://hyperliquid.gitbook.io/hyperliquid-docs/for-developers/api/exchange-endpoint#update-isolated-margin>
+    pub async fn update_isolated_margin<S: SignerSync>(
+        &self,
+        signer: &S,
+        market: &PerpMarket,
+        usd: Decimal,
+        nonce: u64,
+        vault_address: Option<Address>,
+        expires_after: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        let action = UpdateIsolatedMargin::from_usd(market, usd)?;
+        let resp = self
+            .sign_and_send_sync(signer, action, nonce, vault_address, expires_after)
+            .await?;
+
+        match resp {
+            Response::Ok(OkResponse::Default) => Ok(()),
+            Response::Err(err) => Err(Error::from_api(err)),
+            _ => Err(Error::Other(format!(
+                "update_isolated_margin: unexpected response type: {resp:?}"
+            ))),
+        }
+    }
+
+    /// Approve a new agent.
+    ///
+    /// Approves an agent to act on behalf of the signer's account. An account can have:
+    /// - 1 unnamed approved wallet
+    /// - Up to 3 named agents
+    /// - 2 named agents per subaccount
+    ///
+    /// # Parameters
+    ///
+    /// - `signer`: The wallet signing the approval
+    /// - `agent`: The address of the agent to approve
+    /// - `name`: The name for the agent (or empty string for unnamed)
+    /// - `nonce`: The nonce for this action
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hypersdk::hypercore;
+    /// use alloy::primitives::address;
+    /// use alloy::signers::local::PrivateKeySigner;
+    ///
+    /// async fn example() -> anyhow::Result<()> {
+    ///     let client = hypercore::mainnet();
+    ///     let signer = PrivateKeySigner::random();
+    ///     let agent = address!("0x97271b6b7f3b23a2f4700ae671b05515ae5c3319");
+    ///     let name = "my_agent".to_string();
+    ///     let nonce = 123456789;
+    ///
+    ///     client.approve_agent(&signer, agent, name, nonce).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn approve_agent<S: Signer + Send + Sync>(
+        &self,
+        signer: &S,
+        agent: Address,
+        name: String,
+        nonce: u64,
+    ) -> Result<()> {
+        let signature_chain_id = self.chain.arbitrum_id().to_owned();
+
+        let approve_agent = ApproveAgent {
+            signature_chain_id,
+            hyperliquid_chain: self.chain,
+            agent_address: agent,
+            agent_name: if name.is_empty() { None } else { Some(name) },
+            nonce,
+        };
+
+        let resp = self
+            .sign_and_send(signer, approve_agent, nonce, None, None)
+            .await?;
+        match resp {
+            Response::Ok(OkResponse::Default) => Ok(()),
+            Response::Err(err) => Err(Error::from_api(err)),
+            _ => Err(Error::Other(format!(
+                "approve_agent: unexpected response type: {resp:?}"
+            ))),
         }
     }
 
-    /// Approve a new agent.
+    /// Approve a builder fee.
     ///
-    /// Approves an agent to act on behalf of the signer's account. An account can have:
-    /// - 1 unnamed approved wallet
-    /// - Up to 3 named agents
-    /// - 2 named agents per subaccount
+    /// Allows `builder` to charge up to `max_fee` on the orders of the signer, in
+    /// tenths of a basis point like [`Builder::fee`](crate::hypercore::types::Builder::fee).
+    /// Orders with a [`Builder`](crate::hypercore::types::Builder) charging more than
+    /// the approved fee are rejected. Approving a fee of 0 revokes the approval.
     ///
     /// # Parameters
     ///
-    /// - `signer`: The wallet signing the approval
-    /// - `agent`: The address of the agent to approve
-    /// - `name`: The name for the agent (or empty string for unnamed)
+    /// - `signer`: The wallet signing the approval (must be the account owner, not an agent)
+    /// - `builder`: The address of the builder
+    /// - `max_fee`: Maximum fee in tenths of a basis point (10 is 0.01%)
     /// - `nonce`: The nonce for this action
     ///
     /// # Example
@@ -1254,40 +2315,40 @@ impl Client {
     /// async fn example() -> anyhow::Result<()> {
     ///     let client = hypercore::mainnet();
     ///     let signer = PrivateKeySigner::random();
-    ///     let agent = address!("0x97271b6b7f3b23a2f4700ae671b05515ae5c3319");
-    ///     let name = "my_agent".to_string();
+    ///     let builder = address!("0x97271b6b7f3b23a2f4700ae671b05515ae5c3319");
     ///     let nonce = 123456789;
     ///
-    ///     client.approve_agent(&signer, agent, name, nonce).await?;
+    ///     // up to 1 basis point
+    ///     client.approve_builder_fee(&signer, builder, 10, nonce).await?;
     ///     Ok(())
     /// }
     /// ```
-    pub async fn approve_agent<S: Signer + Send + Sync>(
+    pub async fn approve_builder_fee<S: Signer + Send + Sync>(
         &self,
         signer: &S,
-        agent: Address,
-        name: String,
+        builder: Address,
+        max_fee: u64,
         nonce: u64,
     ) -> Result<()> {
         let signature_chain_id = self.chain.arbitrum_id().to_owned();
 
-        let approve_agent = ApproveAgent {
+        let approve_builder_fee = ApproveBuilderFee {
             signature_chain_id,
             hyperliquid_chain: self.chain,
-            agent_address: agent,
-            agent_name: if name.is_empty() { None } else { Some(name) },
+            max_fee_rate: format!("{}%", Decimal::new(max_fee as i64, 3).normalize()),
+            builder,
             nonce,
         };
 
         let resp = self
-            .sign_and_send(signer, approve_agent, nonce, None, None)
+            .sign_and_send(signer, approve_builder_fee, nonce, None, None)
             .await?;
         match resp {
             Response::Ok(OkResponse::Default) => Ok(()),
-            Response::Err(err) => {
-                anyhow::bail!("approve_agent: {err}")
-            }
-            _ => anyhow::bail!("approve_agent: unexpected response type: {resp:?}"),
+            Response::Err(err) => Err(Error::from_api(err)),
+            _ => Err(Error::Other(format!(
+                "approve_builder_fee: unexpected response type: {resp:?}"
+            ))),
         }
     }
 
@@ -1352,10 +2413,10 @@ impl Client {
             .await?;
         match resp {
             Response::Ok(OkResponse::Default) => Ok(()),
-            Response::Err(err) => {
-                anyhow::bail!("convert_to_multisig: {err}")
-            }
-            _ => anyhow::bail!("convert_to_multisig: unexpected response type: {resp:?}"),
+            Response::Err(err) => Err(Error::from_api(err)),
+            _ => Err(Error::Other(format!(
+                "convert_to_multisig: unexpected response type: {resp:?}"
+            ))),
         }
     }
 
@@ -1367,9 +2428,9 @@ impl Client {
         amount: Decimal,
         nonce: u64,
     ) -> Result<()> {
-        let destination = token
-            .cross_chain_address
-            .ok_or_else(|| anyhow::anyhow!("token {token} doesn't have a cross chain address"))?;
+        let destination = token.cross_chain_address.ok_or_else(|| {
+            Error::Other(format!("token {token} doesn't have a cross chain address"))
+        })?;
 
         self.spot_send(
             &signer,
@@ -1395,10 +2456,10 @@ impl Client {
         nonce: u64,
     ) -> Result<()> {
         if token.name != "USDC" {
-            return Err(anyhow::anyhow!(
+            return Err(Error::Other(format!(
                 "only USDC is accepted, tried to transfer {}",
                 token.name
-            ));
+            )));
         }
 
         self.send_asset(
@@ -1428,10 +2489,10 @@ impl Client {
         nonce: u64,
     ) -> Result<()> {
         if token.name != "USDC" {
-            return Err(anyhow::anyhow!(
+            return Err(Error::Other(format!(
                 "only USDC is accepted, tried to transfer {}",
                 token.name
-            ));
+            )));
         }
 
         self.send_asset(
@@ -1466,10 +2527,10 @@ impl Client {
             .await?;
         match resp {
             Response::Ok(OkResponse::Default) => Ok(()),
-            Response::Err(err) => {
-                anyhow::bail!("send_usdc: {err}")
-            }
-            _ => anyhow::bail!("send_usdc: unexpected response type: {resp:?}"),
+            Response::Err(err) => Err(Error::from_api(err)),
+            _ => Err(Error::Other(format!(
+                "send_usdc: unexpected response type: {resp:?}"
+            ))),
         }
     }
 
@@ -1494,15 +2555,19 @@ impl Client {
     ) -> Result<()> {
         let usd_raw = (usd * rust_decimal::Decimal::from(1_000_000))
             .to_u64()
-            .ok_or_else(|| anyhow::anyhow!("vault_transfer: usd amount out of range: {usd}"))?;
+            .ok_or_else(|| {
+                Error::Other(format!("vault_transfer: usd amount out of range: {usd}"))
+            })?;
         let action = VaultTransfer { vault_address, is_deposit, usd: usd_raw };
         let resp = self
             .sign_and_send_sync(signer, action, nonce, None, None)
             .await?;
         match resp {
             Response::Ok(OkResponse::Default) => Ok(()),
-            Response::Err(err) => anyhow::bail!("vault_transfer: {err}"),
-            _ => anyhow::bail!("vault_transfer: unexpected response type: {resp:?}"),
+            Response::Err(err) => Err(Error::from_api(err)),
+            _ => Err(Error::Other(format!(
+                "vault_transfer: unexpected response type: {resp:?}"
+            ))),
         }
     }
 
@@ -1524,10 +2589,10 @@ impl Client {
             let resp = future.await?;
             match resp {
                 Response::Ok(OkResponse::Default) => Ok(()),
-                Response::Err(err) => {
-                    anyhow::bail!("send_asset: {err}")
-                }
-                _ => anyhow::bail!("send_asset: unexpected response type: {resp:?}"),
+                Response::Err(err) => Err(Error::from_api(err)),
+                _ => Err(Error::Other(format!(
+                    "send_asset: unexpected response type: {resp:?}"
+                ))),
             }
         }
     }
@@ -1550,10 +2615,10 @@ impl Client {
             let resp = future.await?;
             match resp {
                 Response::Ok(OkResponse::Default) => Ok(()),
-                Response::Err(err) => {
-                    anyhow::bail!("spot send: {err}")
-                }
-                _ => anyhow::bail!("spot_send: unexpected response type: {resp:?}"),
+                Response::Err(err) => Err(Error::from_api(err)),
+                _ => Err(Error::Other(format!(
+                    "spot_send: unexpected response type: {resp:?}"
+                ))),
             }
         }
     }
@@ -1581,10 +2646,10 @@ impl Client {
 
         match resp {
             Response::Ok(OkResponse::Default) => Ok(()),
-            Response::Err(err) => {
-                anyhow::bail!("evm_user_modify: {err}")
-            }
-            _ => anyhow::bail!("evm_user_modify: unexpected response type: {resp:?}"),
+            Response::Err(err) => Err(Error::from_api(err)),
+            _ => Err(Error::Other(format!(
+                "evm_user_modify: unexpected response type: {resp:?}"
+            ))),
         }
     }
 
@@ -1602,10 +2667,10 @@ impl Client {
 
         match resp {
             Response::Ok(OkResponse::Default) => Ok(()),
-            Response::Err(err) => {
-                anyhow::bail!("noop: {err}")
-            }
-            _ => anyhow::bail!("noop: unexpected response type: {resp:?}"),
+            Response::Err(err) => Err(Error::from_api(err)),
+            _ => Err(Error::Other(format!(
+                "noop: unexpected response type: {resp:?}"
+            ))),
         }
     }
 
@@ -1691,24 +2756,14 @@ impl Client {
         );
 
         let http_client = self.http_client.clone();
-        let mut url = self.base_url.clone();
-        url.set_path("/exchange");
+        let url = self.base_url.clone();
+        let limiter = self.limiter.clone();
+        let retry = self.retry;
+        let chain = self.chain;
 
         async move {
             let req = res?;
-            let res = http_client.post(url).json(&req).send().await?;
-
-            let status = res.status();
-            let text = res.text().await?;
-
-            if !status.is_success() {
-                return Err(anyhow!("HTTP {status} body={text}"));
-            }
-
-            let parsed = serde_json::from_str(&text)
-                .map_err(|e| anyhow!("decode failed: {e}; body={text}"))?;
-
-            Ok(parsed)
+            post_action(&http_client, url, limiter.as_ref(), retry, chain, &req).await
         }
     }
 
@@ -1737,21 +2792,78 @@ impl Client {
 
     #[doc(hidden)]
     pub async fn send(&self, req: ActionRequest) -> Result<Response> {
-        let http_client = self.http_client.clone();
+        post_action(
+            &self.http_client,
+            self.base_url.clone(),
+            self.limiter.as_ref(),
+            self.retry,
+            self.chain,
+            &req,
+        )
+        .await
+    }
+
+    /// Sends an info request, applying the rate limiter and retry policy.
+    async fn info<T: DeserializeOwned>(&self, req: &InfoRequest) -> Result<T> {
         let mut url = self.base_url.clone();
-        url.set_path("/exchange");
-
-        let res = http_client
-            .post(url)
-            .timeout(Duration::from_secs(5))
-            // .header(header::CONTENT_TYPE, "application/json")
-            // .body(text)
-            .json(&req)
-            .send()
-            .await?
-            .json()
-            .await?;
-        Ok(res)
+        url.set_path("/info");
+
+        let mut attempt = 0;
+        loop {
+            self.throttle(req.weight()).await;
+
+            let res = async {
+                let res = self.http_client.post(url.clone()).json(req).send().await?;
+                let status = res.status();
+                if status == StatusCode::TOO_MANY_REQUESTS {
+                    return Err(Error::RateLimited);
+                }
+
+                let text = res.text().await?;
+                if !status.is_success() {
+                    return Err(Error::Api(format!("HTTP {status} body={text}")));
+                }
+                self.charge_items(req, &text).await;
+                Ok(serde_json::from_str(&text)?)
+            }
+            .await;
+
+            match res {
+                Err(err) if self.should_retry(&err, attempt) => {
+                    self.backoff(attempt).await;
+                    attempt += 1;
+                }
+                res => return res,
+            }
+        }
+    }
+
+    /// Charges the weight of the items returned by an info request, if enabled.
+    async fn charge_items(&self, req: &InfoRequest, text: &str) {
+        let (Some(limiter), Some(per_weight)) = (&self.limiter, req.items_per_weight()) else {
+            return;
+        };
+        if let Ok(items) = serde_json::from_str::<Vec<serde::de::IgnoredAny>>(text) {
+            limiter.spend((items.len() / per_weight) as u32).await;
+        }
+    }
+
+    /// Waits for `weight` in the rate limiter, if enabled.
+    async fn throttle(&self, weight: u32) {
+        if let Some(limiter) = &self.limiter {
+            limiter.acquire(weight).await;
+        }
+    }
+
+    fn should_retry(&self, err: &Error, attempt: u32) -> bool {
+        self.retry
+            .is_some_and(|retry| retry.should_retry(err, attempt))
+    }
+
+    async fn backoff(&self, attempt: u32) {
+        if let Some(retry) = self.retry {
+            sleep(retry.backoff(attempt)).await;
+        }
     }
 
     // TODO: https://hyperliquid.gitbook.io/hyperliquid-docs/for-developers/api/info-endpoint#retrieve-a-users-subaccounts
@@ -1950,6 +3062,7 @@ where
     /// let batch = BatchOrder {
     ///     orders: vec![order],
     ///     grouping: OrderGrouping::Na,
+    ///     builder: None,
     /// };
     ///
     /// let statuses = client
@@ -2078,8 +3191,10 @@ where
 
         match resp {
             Response::Ok(OkResponse::Default) => Ok(()),
-            Response::Err(err) => anyhow::bail!("send_usdc: {err}"),
-            _ => anyhow::bail!("send_usdc: unexpected response type: {resp:?}"),
+            Response::Err(err) => Err(Error::from_api(err)),
+            _ => Err(Error::Other(format!(
+                "send_usdc: unexpected response type: {resp:?}"
+            ))),
         }
     }
 
@@ -2158,8 +3273,205 @@ where
 
         match resp {
             Response::Ok(OkResponse::Default) => Ok(()),
-            Response::Err(err) => anyhow::bail!("send_asset: {err}"),
-            _ => anyhow::bail!("send_asset: unexpected response type: {resp:?}"),
+            Response::Err(err) => Err(Error::from_api(err)),
+            _ => Err(Error::Other(format!(
+                "send_asset: unexpected response type: {resp:?}"
+            ))),
+        }
+    }
+
+    /// Place a TWAP order using the multisig account.
+    ///
+    /// Uses RMP hashing like [`place`](Self::place). Returns the TWAP ID on success.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let twap_id = client
+    ///     .multi_sig(&lead, multisig_addr, nonce)
+    ///     .signers(&signers)
+    ///     .twap_order(twap, None, None)
+    ///     .await?;
+    /// ```
+    pub async fn twap_order(
+        &self,
+        twap: TwapOrder,
+        vault_address: Option<Address>,
+        expires_after: Option<DateTime<Utc>>,
+    ) -> Result<u64> {
+        let action = multisig_collect_signatures(
+            self.lead.address(),
+            self.multi_sig_user,
+            self.signers.iter().copied(),
+            self.signatures.iter().copied(),
+            Action::TwapOrder(twap),
+            self.nonce,
+            self.client.chain,
+        )
+        .await?;
+
+        let resp = self
+            .client
+            .sign_and_send(self.lead, action, self.nonce, vault_address, expires_after)
+            .await?;
+
+        match resp {
+            Response::Ok(OkResponse::TwapOrder {
+                status: TwapOrderResponseStatus::Running { twap_id },
+            }) => Ok(twap_id),
+            Response::Ok(OkResponse::TwapOrder {
+                status: TwapOrderResponseStatus::Error(err),
+            })
+            | Response::Err(err) => Err(Error::from_api(err)),
+            _ => Err(Error::Other(format!(
+                "twap_order: unexpected response type: {resp:?}"
+            ))),
+        }
+    }
+
+    /// Cancel a running TWAP order of the multisig account.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// client
+    ///     .multi_sig(&lead, multisig_addr, nonce)
+    ///     .signers(&signers)
+    ///     .twap_cancel(TwapCancel { asset: 0, twap_id }, None, None)
+    ///     .await?;
+    /// ```
+    pub async fn twap_cancel(
+        &self,
+        cancel: TwapCancel,
+        vault_address: Option<Address>,
+        expires_after: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        let action = multisig_collect_signatures(
+            self.lead.address(),
+            self.multi_sig_user,
+            self.signers.iter().copied(),
+            self.signatures.iter().copied(),
+            Action::TwapCancel(cancel),
+            self.nonce,
+            self.client.chain,
+        )
+        .await?;
+
+        let resp = self
+            .client
+            .sign_and_send(self.lead, action, self.nonce, vault_address, expires_after)
+            .await?;
+
+        match resp {
+            Response::Ok(OkResponse::TwapCancel {
+                status: OrderResponseStatus::Error(err),
+            })
+            | Response::Err(err) => Err(Error::from_api(err)),
+            Response::Ok(OkResponse::TwapCancel { .. }) => Ok(()),
+            _ => Err(Error::Other(format!(
+                "twap_cancel: unexpected response type: {resp:?}"
+            ))),
+        }
+    }
+
+    /// Update the leverage and margin mode of an asset for the multisig account.
+    ///
+    /// Uses RMP hashing like [`place`](Self::place).
+    ///
+    /// # Parameters
+    ///
+    /// - `update`: Asset index, margin mode and new leverage
+    /// - `vault_address`: Optional vault address if trading on behalf of a vault
+    /// - `expires_after`: Optional expiration time for the request
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use hypersdk::hypercore::api::UpdateLeverage;
+    ///
+    /// let update = UpdateLeverage { asset: 0, is_cross: true, leverage: 5 };
+    ///
+    /// client
+    ///     .multi_sig(&lead, multisig_addr, nonce)
+    ///     .signers(&signers)
+    ///     .update_leverage(update, None, None)
+    ///     .await?;
+    /// ```
+    pub async fn update_leverage(
+        &self,
+        update: UpdateLeverage,
+        vault_address: Option<Address>,
+        expires_after: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        let action = multisig_collect_signatures(
+            self.lead.address(),
+            self.multi_sig_user,
+            self.signers.iter().copied(),
+            self.signatures.iter().copied(),
+            Action::UpdateLeverage(update),
+            self.nonce,
+            self.client.chain,
+        )
+        .await?;
+
+        let resp = self
+            .client
+            .sign_and_send(self.lead, action, self.nonce, vault_address, expires_after)
+            .await?;
+
+        match resp {
+            Response::Ok(OkResponse::Default) => Ok(()),
+            Response::Err(err) => Err(Error::from_api(err)),
+            _ => Err(Error::Other(format!(
+                "update_leverage: unexpected response type: {resp:?}"
+            ))),
+        }
+    }
+
+    /// Add or remove margin from an isolated position of the multisig account.
+    ///
+    /// See [`Client::update_isolated_margin`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// client
+    ///     .multi_sig(&lead, multisig_addr, nonce)
+    ///     .signers(&signers)
+    ///     .update_isolated_margin(&market, dec!(25), None, None)
+    ///     .await?;
+    /// ```
+    pub async fn update_isolated_margin(
+        &self,
+        market: &PerpMarket,
+        usd: Decimal,
+        vault_address: Option<Address>,
+        expires_after: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        let update = UpdateIsolatedMargin::from_usd(market, usd)?;
+
+        let action = multisig_collect_signatures(
+            self.lead.address(),
+            self.multi_sig_user,
+            self.signers.iter().copied(),
+            self.signatures.iter().copied(),
+            Action::UpdateIsolatedMargin(update),
+            self.nonce,
+            self.client.chain,
+        )
+        .await?;
+
+        let resp = self
+            .client
+            .sign_and_send(self.lead, action, self.nonce, vault_address, expires_after)
+            .await?;
+
+        match resp {
+            Response::Ok(OkResponse::Default) => Ok(()),
+            Response::Err(err) => Err(Error::from_api(err)),
+            _ => Err(Error::Other(format!(
+                "update_isolated_margin: unexpected response type: {resp:?}"
+            ))),
         }
     }
 
@@ -2218,8 +3530,10 @@ where
 
         match resp {
             Response::Ok(OkResponse::Default) => Ok(()),
-            Response::Err(err) => anyhow::bail!("approve_agent: {err}"),
-            _ => anyhow::bail!("approve_agent: unexpected response type: {resp:?}"),
+            Response::Err(err) => Err(Error::from_api(err)),
+            _ => Err(Error::Other(format!(
+                "approve_agent: unexpected response type: {resp:?}"
+            ))),
         }
     }
 
@@ -2270,8 +3584,203 @@ where
 
         match resp {
             Response::Ok(OkResponse::Default) => Ok(()),
-            Response::Err(err) => anyhow::bail!("convert_to_normal_user: {err}"),
-            _ => anyhow::bail!("convert_to_normal_user: unexpected response type: {resp:?}"),
+            Response::Err(err) => Err(Error::from_api(err)),
+            _ => Err(Error::Other(format!(
+                "convert_to_normal_user: unexpected response type: {resp:?}"
+            ))),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use rust_decimal::dec;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+    use crate::hypercore::{
+        PrivateKeySigner,
+        testing::MockServer,
+        types::{Bbo, OrderTypePlacement, TimeInForce},
+    };
+
+    #[tokio::test]
+    async fn test_market_metadata_errors() {
+        // nothing listens on port 1
+        let client = Client::new(Chain::Mainnet).with_url("http://127.0.0.1:1".parse().unwrap());
+        assert!(matches!(client.perps().await, Err(Error::Network(_))));
+        assert!(matches!(client.spot().await, Err(Error::Network(_))));
+        assert!(matches!(client.perp_dexs().await, Err(Error::Network(_))));
+    }
+
+    #[tokio::test]
+    async fn test_market_orders() {
+        let server = MockServer::start().await.unwrap();
+        let signer = PrivateKeySigner::random();
+        let client = server.client();
+        server.set_balance(signer.address(), dec!(100));
+        server.set_mid("ETH", dec!(3000));
+
+        let perps = client.perps().await.unwrap();
+        let eth = perps.iter().find(|market| market.name == "ETH").unwrap();
+
+        let order = MarketOrder::buy(eth, dec!(0.01)).with_slippage(Slippage::bps(100));
+        let status = client
+            .market_open(&signer, order, 1, None, None)
+            .await
+            .unwrap();
+        assert!(matches!(
+            status,
+            OrderResponseStatus::Filled { avg_px, .. } if avg_px == dec!(3000)
+        ));
+        let (_, action) = server.actions().pop().unwrap();
+        let Action::Order(batch) = action else {
+            panic!("expected an order action");
+        };
+        assert_eq!(batch.orders[0].limit_px, dec!(3030));
+
+        // The mid of the supplied market data must be for the same coin.
+        let bbo = Bbo {
+            coin: "BTC".into(),
+            time: 0,
+            bbo: (None, None),
+        };
+        let order =
+            MarketOrder::sell(eth, dec!(0.01)).with_slippage(Slippage::bps(100).with_bbo(&bbo));
+        let err = client
+            .market_open(&signer, order, 2, None, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidOrder { .. }));
+
+        let err = client
+            .market_close(&signer, eth, Slippage::default(), 3, None, None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Invalid order: ETH: no open position");
+    }
+
+    #[tokio::test]
+    async fn test_paginate() {
+        // (time, id) records, 3 per page
+        let records: Vec<(u64, u64)> = vec![(1, 0), (2, 1), (2, 2), (2, 3), (3, 4), (5, 5), (6, 6)];
+        let fetch = |end: u64| {
+            let records = records.clone();
+            move |start: u64| {
+                let page: Vec<_> = records
+                    .iter()
+                    .filter(|record| (start..=end).contains(&record.0))
+                    .take(3)
+                    .copied()
+                    .collect();
+                async move { Ok(page) }
+            }
+        };
+        let time = |record: &(u64, u64)| record.0;
+        let key = |record: &(u64, u64)| record.1;
+
+        let result: Vec<_> = paginate(0, None, 3, time, key, fetch(u64::MAX))
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(result, records);
+
+        // the end of the range is excluded
+        let result: Vec<_> = paginate(2, Some(5), 3, time, key, fetch(5))
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(result, records[1..5]);
+    }
+
+    /// Serves one canned reply per connection and returns the number of requests received.
+    ///
+    /// Connections past the last reply are dropped after reading the request.
+    async fn serve_actions(replies: Vec<&'static str>) -> (Url, Arc<AtomicUsize>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        tokio::spawn(async move {
+            let mut replies = replies.into_iter();
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = vec![0; 1 << 16];
+                let mut len = 0;
+                // read the headers and the body announced by content-length
+                loop {
+                    len += stream.read(&mut buf[len..]).await.unwrap();
+                    let text = String::from_utf8_lossy(&buf[..len]);
+                    if let Some(end) = text.find("\r\n\r\n") {
+                        let body = text
+                            .lines()
+                            .find_map(|line| {
+                                line.to_lowercase()
+                                    .strip_prefix("content-length: ")
+                                    .map(str::to_owned)
+                            })
+                            .and_then(|len| len.trim().parse::<usize>().ok())
+                            .unwrap_or(0);
+                        if len >= end + 4 + body {
+                            break;
+                        }
+                    }
+                }
+                counter.fetch_add(1, Ordering::SeqCst);
+
+                if let Some(reply) = replies.next() {
+                    stream.write_all(reply.as_bytes()).await.unwrap();
+                }
+            }
+        });
+        (url.parse().unwrap(), requests)
+    }
+
+    #[tokio::test]
+    async fn test_action_retries() {
+        const RATE_LIMITED: &str =
+            "HTTP/1.1 429 Too Many Requests\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+        const RESTING: &str = concat!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 87\r\n",
+            "Connection: close\r\n\r\n",
+            r#"{"status":"ok","response":{"type":"order","data":{"statuses":[{"resting":{"oid":1}}]}}}"#,
+        );
+        let signer = PrivateKeySigner::random();
+        let retry = RetryPolicy::default().with_initial_backoff(Duration::from_millis(1));
+        let batch = || BatchOrder {
+            orders: vec![OrderRequest {
+                asset: 0,
+                is_buy: true,
+                limit_px: dec!(50000),
+                sz: dec!(0.01),
+                reduce_only: false,
+                order_type: OrderTypePlacement::Limit {
+                    tif: TimeInForce::Gtc,
+                },
+                cloid: Cloid::ZERO,
+            }],
+            grouping: OrderGrouping::Na,
+            builder: None,
+        };
+
+        // rate limited actions never reached the exchange and are retried
+        let (url, requests) = serve_actions(vec![RATE_LIMITED, RATE_LIMITED, RESTING]).await;
+        let client = Client::new(Chain::Mainnet).with_url(url).with_retry(retry);
+        let statuses = client.place(&signer, batch(), 1, None, None).await.unwrap();
+        assert!(matches!(
+            statuses[0],
+            OrderResponseStatus::Resting { oid: 1, .. }
+        ));
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+
+        // an action dropped after being sent may have been executed and isn't resent
+        let (url, requests) = serve_actions(vec![]).await;
+        let client = Client::new(Chain::Mainnet).with_url(url).with_retry(retry);
+        assert!(client.place(&signer, batch(), 2, None, None).await.is_err());
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+}
@@ -7,6 +7,11 @@
 //!
 //! - [`HttpClient`]: HTTP client for API interactions (orders, queries, transfers)
 //! - [`WebSocket`]: Real-time WebSocket connection for market data and order updates
+//! - [`ConnectionPool`]: Subscriptions sharded across several WebSocket connections
+//! - [`OrderBook`]: Local L2 order book maintained from the WebSocket feed
+//! - [`CandleAggregator`]: Higher interval candles and candle close events
+//! - [`OrderManager`]: Order lifecycle tracking across REST and WebSocket
+//! - [`OrderBuilder`]: Orders validated and rounded against the exchange rules
 //! - Market types: [`PerpMarket`], [`SpotMarket`], [`SpotToken`]
 //! - Order types and operations in the [`types`] module
 //!
@@ -46,7 +51,7 @@
 //!
 //! // Subscribe to trades and order book
 //! ws.subscribe(Subscription::Trades { coin: "BTC".into() });
-//! ws.subscribe(Subscription::L2Book { coin: "BTC".into() });
+//! ws.subscribe(Subscription::l2_book("BTC"));
 //!
 //! while let Some(event) = ws.next().await {
 //!     let Event::Message(msg) = event else { continue };
@@ -81,14 +86,24 @@
 //! # }
 //! ```
 
+pub mod book;
+pub mod candles;
 pub mod error;
 pub mod http;
+pub mod order_builder;
+pub mod orders;
+pub mod pool;
+pub mod ratelimit;
 pub mod signing;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod types;
+pub mod user_stream;
 mod utils;
 pub mod ws;
 
 use std::{
+    collections::HashMap,
     fmt,
     hash::Hash,
     sync::atomic::{self, AtomicU64},
@@ -101,16 +116,32 @@ use alloy::{
     primitives::{B128, U256, address},
 };
 use anyhow::Context;
+/// Re-export the local order book.
+pub use book::OrderBook;
+/// Re-export the candle aggregator.
+pub use candles::{CandleAggregator, CandleEvent};
 use chrono::Utc;
 use either::Either;
 /// Re-export error types.
 pub use error::{ActionError, Error};
+/// Re-export the order builder.
+pub use order_builder::{
+    Bracket, BracketStatus, MarketOrder, OrderBuilder, PositionTpsl, Slippage, TpSlLeg,
+};
+/// Re-export the order lifecycle tracker.
+pub use orders::{OrderManager, OrderState, TrackedOrder};
+/// Re-export the websocket connection pool.
+pub use pool::{ConnectionPool, PoolEvent};
+/// Re-export rate limiting types.
+pub use ratelimit::{RateLimiter, RetryPolicy};
 use reqwest::IntoUrl;
 use rust_decimal::{Decimal, MathematicalOps, RoundingStrategy, prelude::ToPrimitive};
 use serde::{Deserialize, Serialize};
 /// Re-import types.
 pub use types::*;
 use url::Url;
+/// Re-export the gap-free user feed.
+pub use user_stream::{UserStream, UserStreamEvent};
 
 use crate::{
     Address,
@@ -694,7 +725,7 @@ impl PriceTick {
                 RoundingStrategy::ToNegativeInfinity
             }
         };
-        let rounded = price.round_dp_with_strategy(tick.scale(), strategy);
+        let rounded = price.round_dp_with_strategy(tick.normalize().scale(), strategy);
         Some(rounded)
     }
 }
@@ -751,6 +782,14 @@ impl PerpMarket {
         &self.name
     }
 
+    /// Returns the HIP-3 dex of this market, or `None` for the default dex.
+    ///
+    /// The dex is the prefix of the market name (`"xyz"` for `"xyz:EURC"`).
+    #[must_use]
+    pub fn dex(&self) -> Option<&str> {
+        self.name.split_once(':').map(|(dex, _)| dex)
+    }
+
     /// Returns the price tick configuration for this market.
     #[must_use]
     pub fn tick_table(&self) -> &PriceTick {
@@ -1006,6 +1045,23 @@ mod tick_tests {
         }
     }
 
+    #[test]
+    fn test_round_by_side() {
+        let table = PriceTick::for_perp(5);
+        assert_eq!(
+            table.round_by_side(Side::Bid, dec!(99.666), false),
+            Some(dec!(99.7))
+        );
+        assert_eq!(
+            table.round_by_side(Side::Ask, dec!(99.666), false),
+            Some(dec!(99.6))
+        );
+        assert_eq!(
+            table.round_by_side(Side::Bid, dec!(99.666), true),
+            Some(dec!(99.6))
+        );
+    }
+
     #[test]
     fn test_spot() {
         let prices = vec![
@@ -1264,6 +1320,49 @@ pub async fn spot_markets(
     client: reqwest::Client,
 ) -> anyhow::Result<Vec<SpotMarket>> {
     let data = raw_spot_markets(core_url, client).await?;
+    Ok(build_spot_markets(data))
+}
+
+/// Fetches all spot markets along with their current asset contexts.
+///
+/// Returns each [`SpotMarket`] joined with its [`SpotAssetContext`] (mark price,
+/// mid price, 24h volume, supply) using a single `spotMetaAndAssetCtxs` request.
+///
+/// # Example
+///
+/// ```no_run
+/// use hypersdk::hypercore;
+///
+/// # async fn example() -> anyhow::Result<()> {
+/// let url = hypercore::mainnet_url();
+/// let client = reqwest::Client::new();
+/// let markets = hypercore::spot_markets_with_ctxs(url, client).await?;
+///
+/// for (market, ctx) in markets {
+///     println!("{}: mark {:?} volume {}", market.symbol(), ctx.mark_px, ctx.day_ntl_vlm);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub async fn spot_markets_with_ctxs(
+    core_url: impl IntoUrl,
+    client: reqwest::Client,
+) -> anyhow::Result<Vec<(SpotMarket, SpotAssetContext)>> {
+    let mut url = core_url.into_url()?;
+    url.set_path("/info");
+
+    let resp = client
+        .post(url)
+        .json(&InfoRequest::SpotMetaAndAssetCtxs)
+        .send()
+        .await
+        .context("spotMetaAndAssetCtxs")?;
+    let (data, ctxs): (SpotTokens, Vec<SpotAssetContext>) = resp.json().await?;
+
+    Ok(join_spot_ctxs(build_spot_markets(data), ctxs))
+}
+
+fn build_spot_markets(data: SpotTokens) -> Vec<SpotMarket> {
     let mut markets = Vec::with_capacity(data.universe.len());
 
     let spot_tokens: Vec<_> = data.tokens.iter().cloned().map(SpotToken::from).collect();
@@ -1288,7 +1387,28 @@ pub async fn spot_markets(
         });
     }
 
-    Ok(markets)
+    markets
+}
+
+/// Joins spot markets with their contexts by coin name.
+///
+/// Markets without a context are skipped.
+fn join_spot_ctxs(
+    markets: Vec<SpotMarket>,
+    ctxs: Vec<SpotAssetContext>,
+) -> Vec<(SpotMarket, SpotAssetContext)> {
+    let mut ctxs: HashMap<_, _> = ctxs
+        .into_iter()
+        .map(|ctx| (ctx.coin.clone(), ctx))
+        .collect();
+
+    markets
+        .into_iter()
+        .filter_map(|market| {
+            let ctx = ctxs.remove(&market.name)?;
+            Some((market, ctx))
+        })
+        .collect()
 }
 
 /// Fetches all available perpetual futures DEXes from HyperCore.
@@ -1332,7 +1452,11 @@ pub async fn perp_dexs(
         .context("info")?;
 
     let dexes: Vec<Option<PerpDex>> = resp.json().await?;
-    let dex_list = dexes
+    Ok(build_dexes(dexes))
+}
+
+fn build_dexes(dexes: Vec<Option<PerpDex>>) -> Vec<Dex> {
+    dexes
         .into_iter()
         .enumerate()
         .filter_map(|(index, dex)| {
@@ -1342,9 +1466,7 @@ pub async fn perp_dexs(
                 deployer_fee_scale: dex.deployer_fee_scale,
             })
         })
-        .collect();
-
-    Ok(dex_list)
+        .collect()
 }
 
 #[derive(Deserialize)]
@@ -1377,12 +1499,77 @@ pub async fn perp_markets(
         .await
         .context("meta")?;
     let data: PerpTokens = resp.json().await?;
+
+    Ok(build_perp_markets(data, &spot, dex.as_ref()))
+}
+
+/// Fetches all perpetual markets along with their current asset contexts.
+///
+/// Returns each [`PerpMarket`] joined with its [`AssetContext`] (funding, open interest,
+/// mark and oracle prices, premium, 24h volume) using a single `metaAndAssetCtxs` request.
+///
+/// Pass a [`Dex`] to query the markets of a HIP-3 DEX instead of the default one.
+///
+/// # Example
+///
+/// ```no_run
+/// use hypersdk::hypercore;
+///
+/// # async fn example() -> anyhow::Result<()> {
+/// let url = hypercore::mainnet_url();
+/// let client = reqwest::Client::new();
+/// let markets = hypercore::perp_markets_with_ctxs(url, client, None).await?;
+///
+/// for (market, ctx) in markets {
+///     println!("{}: mark {:?} OI {}", market.name, ctx.mark_px, ctx.open_interest);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub async fn perp_markets_with_ctxs(
+    core_url: impl IntoUrl,
+    client: reqwest::Client,
+    dex: Option<Dex>,
+) -> anyhow::Result<Vec<(PerpMarket, AssetContext)>> {
+    let mut url = core_url.into_url()?;
+    url.set_path("/info");
+
+    // get it to gather the collateral token
+    let spot = raw_spot_markets(url.clone(), client.clone()).await?;
+    let resp = client
+        .post(url)
+        .json(&InfoRequest::MetaAndAssetCtxs {
+            dex: dex.as_ref().map(|dex| dex.name.clone()),
+        })
+        .send()
+        .await
+        .context("metaAndAssetCtxs")?;
+    let (data, ctxs): (PerpTokens, Vec<AssetContext>) = resp.json().await?;
+
+    join_perp_ctxs(build_perp_markets(data, &spot, dex.as_ref()), ctxs)
+}
+
+/// Joins perpetual markets with their contexts, which are returned in the same order.
+fn join_perp_ctxs(
+    perps: Vec<PerpMarket>,
+    ctxs: Vec<AssetContext>,
+) -> anyhow::Result<Vec<(PerpMarket, AssetContext)>> {
+    anyhow::ensure!(
+        perps.len() == ctxs.len(),
+        "metaAndAssetCtxs: got {} markets and {} contexts",
+        perps.len(),
+        ctxs.len()
+    );
+
+    Ok(perps.into_iter().zip(ctxs).collect())
+}
+
+fn build_perp_markets(data: PerpTokens, spot: &SpotTokens, dex: Option<&Dex>) -> Vec<PerpMarket> {
     let collateral = &spot.tokens[data.collateral_token];
     let collateral = SpotToken::from(collateral.clone());
-    let dex_index = dex.as_ref().map(|dex| dex.index).unwrap_or_default();
+    let dex_index = dex.map(|dex| dex.index).unwrap_or_default();
 
-    let perps = data
-        .universe
+    data.universe
         .into_iter()
         .enumerate()
         .map(|(index, perp)| {
@@ -1401,9 +1588,7 @@ pub async fn perp_markets(
                 table: PriceTick::for_perp(perp.sz_decimals),
             }
         })
-        .collect();
-
-    Ok(perps)
+        .collect()
 }
 
 // TODO: perpDexs
@@ -1671,6 +1856,65 @@ mod tests {
         assert!(!spots.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_http_perps_with_ctxs() {
+        let client = hypercore::mainnet();
+        let perps = client.perps_with_ctxs().await.unwrap();
+
+        let (_, ctx) = perps.iter().find(|(m, _)| m.name == "BTC").unwrap();
+        assert!(ctx.mark_px.is_some());
+    }
+
+    #[test]
+    fn test_join_asset_ctxs() {
+        let spot: SpotTokens = serde_json::from_value(serde_json::json!({
+            "universe": [
+                {"tokens": [1, 0], "name": "PURR/USDC", "index": 0},
+                {"tokens": [2, 0], "name": "@1", "index": 1},
+            ],
+            "tokens": [
+                {"name": "USDC", "index": 0, "tokenId": "0x6d1e7cde53ba9467b783cb7c530ce054", "szDecimals": 8, "weiDecimals": 8, "evmContract": null},
+                {"name": "PURR", "index": 1, "tokenId": "0xc1fb593aeffbeb02f85e0308e9956a90", "szDecimals": 0, "weiDecimals": 5, "evmContract": null},
+                {"name": "HFUN", "index": 2, "tokenId": "0xbaf265ef389da684513d98d68edf4eae", "szDecimals": 2, "weiDecimals": 8, "evmContract": null},
+            ],
+        }))
+        .unwrap();
+
+        let ctxs: Vec<SpotAssetContext> = serde_json::from_value(serde_json::json!([
+            {"coin": "@1", "dayNtlVlm": "10.5", "markPx": "20.1", "midPx": null, "prevDayPx": "19.8", "circulatingSupply": "1000", "totalSupply": "1000", "dayBaseVlm": "0.5"},
+            {"coin": "PURR/USDC", "dayNtlVlm": "8906.0", "markPx": "0.14", "midPx": "0.1405", "prevDayPx": "0.15", "circulatingSupply": "598", "totalSupply": "598", "dayBaseVlm": "63612"},
+        ]))
+        .unwrap();
+
+        let perp: PerpTokens = serde_json::from_value(serde_json::json!({
+            "universe": [
+                {"name": "xyz:XYZ100", "szDecimals": 4, "maxLeverage": 20},
+                {"name": "xyz:TSLA", "szDecimals": 3, "maxLeverage": 10, "onlyIsolated": true},
+            ],
+            "collateralToken": 0,
+        }))
+        .unwrap();
+        let perp_ctxs: Vec<AssetContext> = serde_json::from_value(serde_json::json!([
+            {"funding": "0.0000125", "openInterest": "1500.5", "markPx": "25000", "oraclePx": "25001", "midPx": "25000.5", "premium": "0.0001", "prevDayPx": "24000", "dayNtlVlm": "1000000", "impactPxs": ["25000", "25001"], "dayBaseVlm": "40"},
+            {"funding": "-0.00001", "openInterest": "300", "markPx": "420", "oraclePx": "421", "midPx": null, "premium": "-0.0002", "prevDayPx": "410", "dayNtlVlm": "5000", "impactPxs": null, "dayBaseVlm": "12"},
+        ]))
+        .unwrap();
+
+        let perps = build_perp_markets(perp, &spot, Some(&Dex::new("xyz".into(), 1)));
+        let perps: Vec<_> = perps.into_iter().zip(perp_ctxs).collect();
+        assert_eq!(perps[0].0.index, 110_000);
+        assert_eq!(perps[1].0.name, "xyz:TSLA");
+        assert_eq!(perps[1].0.collateral.name, "USDC");
+        assert_eq!(perps[1].1.mark_px, Some(rust_decimal::dec!(420)));
+
+        let spots = join_spot_ctxs(build_spot_markets(spot), ctxs);
+        assert_eq!(spots.len(), 2);
+        assert_eq!(spots[0].0.name, "PURR/USDC");
+        assert_eq!(spots[0].1.mid_px, Some(rust_decimal::dec!(0.1405)));
+        assert_eq!(spots[1].0.index, 10_001);
+        assert_eq!(spots[1].1.mid_px, None);
+    }
+
     #[test]
     fn test_nonce_handler_uniqueness_single_thread() {
         let handler = NonceHandler::default();
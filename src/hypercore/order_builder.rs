@@ -0,0 +1,1158 @@
+//! Order construction with local validation.
+//!
+//! [`OrderBuilder`] turns a market, a side, a size and a price into an
+//! [`OrderRequest`] that follows the exchange rules:
+//!
+//! - the asset index is taken from the market,
+//! - the price is rounded to a valid tick with [`PriceTick::round_by_side`],
+//! - the size is truncated to the market's `sz_decimals`,
+//! - the order value must be at least [`MIN_NOTIONAL`] (except for reduce-only orders),
+//! - the requested leverage must not exceed the market's maximum.
+//!
+//! Invalid orders are rejected with [`Error::InvalidOrder`] before anything is signed,
+//! instead of failing on the server with a less specific error.
+//!
+//! [`Bracket`] and [`PositionTpsl`] group take-profit and stop-loss legs with an entry
+//! order or an open position, round their prices to the tick of the market and check
+//! that each rounded trigger price is on the right side.
+//! They are placed with [`HttpClient::place_bracket`](crate::hypercore::HttpClient::place_bracket)
+//! and [`HttpClient::attach_position_tpsl`](crate::hypercore::HttpClient::attach_position_tpsl).
+//!
+//! [`MarketOrder`] and [`Slippage`] describe IOC orders priced at a slippage from the
+//! mid price, placed with [`HttpClient::market_open`](crate::hypercore::HttpClient::market_open)
+//! and [`HttpClient::market_close`](crate::hypercore::HttpClient::market_close).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use hypersdk::hypercore::{self, OrderBuilder, types::*};
+//! use rust_decimal::dec;
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let client = hypercore::mainnet();
+//! let perps = client.perps().await?;
+//! let btc = perps.iter().find(|m| m.name == "BTC").unwrap();
+//!
+//! // Resting limit buy, rounded down to the tick.
+//! let bid = OrderBuilder::perp(btc)
+//!     .buy(dec!(0.01))
+//!     .limit_px(dec!(50000.37))
+//!     .build()?;
+//!
+//! // IOC sell at most 1% below the mid price.
+//! let ask = OrderBuilder::perp(btc)
+//!     .sell(dec!(0.01))
+//!     .slippage(dec!(51000), dec!(0.01))
+//!     .build()?;
+//!
+//! let order = BatchOrder {
+//!     orders: vec![bid, ask],
+//!     grouping: OrderGrouping::Na,
+//!     builder: None,
+//! };
+//! # Ok(())
+//! # }
+//! ```
+
+use rust_decimal::{Decimal, RoundingStrategy};
+
+use super::{
+    Cloid, PerpMarket, PriceTick, SpotMarket,
+    error::{Error, Result},
+    types::{
+        BatchOrder, Bbo, L2Book, OrderGrouping, OrderRequest, OrderResponseStatus,
+        OrderTypePlacement, PositionData, Side, TimeInForce, TpSl,
+    },
+};
+
+/// Minimum order value enforced by the exchange, in quote units (USDC).
+pub const MIN_NOTIONAL: Decimal = Decimal::TEN;
+
+/// Default slippage of market orders, in basis points (5%).
+pub const DEFAULT_SLIPPAGE_BPS: u32 = 500;
+
+/// How the limit price of the order is determined.
+#[derive(Debug, Clone, Copy)]
+enum Price {
+    /// Explicit limit price.
+    Limit(Decimal),
+    /// Price derived from a mid price and a maximum slippage fraction.
+    Slippage { mid: Decimal, slippage: Decimal },
+}
+
+/// Builder for validated [`OrderRequest`]s.
+///
+/// See the [module documentation](self) for the rules that are applied.
+#[derive(Debug, Clone)]
+pub struct OrderBuilder {
+    asset: usize,
+    name: String,
+    sz_decimals: i64,
+    table: PriceTick,
+    max_leverage: Option<u64>,
+    side: Option<Side>,
+    sz: Option<Decimal>,
+    price: Option<Price>,
+    tif: Option<TimeInForce>,
+    reduce_only: bool,
+    cloid: Cloid,
+    leverage: Option<u64>,
+}
+
+impl OrderBuilder {
+    /// Creates a builder for an order on a perpetual market.
+    #[must_use]
+    pub fn perp(market: &PerpMarket) -> Self {
+        Self::new(
+            market.index,
+            market.name.clone(),
+            market.sz_decimals,
+            market.table,
+            Some(market.max_leverage),
+        )
+    }
+
+    /// Creates a builder for an order on a spot market.
+    ///
+    /// The size is expressed in units of the base token.
+    #[must_use]
+    pub fn spot(market: &SpotMarket) -> Self {
+        Self::new(
+            market.index,
+            market.name.clone(),
+            market.base().sz_decimals,
+            market.table,
+            None,
+        )
+    }
+
+    fn new(
+        asset: usize,
+        name: String,
+        sz_decimals: i64,
+        table: PriceTick,
+        max_leverage: Option<u64>,
+    ) -> Self {
+        Self {
+            asset,
+            name,
+            sz_decimals,
+            table,
+            max_leverage,
+            side: None,
+            sz: None,
+            price: None,
+            tif: None,
+            reduce_only: false,
+            cloid: Cloid::ZERO,
+            leverage: None,
+        }
+    }
+
+    /// Sets the side of the order.
+    #[must_use]
+    pub fn side(mut self, side: Side) -> Self {
+        self.side = Some(side);
+        self
+    }
+
+    /// Sets the size of the order in base asset units.
+    ///
+    /// The size is truncated to the market's `sz_decimals` when the order is built.
+    #[must_use]
+    pub fn size(mut self, sz: Decimal) -> Self {
+        self.sz = Some(sz);
+        self
+    }
+
+    /// Buys `sz` units of the asset.
+    #[must_use]
+    pub fn buy(self, sz: Decimal) -> Self {
+        self.side(Side::Bid).size(sz)
+    }
+
+    /// Sells `sz` units of the asset.
+    #[must_use]
+    pub fn sell(self, sz: Decimal) -> Self {
+        self.side(Side::Ask).size(sz)
+    }
+
+    /// Sets an explicit limit price.
+    ///
+    /// The price is rounded conservatively to a valid tick: down for buys, up for sells.
+    #[must_use]
+    pub fn limit_px(mut self, px: Decimal) -> Self {
+        self.price = Some(Price::Limit(px));
+        self
+    }
+
+    /// Prices the order at `slippage` away from `mid`, in the direction that crosses the book.
+    ///
+    /// `slippage` is a fraction (`0.01` for 1%). The price is rounded towards the mid,
+    /// so the order never executes beyond the requested slippage. Unless a time in
+    /// force is set explicitly, the order is sent as [`TimeInForce::Ioc`].
+    #[must_use]
+    pub fn slippage(mut self, mid: Decimal, slippage: Decimal) -> Self {
+        self.price = Some(Price::Slippage { mid, slippage });
+        self
+    }
+
+    /// Sets the time in force.
+    ///
+    /// Defaults to [`TimeInForce::Gtc`] for limit prices and [`TimeInForce::Ioc`]
+    /// for prices derived from slippage.
+    #[must_use]
+    pub fn tif(mut self, tif: TimeInForce) -> Self {
+        self.tif = Some(tif);
+        self
+    }
+
+    /// Makes the order reduce-only.
+    #[must_use]
+    pub fn reduce_only(mut self, reduce_only: bool) -> Self {
+        self.reduce_only = reduce_only;
+        self
+    }
+
+    /// Sets the client order ID.
+    #[must_use]
+    pub fn cloid(mut self, cloid: Cloid) -> Self {
+        self.cloid = cloid;
+        self
+    }
+
+    /// Sets the leverage the order is intended to be placed with.
+    ///
+    /// Leverage is not part of the order itself; it is only checked against the
+    /// market's maximum. Use `update_leverage` to change it on the account.
+    #[must_use]
+    pub fn leverage(mut self, leverage: u64) -> Self {
+        self.leverage = Some(leverage);
+        self
+    }
+
+    /// Validates the order and returns the request to sign.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidOrder`] if the side, size or price is missing, or if
+    /// the order breaks one of the exchange rules.
+    pub fn build(self) -> Result<OrderRequest> {
+        let Some(side) = self.side else {
+            return Err(self.invalid("side is not set"));
+        };
+        let Some(sz) = self.sz else {
+            return Err(self.invalid("size is not set"));
+        };
+        let Some(price) = self.price else {
+            return Err(self.invalid("price is not set"));
+        };
+
+        if let Some(leverage) = self.leverage {
+            match self.max_leverage {
+                None => return Err(self.invalid("spot orders can't use leverage")),
+                Some(_) if leverage == 0 => {
+                    return Err(self.invalid("leverage must be at least 1"));
+                }
+                Some(max) if leverage > max => {
+                    return Err(self.invalid(format!(
+                        "leverage {leverage}x exceeds the maximum of {max}x"
+                    )));
+                }
+                Some(_) => {}
+            }
+        }
+
+        let sz = sz
+            .round_dp_with_strategy(self.sz_decimals.max(0) as u32, RoundingStrategy::ToZero)
+            .normalize();
+        if sz <= Decimal::ZERO {
+            return Err(self.invalid(format!(
+                "size must be positive with at most {} decimals",
+                self.sz_decimals
+            )));
+        }
+
+        let (px, tif) = match price {
+            Price::Limit(px) => (px, TimeInForce::Gtc),
+            Price::Slippage { mid, slippage } => {
+                if mid <= Decimal::ZERO {
+                    return Err(self.invalid("mid price must be positive"));
+                }
+                if slippage < Decimal::ZERO || slippage >= Decimal::ONE {
+                    return Err(self.invalid("slippage must be between 0 and 1"));
+                }
+                let px = match side {
+                    Side::Bid => mid * (Decimal::ONE + slippage),
+                    Side::Ask => mid * (Decimal::ONE - slippage),
+                };
+                (px, TimeInForce::Ioc)
+            }
+        };
+        if px <= Decimal::ZERO {
+            return Err(self.invalid("price must be positive"));
+        }
+        let Some(limit_px) = self
+            .table
+            .round_by_side(side, px, true)
+            .map(|px| px.normalize())
+            .filter(|px| !px.is_zero())
+        else {
+            return Err(self.invalid(format!("price {px} can't be rounded to a valid tick")));
+        };
+
+        // Reduce-only orders may close positions worth less than the minimum.
+        let notional = limit_px * sz;
+        if !self.reduce_only && notional < MIN_NOTIONAL {
+            return Err(self.invalid(format!(
+                "order value {notional} is below the minimum of {MIN_NOTIONAL}"
+            )));
+        }
+
+        Ok(OrderRequest {
+            asset: self.asset,
+            is_buy: side == Side::Bid,
+            limit_px,
+            sz,
+            reduce_only: self.reduce_only,
+            order_type: OrderTypePlacement::Limit {
+                tif: self.tif.unwrap_or(tif),
+            },
+            cloid: self.cloid,
+        })
+    }
+
+    fn invalid(&self, message: impl std::fmt::Display) -> Error {
+        Error::InvalidOrder {
+            message: format!("{}: {message}", self.name),
+        }
+    }
+}
+
+/// Take-profit or stop-loss leg of a [`Bracket`] or [`PositionTpsl`].
+///
+/// Prices are rounded to a valid tick when the batch is built, away from the entry
+/// or mark price.
+#[derive(Debug, Clone, Copy)]
+pub struct TpSlLeg {
+    /// Price that triggers the leg.
+    pub trigger_px: Decimal,
+    /// Limit price of the triggered order, or `None` to execute it at market.
+    pub limit_px: Option<Decimal>,
+    /// Client order ID of the leg.
+    pub cloid: Cloid,
+}
+
+impl TpSlLeg {
+    /// Creates a leg that executes at market once triggered.
+    #[must_use]
+    pub fn market(trigger_px: Decimal) -> Self {
+        Self {
+            trigger_px,
+            limit_px: None,
+            cloid: Cloid::ZERO,
+        }
+    }
+
+    /// Creates a leg that places a limit order at `limit_px` once triggered.
+    #[must_use]
+    pub fn limit(trigger_px: Decimal, limit_px: Decimal) -> Self {
+        Self {
+            trigger_px,
+            limit_px: Some(limit_px),
+            cloid: Cloid::ZERO,
+        }
+    }
+
+    /// Sets the client order ID of the leg.
+    #[must_use]
+    pub fn with_cloid(mut self, cloid: Cloid) -> Self {
+        self.cloid = cloid;
+        self
+    }
+
+    /// Rounds the prices to valid ticks of `market`, away from the reference price
+    /// of a position of the given direction.
+    ///
+    /// Take-profits round on the conservative side of the closing order and
+    /// stop-losses on the aggressive one, so that rounding never moves a trigger
+    /// towards the reference price.
+    fn round(&self, market: &PerpMarket, is_long: bool, tpsl: TpSl) -> Result<Self> {
+        let side = if is_long { Side::Ask } else { Side::Bid };
+        let conservative = matches!(tpsl, TpSl::Tp);
+        let round = |px: Decimal| {
+            market
+                .round_by_side(side, px, conservative)
+                .map(|px| px.normalize())
+                .filter(|px| !px.is_zero())
+                .ok_or_else(|| Error::InvalidOrder {
+                    message: format!(
+                        "{}: price {px} can't be rounded to a valid tick",
+                        market.name
+                    ),
+                })
+        };
+
+        Ok(Self {
+            trigger_px: round(self.trigger_px)?,
+            limit_px: self.limit_px.map(round).transpose()?,
+            cloid: self.cloid,
+        })
+    }
+
+    /// Returns the reduce-only order closing a position of the given direction.
+    fn order(&self, asset: usize, is_long: bool, sz: Decimal, tpsl: TpSl) -> OrderRequest {
+        OrderRequest {
+            asset,
+            is_buy: !is_long,
+            limit_px: self.limit_px.unwrap_or(self.trigger_px),
+            sz,
+            reduce_only: true,
+            order_type: OrderTypePlacement::Trigger {
+                is_market: self.limit_px.is_none(),
+                trigger_px: self.trigger_px,
+                tpsl,
+            },
+            cloid: self.cloid,
+        }
+    }
+}
+
+/// Rounds the legs to the ticks of `market` and checks that the rounded
+/// triggers are on the right side of `reference_px`.
+///
+/// A long position takes profit above the reference and stops out below it,
+/// a short position the other way around.
+fn round_legs(
+    take_profit: Option<TpSlLeg>,
+    stop_loss: Option<TpSlLeg>,
+    market: &PerpMarket,
+    is_long: bool,
+    reference_px: Decimal,
+) -> Result<(Option<TpSlLeg>, Option<TpSlLeg>)> {
+    let invalid = |message: String| Error::InvalidOrder { message };
+    let (above, below) = if is_long {
+        ("above", "below")
+    } else {
+        ("below", "above")
+    };
+
+    if take_profit.is_none() && stop_loss.is_none() {
+        return Err(invalid("no take-profit or stop-loss leg".into()));
+    }
+    for leg in take_profit.iter().chain(&stop_loss) {
+        if leg.trigger_px <= Decimal::ZERO || leg.limit_px.is_some_and(|px| px <= Decimal::ZERO) {
+            return Err(invalid("trigger and limit prices must be positive".into()));
+        }
+    }
+    let take_profit = take_profit
+        .map(|leg| leg.round(market, is_long, TpSl::Tp))
+        .transpose()?;
+    let stop_loss = stop_loss
+        .map(|leg| leg.round(market, is_long, TpSl::Sl))
+        .transpose()?;
+
+    if let Some(tp) = &take_profit
+        && (tp.trigger_px > reference_px) != is_long
+    {
+        return Err(invalid(format!(
+            "take-profit trigger {} must be {above} {reference_px}",
+            tp.trigger_px
+        )));
+    }
+    if let Some(sl) = &stop_loss
+        && (sl.trigger_px < reference_px) != is_long
+    {
+        return Err(invalid(format!(
+            "stop-loss trigger {} must be {below} {reference_px}",
+            sl.trigger_px
+        )));
+    }
+    Ok((take_profit, stop_loss))
+}
+
+/// Entry order with take-profit and stop-loss legs.
+///
+/// The legs are sent in a [`OrderGrouping::NormalTpsl`] group: they close the
+/// entry's size on the opposite side and only become active once the entry fills.
+#[derive(Debug, Clone)]
+pub struct Bracket {
+    /// Entry order, usually built with [`OrderBuilder`].
+    pub entry: OrderRequest,
+    /// Take-profit leg
+    pub take_profit: Option<TpSlLeg>,
+    /// Stop-loss leg
+    pub stop_loss: Option<TpSlLeg>,
+}
+
+impl Bracket {
+    /// Creates a bracket without legs around `entry`.
+    #[must_use]
+    pub fn new(entry: OrderRequest) -> Self {
+        Self {
+            entry,
+            take_profit: None,
+            stop_loss: None,
+        }
+    }
+
+    /// Sets the take-profit leg.
+    #[must_use]
+    pub fn with_take_profit(mut self, leg: TpSlLeg) -> Self {
+        self.take_profit = Some(leg);
+        self
+    }
+
+    /// Sets the stop-loss leg.
+    #[must_use]
+    pub fn with_stop_loss(mut self, leg: TpSlLeg) -> Self {
+        self.stop_loss = Some(leg);
+        self
+    }
+
+    /// Rounds the prices of the legs to the ticks of `market`, validates them
+    /// against the entry and returns the grouped batch.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidOrder`] if the entry is a trigger or reduce-only order
+    /// or is not on `market`, if there is no leg, or if a trigger price is on the
+    /// wrong side of the entry price.
+    pub fn to_batch(&self, market: &PerpMarket) -> Result<BatchOrder> {
+        let entry = &self.entry;
+        if matches!(entry.order_type, OrderTypePlacement::Trigger { .. }) || entry.reduce_only {
+            return Err(Error::InvalidOrder {
+                message: "bracket entry must be a limit order that opens a position".into(),
+            });
+        }
+        if entry.asset != market.index {
+            return Err(Error::InvalidOrder {
+                message: format!("bracket entry is not on {}", market.name),
+            });
+        }
+        let (take_profit, stop_loss) = round_legs(
+            self.take_profit,
+            self.stop_loss,
+            market,
+            entry.is_buy,
+            entry.limit_px,
+        )?;
+
+        let mut orders = vec![entry.clone()];
+        if let Some(leg) = take_profit {
+            orders.push(leg.order(entry.asset, entry.is_buy, entry.sz, TpSl::Tp));
+        }
+        if let Some(leg) = stop_loss {
+            orders.push(leg.order(entry.asset, entry.is_buy, entry.sz, TpSl::Sl));
+        }
+
+        Ok(BatchOrder {
+            orders,
+            grouping: OrderGrouping::NormalTpsl,
+            builder: None,
+        })
+    }
+}
+
+/// Take-profit and stop-loss legs attached to an open position.
+///
+/// The legs are sent in a [`OrderGrouping::PositionTpsl`] group and close the
+/// whole position.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PositionTpsl {
+    /// Take-profit leg
+    pub take_profit: Option<TpSlLeg>,
+    /// Stop-loss leg
+    pub stop_loss: Option<TpSlLeg>,
+}
+
+impl PositionTpsl {
+    /// Sets the take-profit leg.
+    #[must_use]
+    pub fn with_take_profit(mut self, leg: TpSlLeg) -> Self {
+        self.take_profit = Some(leg);
+        self
+    }
+
+    /// Sets the stop-loss leg.
+    #[must_use]
+    pub fn with_stop_loss(mut self, leg: TpSlLeg) -> Self {
+        self.stop_loss = Some(leg);
+        self
+    }
+
+    /// Rounds the prices of the legs to the ticks of `market`, validates them
+    /// against `position` and returns the grouped batch.
+    ///
+    /// Trigger prices are checked against the position's mark price, derived from
+    /// its value and size, so that neither leg triggers immediately.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidOrder`] if the position is empty, if there is no leg,
+    /// or if a trigger price is on the wrong side of the mark price.
+    pub fn to_batch(&self, market: &PerpMarket, position: &PositionData) -> Result<BatchOrder> {
+        let sz = position.abs_size();
+        if sz.is_zero() {
+            return Err(Error::InvalidOrder {
+                message: format!("{}: no open position", position.coin),
+            });
+        }
+        let mark_px = position.position_value / sz;
+        let is_long = position.is_long();
+        let (take_profit, stop_loss) =
+            round_legs(self.take_profit, self.stop_loss, market, is_long, mark_px).map_err(
+                |err| match err {
+                    Error::InvalidOrder { message } => Error::InvalidOrder {
+                        message: format!("{}: {message}", position.coin),
+                    },
+                    err => err,
+                },
+            )?;
+
+        let mut orders = vec![];
+        if let Some(leg) = take_profit {
+            orders.push(leg.order(market.index, is_long, sz, TpSl::Tp));
+        }
+        if let Some(leg) = stop_loss {
+            orders.push(leg.order(market.index, is_long, sz, TpSl::Sl));
+        }
+
+        Ok(BatchOrder {
+            orders,
+            grouping: OrderGrouping::PositionTpsl,
+            builder: None,
+        })
+    }
+}
+
+/// Response status of each leg of a [`Bracket`] or [`PositionTpsl`].
+#[derive(Debug)]
+pub struct BracketStatus {
+    /// Entry order status, `None` for [`PositionTpsl`]
+    pub entry: Option<OrderResponseStatus>,
+    /// Take-profit status, if the leg was sent
+    pub take_profit: Option<OrderResponseStatus>,
+    /// Stop-loss status, if the leg was sent
+    pub stop_loss: Option<OrderResponseStatus>,
+}
+
+impl BracketStatus {
+    /// Maps the statuses returned for `batch` back to its legs.
+    pub(crate) fn new(batch: &BatchOrder, statuses: Vec<OrderResponseStatus>) -> Result<Self> {
+        if batch.orders.len() != statuses.len() {
+            return Err(Error::Other(format!(
+                "expected {} order statuses, got {}",
+                batch.orders.len(),
+                statuses.len()
+            )));
+        }
+
+        let mut status = Self {
+            entry: None,
+            take_profit: None,
+            stop_loss: None,
+        };
+        for (order, leg) in batch.orders.iter().zip(statuses) {
+            let slot = match order.order_type {
+                OrderTypePlacement::Trigger { tpsl: TpSl::Tp, .. } => &mut status.take_profit,
+                OrderTypePlacement::Trigger { tpsl: TpSl::Sl, .. } => &mut status.stop_loss,
+                OrderTypePlacement::Limit { .. } => &mut status.entry,
+            };
+            *slot = Some(leg);
+        }
+        Ok(status)
+    }
+
+    /// Returns true if every leg was accepted.
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        [&self.entry, &self.take_profit, &self.stop_loss]
+            .into_iter()
+            .flatten()
+            .all(OrderResponseStatus::is_ok)
+    }
+}
+
+/// Price a market order's slippage is applied to.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum MarketPrice<'a> {
+    /// Mid price fetched with `all_mids`.
+    Mids,
+    /// Mid price supplied by the caller.
+    Mid(Decimal),
+    /// Mid of a best bid and offer.
+    Bbo(&'a Bbo),
+    /// Mid of an L2 book.
+    Book(&'a L2Book),
+}
+
+/// Slippage of a market order.
+///
+/// The order is priced `bps` basis points away from a reference mid price. By default
+/// the mid is fetched with [`all_mids`](crate::hypercore::HttpClient::all_mids); use
+/// [`with_bbo`](Self::with_bbo) or [`with_book`](Self::with_book) to derive it from
+/// market data the caller already has.
+///
+/// # Example
+///
+/// ```
+/// use hypersdk::hypercore::Slippage;
+///
+/// // 0.5% from the live mid price
+/// let slippage = Slippage::bps(50);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Slippage<'a> {
+    pub(crate) bps: u32,
+    pub(crate) price: MarketPrice<'a>,
+}
+
+impl Default for Slippage<'_> {
+    fn default() -> Self {
+        Self::bps(DEFAULT_SLIPPAGE_BPS)
+    }
+}
+
+impl<'a> Slippage<'a> {
+    /// Creates a slippage of `bps` basis points from the live mid price.
+    #[must_use]
+    pub fn bps(bps: u32) -> Self {
+        Self {
+            bps,
+            price: MarketPrice::Mids,
+        }
+    }
+
+    /// Applies the slippage to `mid` instead of the live mid price.
+    #[must_use]
+    pub fn with_mid(mut self, mid: Decimal) -> Self {
+        self.price = MarketPrice::Mid(mid);
+        self
+    }
+
+    /// Applies the slippage to the mid of `bbo`.
+    #[must_use]
+    pub fn with_bbo(mut self, bbo: &'a Bbo) -> Self {
+        self.price = MarketPrice::Bbo(bbo);
+        self
+    }
+
+    /// Applies the slippage to the mid of `book`.
+    #[must_use]
+    pub fn with_book(mut self, book: &'a L2Book) -> Self {
+        self.price = MarketPrice::Book(book);
+        self
+    }
+
+    /// Returns the slippage as a fraction (`0.01` for 100 bps).
+    #[must_use]
+    pub fn fraction(&self) -> Decimal {
+        Decimal::new(self.bps.into(), 4)
+    }
+}
+
+/// Market order on a perpetual market, placed with
+/// [`HttpClient::market_open`](crate::hypercore::HttpClient::market_open).
+#[derive(Debug, Clone, Copy)]
+pub struct MarketOrder<'a> {
+    /// Market to trade
+    pub market: &'a PerpMarket,
+    /// Side of the order
+    pub side: Side,
+    /// Size in base asset units
+    pub sz: Decimal,
+    /// Slippage from the mid price
+    pub slippage: Slippage<'a>,
+    /// Client order ID
+    pub cloid: Cloid,
+}
+
+impl<'a> MarketOrder<'a> {
+    /// Creates a market buy of `sz` with the default slippage.
+    #[must_use]
+    pub fn buy(market: &'a PerpMarket, sz: Decimal) -> Self {
+        Self::new(market, Side::Bid, sz)
+    }
+
+    /// Creates a market sell of `sz` with the default slippage.
+    #[must_use]
+    pub fn sell(market: &'a PerpMarket, sz: Decimal) -> Self {
+        Self::new(market, Side::Ask, sz)
+    }
+
+    fn new(market: &'a PerpMarket, side: Side, sz: Decimal) -> Self {
+        Self {
+            market,
+            side,
+            sz,
+            slippage: Slippage::default(),
+            cloid: Cloid::ZERO,
+        }
+    }
+
+    /// Sets the slippage.
+    #[must_use]
+    pub fn with_slippage(mut self, slippage: Slippage<'a>) -> Self {
+        self.slippage = slippage;
+        self
+    }
+
+    /// Sets the client order ID.
+    #[must_use]
+    pub fn with_cloid(mut self, cloid: Cloid) -> Self {
+        self.cloid = cloid;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::dec;
+
+    use super::*;
+    use crate::hypercore::SpotToken;
+
+    fn token(name: &str, index: u32, sz_decimals: i64) -> SpotToken {
+        SpotToken {
+            name: name.into(),
+            index,
+            token_id: Default::default(),
+            evm_contract: None,
+            cross_chain_address: None,
+            sz_decimals,
+            wei_decimals: 8,
+            evm_extra_decimals: 0,
+        }
+    }
+
+    fn btc() -> PerpMarket {
+        PerpMarket {
+            name: "BTC".into(),
+            index: 0,
+            sz_decimals: 5,
+            collateral: token("USDC", 0, 8),
+            max_leverage: 40,
+            isolated_margin: false,
+            margin_mode: None,
+            growth_mode: false,
+            aligned_quote_token: false,
+            table: PriceTick::for_perp(5),
+        }
+    }
+
+    fn purr() -> SpotMarket {
+        SpotMarket {
+            name: "PURR/USDC".into(),
+            index: 10_000,
+            tokens: [token("PURR", 1, 0), token("USDC", 0, 8)],
+            table: PriceTick::for_spot(0),
+        }
+    }
+
+    fn message(err: Error) -> String {
+        match err {
+            Error::InvalidOrder { message } => message,
+            err => panic!("unexpected error: {err}"),
+        }
+    }
+
+    #[test]
+    fn test_limit_rounding() {
+        let bid = OrderBuilder::perp(&btc())
+            .buy(dec!(0.012349))
+            .limit_px(dec!(50000.7))
+            .build()
+            .unwrap();
+        assert_eq!(bid.asset, 0);
+        assert!(bid.is_buy);
+        assert_eq!(bid.limit_px, dec!(50000));
+        assert_eq!(bid.sz, dec!(0.01234));
+        assert!(matches!(
+            bid.order_type,
+            OrderTypePlacement::Limit {
+                tif: TimeInForce::Gtc
+            }
+        ));
+
+        let ask = OrderBuilder::perp(&btc())
+            .sell(dec!(0.01))
+            .limit_px(dec!(50000.2))
+            .tif(TimeInForce::Alo)
+            .build()
+            .unwrap();
+        assert!(!ask.is_buy);
+        assert_eq!(ask.limit_px, dec!(50001));
+        assert!(matches!(
+            ask.order_type,
+            OrderTypePlacement::Limit {
+                tif: TimeInForce::Alo
+            }
+        ));
+    }
+
+    #[test]
+    fn test_slippage() {
+        let bid = OrderBuilder::perp(&btc())
+            .buy(dec!(0.01))
+            .slippage(dec!(50000), dec!(0.01))
+            .build()
+            .unwrap();
+        assert_eq!(bid.limit_px, dec!(50500));
+        assert!(matches!(
+            bid.order_type,
+            OrderTypePlacement::Limit {
+                tif: TimeInForce::Ioc
+            }
+        ));
+
+        // 12.345 * 0.99 = 12.22155, rounded up towards the mid.
+        let ask = OrderBuilder::spot(&purr())
+            .sell(dec!(10))
+            .slippage(dec!(12.345), dec!(0.01))
+            .build()
+            .unwrap();
+        assert_eq!(ask.asset, 10_000);
+        assert_eq!(ask.limit_px, dec!(12.222));
+
+        let err = OrderBuilder::perp(&btc())
+            .buy(dec!(0.01))
+            .slippage(dec!(50000), dec!(1.5))
+            .build()
+            .unwrap_err();
+        assert!(message(err).contains("slippage"));
+    }
+
+    #[test]
+    fn test_validation() {
+        let err = OrderBuilder::perp(&btc())
+            .buy(dec!(0.000001))
+            .limit_px(dec!(50000))
+            .build()
+            .unwrap_err();
+        assert!(message(err).contains("size must be positive"));
+
+        let err = OrderBuilder::perp(&btc())
+            .buy(dec!(0.0001))
+            .limit_px(dec!(50000))
+            .build()
+            .unwrap_err();
+        assert!(message(err).contains("below the minimum"));
+
+        let close = OrderBuilder::perp(&btc())
+            .sell(dec!(0.0001))
+            .limit_px(dec!(50000))
+            .reduce_only(true)
+            .build()
+            .unwrap();
+        assert!(close.reduce_only);
+
+        let err = OrderBuilder::perp(&btc())
+            .buy(dec!(0.01))
+            .limit_px(dec!(50000))
+            .leverage(50)
+            .build()
+            .unwrap_err();
+        assert_eq!(message(err), "BTC: leverage 50x exceeds the maximum of 40x");
+
+        let err = OrderBuilder::spot(&purr())
+            .buy(dec!(10))
+            .limit_px(dec!(2))
+            .leverage(2)
+            .build()
+            .unwrap_err();
+        assert!(message(err).contains("leverage"));
+
+        let err = OrderBuilder::perp(&btc())
+            .buy(dec!(0.01))
+            .build()
+            .unwrap_err();
+        assert!(message(err).contains("price is not set"));
+    }
+
+    fn entry(is_buy: bool) -> OrderRequest {
+        let side = if is_buy { Side::Bid } else { Side::Ask };
+        OrderBuilder::perp(&btc())
+            .side(side)
+            .size(dec!(0.01))
+            .limit_px(dec!(50000))
+            .build()
+            .unwrap()
+    }
+
+    fn short_eth() -> PositionData {
+        serde_json::from_str(
+            r#"{
+                "coin":"ETH","szi":"-0.25","leverage":{"type":"cross","value":10},
+                "entryPx":"2000.0","positionValue":"550.0","unrealizedPnl":"-50.0",
+                "returnOnEquity":"0.0","liquidationPx":null,"marginUsed":"55.0","maxLeverage":25,
+                "cumFunding":{"allTime":"1.0","sinceOpen":"0.5","sinceChange":"0.1"}
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_bracket() {
+        let batch = Bracket::new(entry(true))
+            .with_take_profit(TpSlLeg::market(dec!(55000)))
+            .with_stop_loss(TpSlLeg::limit(dec!(48000), dec!(47500)))
+            .to_batch(&btc())
+            .unwrap();
+        assert!(matches!(batch.grouping, OrderGrouping::NormalTpsl));
+        assert_eq!(batch.orders.len(), 3);
+
+        let tp = &batch.orders[1];
+        assert!(!tp.is_buy && tp.reduce_only);
+        assert_eq!(tp.sz, dec!(0.01));
+        assert_eq!(tp.limit_px, dec!(55000));
+        assert!(matches!(
+            tp.order_type,
+            OrderTypePlacement::Trigger {
+                is_market: true,
+                tpsl: TpSl::Tp,
+                ..
+            }
+        ));
+
+        let sl = &batch.orders[2];
+        assert!(!sl.is_buy && sl.reduce_only);
+        assert_eq!(sl.limit_px, dec!(47500));
+        assert!(matches!(
+            sl.order_type,
+            OrderTypePlacement::Trigger {
+                is_market: false,
+                tpsl: TpSl::Sl,
+                ..
+            }
+        ));
+
+        // A short entry takes profit below the entry price.
+        let err = Bracket::new(entry(false))
+            .with_take_profit(TpSlLeg::market(dec!(55000)))
+            .to_batch(&btc())
+            .unwrap_err();
+        assert_eq!(
+            message(err),
+            "take-profit trigger 55000 must be below 50000"
+        );
+
+        let err = Bracket::new(entry(true)).to_batch(&btc()).unwrap_err();
+        assert!(message(err).contains("no take-profit or stop-loss"));
+
+        let mut eth = btc();
+        eth.name = "ETH".into();
+        eth.index = 1;
+        let err = Bracket::new(entry(true))
+            .with_take_profit(TpSlLeg::market(dec!(55000)))
+            .to_batch(&eth)
+            .unwrap_err();
+        assert_eq!(message(err), "bracket entry is not on ETH");
+    }
+
+    #[test]
+    fn test_bracket_rounding() {
+        // Legs of a long round away from the entry to 5 significant figures:
+        // the take-profit up, the stop-loss down.
+        let batch = Bracket::new(entry(true))
+            .with_take_profit(TpSlLeg::market(dec!(55000.7)))
+            .with_stop_loss(TpSlLeg::limit(dec!(48000.25), dec!(47500.3)))
+            .to_batch(&btc())
+            .unwrap();
+        assert_eq!(batch.orders[1].limit_px, dec!(55001));
+        assert!(matches!(
+            batch.orders[1].order_type,
+            OrderTypePlacement::Trigger { trigger_px, .. } if trigger_px == dec!(55001)
+        ));
+        assert_eq!(batch.orders[2].limit_px, dec!(47500));
+        assert!(matches!(
+            batch.orders[2].order_type,
+            OrderTypePlacement::Trigger { trigger_px, .. } if trigger_px == dec!(48000)
+        ));
+
+        // A stop-loss within a tick of the entry must not round onto it.
+        let batch = Bracket::new(entry(true))
+            .with_stop_loss(TpSlLeg::market(dec!(49999.5)))
+            .to_batch(&btc())
+            .unwrap();
+        assert!(matches!(
+            batch.orders[1].order_type,
+            OrderTypePlacement::Trigger { trigger_px, .. } if trigger_px == dec!(49999)
+        ));
+        let batch = Bracket::new(entry(false))
+            .with_stop_loss(TpSlLeg::market(dec!(50000.5)))
+            .to_batch(&btc())
+            .unwrap();
+        assert!(matches!(
+            batch.orders[1].order_type,
+            OrderTypePlacement::Trigger { trigger_px, .. } if trigger_px == dec!(50001)
+        ));
+
+        // The checks run on the rounded prices.
+        let err = Bracket::new(entry(true))
+            .with_take_profit(TpSlLeg::market(dec!(49999.5)))
+            .to_batch(&btc())
+            .unwrap_err();
+        assert_eq!(
+            message(err),
+            "take-profit trigger 50000 must be above 50000"
+        );
+    }
+
+    #[test]
+    fn test_position_tpsl() {
+        let position = short_eth();
+        let mut eth = btc();
+        eth.name = "ETH".into();
+        eth.index = 1;
+        eth.sz_decimals = 4;
+        eth.table = PriceTick::for_perp(4);
+
+        let batch = PositionTpsl::default()
+            .with_take_profit(TpSlLeg::market(dec!(1800.123)))
+            .with_stop_loss(TpSlLeg::market(dec!(2400)))
+            .to_batch(&eth, &position)
+            .unwrap();
+        assert!(matches!(batch.grouping, OrderGrouping::PositionTpsl));
+        assert!(batch.orders.iter().all(|order| order.asset == 1
+            && order.is_buy
+            && order.reduce_only
+            && order.sz == dec!(0.25)));
+        // Legs of a short close with buys, rounded down.
+        assert_eq!(batch.orders[0].limit_px, dec!(1800.1));
+
+        // The mark price is 550 / 0.25 = 2200: a stop below it would trigger immediately.
+        let err = PositionTpsl::default()
+            .with_stop_loss(TpSlLeg::market(dec!(2100)))
+            .to_batch(&eth, &position)
+            .unwrap_err();
+        assert!(message(err).starts_with("ETH: stop-loss trigger 2100 must be above"));
+    }
+
+    #[test]
+    fn test_bracket_status() {
+        let batch = Bracket::new(entry(true))
+            .with_stop_loss(TpSlLeg::market(dec!(48000)))
+            .to_batch(&btc())
+            .unwrap();
+        let status = BracketStatus::new(
+            &batch,
+            vec![
+                OrderResponseStatus::Resting {
+                    oid: 1,
+                    cloid: None,
+                },
+                OrderResponseStatus::WaitingForFill,
+            ],
+        )
+        .unwrap();
+        assert_eq!(status.entry.and_then(|status| status.oid()), Some(1));
+        assert!(status.take_profit.is_none());
+        assert!(matches!(
+            status.stop_loss,
+            Some(OrderResponseStatus::WaitingForFill)
+        ));
+
+        assert!(BracketStatus::new(&batch, vec![OrderResponseStatus::Success]).is_err());
+    }
+}
@@ -0,0 +1,981 @@
+//! Order lifecycle tracking.
+//!
+//! [`OrderManager`] records the orders placed through it and keeps their state
+//! up to date from three sources:
+//!
+//! - the exchange response to [`OrderManager::place`],
+//! - the [`Incoming::OrderUpdates`] and [`Incoming::UserFills`] websocket channels,
+//! - REST queries made by [`OrderManager::reconcile`] after a reconnection.
+//!
+//! Orders are identified by their client order ID ([`Cloid`]), so every order placed
+//! through the manager must carry a non-zero cloid.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//!
+//! use futures::StreamExt;
+//! use hypersdk::hypercore::{self, Cloid, OrderManager, PrivateKeySigner, types::*, ws::Event};
+//! use rust_decimal::dec;
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let signer: PrivateKeySigner = "your_key".parse()?;
+//! let manager = Arc::new(OrderManager::new(hypercore::mainnet(), signer.address()));
+//!
+//! let mut ws = hypercore::mainnet_ws();
+//! for subscription in manager.subscriptions() {
+//!     ws.subscribe(subscription);
+//! }
+//!
+//! let feed = manager.clone();
+//! tokio::spawn(async move {
+//!     while let Some(event) = ws.next().await {
+//!         if matches!(event, Event::Connected) {
+//!             let _ = feed.reconcile().await;
+//!         }
+//!         feed.handle(&event);
+//!     }
+//! });
+//!
+//! let cloid = Cloid::random();
+//! let order = BatchOrder {
+//!     orders: vec![OrderRequest {
+//!         asset: 0,
+//!         is_buy: true,
+//!         limit_px: dec!(50000),
+//!         sz: dec!(0.01),
+//!         reduce_only: false,
+//!         order_type: OrderTypePlacement::Limit { tif: TimeInForce::Gtc },
+//!         cloid,
+//!     }],
+//!     grouping: OrderGrouping::Na,
+//!     builder: None,
+//! };
+//!
+//! let nonce = chrono::Utc::now().timestamp_millis() as u64;
+//! manager.place(&signer, order, nonce, None, None).await?;
+//!
+//! let filled = manager.wait_filled(cloid).await?;
+//! println!("filled {} @ {:?}", filled.filled_sz, filled.avg_px());
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use alloy::signers::SignerSync;
+use chrono::{DateTime, Utc};
+use either::Either;
+use futures::TryStreamExt;
+use rust_decimal::Decimal;
+use tokio::sync::watch;
+
+use crate::{
+    Address,
+    hypercore::{
+        ActionError, Cloid, Error,
+        error::Result,
+        http::Client,
+        types::{
+            BatchOrder, Fill, Incoming, OrderRequest, OrderResponseStatus, OrderStatus,
+            Subscription,
+        },
+        ws::Event,
+    },
+};
+
+/// Lifecycle state of a tracked order.
+#[derive(Debug, Clone, derive_more::IsVariant)]
+pub enum OrderState {
+    /// Sent to the exchange, no confirmation received yet.
+    Pending,
+    /// Resting on the book without fills.
+    Resting,
+    /// Resting on the book with some size filled.
+    PartiallyFilled,
+    /// Completely filled.
+    Filled,
+    /// Removed from the book before being completely filled.
+    ///
+    /// The status carries the reason reported by the exchange
+    /// (e.g. [`OrderStatus::Canceled`], [`OrderStatus::MarginCanceled`]).
+    Cancelled(OrderStatus),
+    /// Rejected when placed, with the error returned by the exchange.
+    Rejected(String),
+}
+
+impl OrderState {
+    /// Returns true if the order can no longer change state.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            OrderState::Filled | OrderState::Cancelled(_) | OrderState::Rejected(_)
+        )
+    }
+}
+
+/// Snapshot of an order tracked by [`OrderManager`].
+#[derive(Debug, Clone)]
+pub struct TrackedOrder {
+    /// Client order ID
+    pub cloid: Cloid,
+    /// Exchange order ID, once known
+    pub oid: Option<u64>,
+    /// Asset index
+    pub asset: usize,
+    /// True for a buy order
+    pub is_buy: bool,
+    /// Limit price
+    pub limit_px: Decimal,
+    /// Original order size
+    pub sz: Decimal,
+    /// Filled size
+    pub filled_sz: Decimal,
+    /// Current lifecycle state
+    pub state: OrderState,
+    /// Time the order started being tracked
+    pub created_at: DateTime<Utc>,
+    /// Size and notional of the fills received, deduplicated by trade ID.
+    fills_sz: Decimal,
+    fills_notional: Decimal,
+    /// Filled size reported by the REST API, with its notional when the average price is known.
+    ///
+    /// Kept apart from the fills because the same fills are reported by both sources.
+    reported_sz: Decimal,
+    reported_notional: Option<Decimal>,
+}
+
+impl TrackedOrder {
+    fn new(order: &OrderRequest) -> Self {
+        Self {
+            cloid: order.cloid,
+            oid: None,
+            asset: order.asset,
+            is_buy: order.is_buy,
+            limit_px: order.limit_px,
+            sz: order.sz,
+            filled_sz: Decimal::ZERO,
+            state: OrderState::Pending,
+            created_at: Utc::now(),
+            fills_sz: Decimal::ZERO,
+            fills_notional: Decimal::ZERO,
+            reported_sz: Decimal::ZERO,
+            reported_notional: None,
+        }
+    }
+
+    /// Returns the size left to fill.
+    #[must_use]
+    pub fn remaining_sz(&self) -> Decimal {
+        (self.sz - self.filled_sz).max(Decimal::ZERO)
+    }
+
+    /// Returns the average fill price, if any size was filled.
+    #[must_use]
+    pub fn avg_px(&self) -> Option<Decimal> {
+        let (sz, notional) = match self.reported_notional {
+            Some(notional) if self.reported_sz > self.fills_sz => (self.reported_sz, notional),
+            _ => (self.fills_sz, self.fills_notional),
+        };
+        if sz.is_zero() || notional.is_zero() {
+            return None;
+        }
+        Some(notional / sz)
+    }
+
+    /// Moves the order to `state`, never going back from a later state.
+    fn transition(&mut self, state: OrderState) -> bool {
+        if self.state.is_finished() {
+            return false;
+        }
+
+        let state = match state {
+            OrderState::Resting if !self.filled_sz.is_zero() => OrderState::PartiallyFilled,
+            OrderState::Resting | OrderState::Pending if self.state.is_partially_filled() => {
+                return false;
+            }
+            OrderState::Pending if self.state.is_resting() => return false,
+            state => state,
+        };
+
+        let changed = std::mem::discriminant(&self.state) != std::mem::discriminant(&state);
+        self.state = state;
+        changed
+    }
+
+    /// Records the filled size reported by the REST API, where only the total is known.
+    fn report_filled(&mut self, filled_sz: Decimal, avg_px: Option<Decimal>) -> bool {
+        if filled_sz <= self.reported_sz {
+            return false;
+        }
+
+        self.reported_sz = filled_sz;
+        self.reported_notional = avg_px.map(|avg_px| filled_sz * avg_px);
+        self.update_filled()
+    }
+
+    /// Records a fill not seen before.
+    fn add_fill(&mut self, fill: &Fill) -> bool {
+        self.fills_sz += fill.sz;
+        self.fills_notional += fill.notional();
+        self.update_filled()
+    }
+
+    /// Sets the filled size to the larger of the fills and the reported total.
+    fn update_filled(&mut self) -> bool {
+        let filled_sz = self.fills_sz.max(self.reported_sz);
+        let changed = filled_sz != self.filled_sz;
+        self.filled_sz = filled_sz;
+        changed
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    orders: HashMap<Cloid, Entry>,
+    oids: HashMap<u64, Cloid>,
+    /// Orders whose placement request has not resolved yet.
+    in_flight: HashSet<Cloid>,
+}
+
+/// Marks orders as in flight until dropped, when their placement resolves or is abandoned.
+struct InFlight {
+    inner: Arc<Mutex<Inner>>,
+    cloids: Vec<Cloid>,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        for cloid in &self.cloids {
+            inner.in_flight.remove(cloid);
+        }
+    }
+}
+
+struct Entry {
+    tx: watch::Sender<TrackedOrder>,
+    tids: HashSet<u64>,
+}
+
+impl Inner {
+    fn track(&mut self, order: &OrderRequest) {
+        if order.cloid.is_zero() {
+            return;
+        }
+
+        let (tx, _) = watch::channel(TrackedOrder::new(order));
+        self.orders.insert(
+            order.cloid,
+            Entry {
+                tx,
+                tids: HashSet::new(),
+            },
+        );
+    }
+
+    fn resolve(&self, cloid: Option<Cloid>, oid: u64) -> Option<Cloid> {
+        cloid
+            .filter(|cloid| self.orders.contains_key(cloid))
+            .or_else(|| self.oids.get(&oid).copied())
+    }
+
+    fn set_oid(&mut self, cloid: Cloid, oid: u64) {
+        let Some(entry) = self.orders.get(&cloid) else {
+            return;
+        };
+
+        self.oids.insert(oid, cloid);
+        entry.tx.send_if_modified(|order| {
+            let changed = order.oid != Some(oid);
+            order.oid = Some(oid);
+            changed
+        });
+    }
+
+    fn apply_response(&mut self, cloid: Cloid, status: &OrderResponseStatus) -> bool {
+        if let Some(oid) = status.oid() {
+            self.set_oid(cloid, oid);
+        }
+
+        let Some(entry) = self.orders.get(&cloid) else {
+            return false;
+        };
+
+        entry.tx.send_if_modified(|order| match status {
+            OrderResponseStatus::Success
+            | OrderResponseStatus::WaitingForFill
+            | OrderResponseStatus::WaitingForTrigger => false,
+            OrderResponseStatus::Resting { .. } => order.transition(OrderState::Resting),
+            OrderResponseStatus::Filled {
+                total_sz, avg_px, ..
+            } => {
+                let filled = order.report_filled(*total_sz, Some(*avg_px));
+                order.transition(OrderState::Filled) || filled
+            }
+            OrderResponseStatus::Error(err) => order.transition(OrderState::Rejected(err.clone())),
+        })
+    }
+
+    fn apply_status(
+        &mut self,
+        cloid: Option<Cloid>,
+        oid: u64,
+        status: OrderStatus,
+        orig_sz: Decimal,
+        sz: Decimal,
+    ) -> bool {
+        let Some(cloid) = self.resolve(cloid, oid) else {
+            return false;
+        };
+        self.set_oid(cloid, oid);
+
+        let entry = &self.orders[&cloid];
+        entry.tx.send_if_modified(|order| {
+            let filled = order.report_filled(orig_sz - sz, None);
+            let state = match status {
+                OrderStatus::Open | OrderStatus::Triggered => OrderState::Resting,
+                OrderStatus::Filled => OrderState::Filled,
+                reason => OrderState::Cancelled(reason),
+            };
+            order.transition(state) || filled
+        })
+    }
+
+    fn apply_fill(&mut self, fill: &Fill) -> bool {
+        let Some(cloid) = self.resolve(fill.cloid, fill.oid) else {
+            return false;
+        };
+        self.set_oid(cloid, fill.oid);
+
+        let entry = self.orders.get_mut(&cloid).expect("resolved cloid");
+        if !entry.tids.insert(fill.tid) {
+            return false;
+        }
+
+        entry.tx.send_if_modified(|order| {
+            let filled = order.add_fill(fill);
+            let state = if order.filled_sz >= order.sz {
+                OrderState::Filled
+            } else {
+                OrderState::PartiallyFilled
+            };
+            order.transition(state) || filled
+        })
+    }
+}
+
+/// Tracks the lifecycle of orders across REST responses and websocket updates.
+///
+/// The manager does not own a websocket connection: feed it the events of a
+/// connection subscribed to [`subscriptions`](Self::subscriptions) through
+/// [`handle`](Self::handle), and call [`reconcile`](Self::reconcile) on every
+/// [`Event::Connected`] to recover updates missed while disconnected.
+///
+/// Only orders with a non-zero [`Cloid`] are tracked.
+pub struct OrderManager {
+    client: Client,
+    user: Address,
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl OrderManager {
+    /// Creates a manager for the orders of `user`.
+    ///
+    /// When trading on behalf of a vault or subaccount, `user` is the vault or subaccount address.
+    pub fn new(client: Client, user: Address) -> Self {
+        Self {
+            client,
+            user,
+            inner: Arc::default(),
+        }
+    }
+
+    /// Returns the underlying HTTP client.
+    #[must_use]
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// Returns the user whose orders are tracked.
+    #[must_use]
+    pub fn user(&self) -> Address {
+        self.user
+    }
+
+    /// Returns the websocket subscriptions required to track orders.
+    #[must_use]
+    pub fn subscriptions(&self) -> [Subscription; 2] {
+        [
+            Subscription::OrderUpdates { user: self.user },
+            Subscription::UserFills { user: self.user },
+        ]
+    }
+
+    /// Places a batch of orders and tracks them.
+    ///
+    /// Orders are recorded as [`OrderState::Pending`] before being sent and updated
+    /// with the exchange response. If the request fails, they stay pending until
+    /// the next [`reconcile`](Self::reconcile); while it is in flight,
+    /// [`reconcile`](Self::reconcile) leaves them alone.
+    ///
+    /// See [`Client::place`] for the parameters.
+    pub fn place<S: SignerSync>(
+        &self,
+        signer: &S,
+        batch: BatchOrder,
+        nonce: u64,
+        vault_address: Option<Address>,
+        expires_after: Option<DateTime<Utc>>,
+    ) -> impl Future<Output = Result<Vec<OrderResponseStatus>, ActionError<Cloid>>> + Send + 'static
+    {
+        let cloids: Vec<_> = batch.orders.iter().map(|order| order.cloid).collect();
+        {
+            let mut inner = self.lock();
+            for order in &batch.orders {
+                inner.track(order);
+            }
+            inner.in_flight.extend(cloids.iter().copied());
+        }
+        let in_flight = InFlight {
+            inner: self.inner.clone(),
+            cloids,
+        };
+
+        let future = self
+            .client
+            .place(signer, batch, nonce, vault_address, expires_after);
+        async move {
+            let statuses = future.await?;
+
+            let mut inner = in_flight
+                .inner
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            for (cloid, status) in in_flight.cloids.iter().zip(&statuses) {
+                inner.apply_response(*cloid, status);
+            }
+            drop(inner);
+
+            Ok(statuses)
+        }
+    }
+
+    /// Starts tracking an order placed through another path (e.g. a multisig).
+    ///
+    /// Orders with a zero cloid are ignored.
+    pub fn track(&self, order: &OrderRequest) {
+        self.lock().track(order);
+    }
+
+    /// Updates a tracked order with the exchange response to its placement.
+    ///
+    /// Returns true if the order changed.
+    pub fn apply_response(&self, cloid: Cloid, status: &OrderResponseStatus) -> bool {
+        self.lock().apply_response(cloid, status)
+    }
+
+    /// Processes a websocket event.
+    ///
+    /// Applies [`Incoming::OrderUpdates`] and [`Incoming::UserFills`] to the tracked
+    /// orders. Fills are deduplicated by trade ID, so snapshots can be replayed safely.
+    /// Returns true if any tracked order changed.
+    pub fn handle(&self, event: &Event) -> bool {
+        let Event::Message(msg) = event else {
+            return false;
+        };
+
+        let mut inner = self.lock();
+        let mut changed = false;
+        match msg {
+            Incoming::OrderUpdates(updates) => {
+                for update in updates {
+                    let order = &update.order;
+                    changed |= inner.apply_status(
+                        order.cloid,
+                        order.oid,
+                        update.status,
+                        order.orig_sz,
+                        order.sz,
+                    );
+                }
+            }
+            Incoming::UserFills { user, fills, .. } if *user == self.user => {
+                for fill in fills {
+                    changed |= inner.apply_fill(fill);
+                }
+            }
+            _ => {}
+        }
+        changed
+    }
+
+    /// Brings unfinished orders up to date using the REST API.
+    ///
+    /// Queries open orders, fills since the oldest unfinished order, and the status
+    /// of every unfinished order that is no longer open. Orders unknown to the exchange
+    /// are marked as [`OrderState::Rejected`], unless their [`place`](Self::place)
+    /// request is still in flight.
+    pub async fn reconcile(&self) -> Result<()> {
+        let Some(since) = self
+            .orders()
+            .into_iter()
+            .filter(|order| !order.state.is_finished())
+            .map(|order| order.created_at)
+            .min()
+        else {
+            return Ok(());
+        };
+
+        let open_orders = self.client.open_orders(self.user, None).await?;
+        let mut open = HashSet::new();
+        {
+            let mut inner = self.lock();
+            for order in &open_orders {
+                if let Some(cloid) = inner.resolve(order.cloid, order.oid) {
+                    open.insert(cloid);
+                }
+                inner.apply_status(
+                    order.cloid,
+                    order.oid,
+                    OrderStatus::Open,
+                    order.orig_sz,
+                    order.sz,
+                );
+            }
+        }
+
+        // a single request is capped at 2000 fills
+        let fills: Vec<Fill> = self
+            .client
+            .user_fills_by_time_stream(self.user, since.timestamp_millis() as u64, None)
+            .try_collect()
+            .await?;
+        {
+            let mut inner = self.lock();
+            for fill in &fills {
+                inner.apply_fill(fill);
+            }
+        }
+
+        let missing: Vec<_> = {
+            let inner = self.lock();
+            inner
+                .orders
+                .iter()
+                .filter(|(cloid, entry)| {
+                    !entry.tx.borrow().state.is_finished()
+                        && !open.contains(*cloid)
+                        && !inner.in_flight.contains(*cloid)
+                })
+                .map(|(cloid, _)| *cloid)
+                .collect()
+        };
+
+        for cloid in missing {
+            let status = self
+                .client
+                .order_status(self.user, Either::Right(cloid))
+                .await?;
+
+            let mut inner = self.lock();
+            match status {
+                Some(update) => {
+                    let order = &update.order;
+                    inner.apply_status(
+                        Some(cloid),
+                        order.oid,
+                        update.status,
+                        order.orig_sz,
+                        order.sz,
+                    );
+                }
+                None => {
+                    inner.apply_response(
+                        cloid,
+                        &OrderResponseStatus::Error("order not found".to_owned()),
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the current state of an order.
+    #[must_use]
+    pub fn get(&self, cloid: Cloid) -> Option<TrackedOrder> {
+        self.lock()
+            .orders
+            .get(&cloid)
+            .map(|entry| entry.tx.borrow().clone())
+    }
+
+    /// Returns the current state of every tracked order.
+    #[must_use]
+    pub fn orders(&self) -> Vec<TrackedOrder> {
+        self.lock()
+            .orders
+            .values()
+            .map(|entry| entry.tx.borrow().clone())
+            .collect()
+    }
+
+    /// Returns a receiver notified on every change of an order.
+    #[must_use]
+    pub fn watch(&self, cloid: Cloid) -> Option<watch::Receiver<TrackedOrder>> {
+        self.lock()
+            .orders
+            .get(&cloid)
+            .map(|entry| entry.tx.subscribe())
+    }
+
+    /// Stops tracking an order.
+    pub fn forget(&self, cloid: Cloid) -> Option<TrackedOrder> {
+        let mut inner = self.lock();
+        let entry = inner.orders.remove(&cloid)?;
+        inner.oids.retain(|_, value| *value != cloid);
+        Some(entry.tx.borrow().clone())
+    }
+
+    /// Removes every finished order and returns them.
+    pub fn prune(&self) -> Vec<TrackedOrder> {
+        let finished: Vec<_> = self
+            .orders()
+            .into_iter()
+            .filter(|order| order.state.is_finished())
+            .collect();
+        for order in &finished {
+            self.forget(order.cloid);
+        }
+        finished
+    }
+
+    /// Waits until an order reaches a final state and returns it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Other`] if the order is not tracked or is forgotten meanwhile.
+    pub async fn wait_finished(&self, cloid: Cloid) -> Result<TrackedOrder> {
+        let mut rx = self
+            .watch(cloid)
+            .ok_or_else(|| Error::Other(format!("order {cloid} is not tracked")))?;
+
+        let order = rx
+            .wait_for(|order| order.state.is_finished())
+            .await
+            .map_err(|_| Error::Other(format!("order {cloid} is no longer tracked")))?;
+        Ok(order.clone())
+    }
+
+    /// Waits until an order is completely filled.
+    ///
+    /// # Errors
+    ///
+    /// Fails like [`wait_finished`](Self::wait_finished), and if the order is
+    /// cancelled or rejected instead. Rejections are classified with
+    /// [`Error::from_api`].
+    pub async fn wait_filled(&self, cloid: Cloid) -> Result<TrackedOrder> {
+        let order = self.wait_finished(cloid).await?;
+        match &order.state {
+            OrderState::Filled => Ok(order),
+            OrderState::Cancelled(reason) => {
+                Err(Error::Other(format!("order {cloid} cancelled: {reason}")))
+            }
+            OrderState::Rejected(err) => {
+                Err(Error::from_api(format!("order {cloid} rejected: {err}")))
+            }
+            state => Err(Error::Other(format!(
+                "order {cloid} in unexpected state: {state:?}"
+            ))),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::dec;
+
+    use super::*;
+    use crate::hypercore::{
+        PrivateKeySigner,
+        testing::MockServer,
+        types::{OrderGrouping, OrderTypePlacement, OrderUpdate, Side, TimeInForce, WsBasicOrder},
+    };
+
+    fn request(cloid: Cloid, sz: Decimal) -> OrderRequest {
+        OrderRequest {
+            asset: 0,
+            is_buy: true,
+            limit_px: dec!(100),
+            sz,
+            reduce_only: false,
+            order_type: OrderTypePlacement::Limit {
+                tif: TimeInForce::Gtc,
+            },
+            cloid,
+        }
+    }
+
+    fn update(cloid: Cloid, oid: u64, status: OrderStatus, sz: Decimal) -> Event {
+        Event::Message(Incoming::OrderUpdates(vec![OrderUpdate {
+            status,
+            status_timestamp: 0,
+            order: WsBasicOrder {
+                timestamp: 0,
+                coin: "BTC".into(),
+                side: Side::Bid,
+                limit_px: dec!(100),
+                sz,
+                oid,
+                orig_sz: dec!(2),
+                cloid: Some(cloid),
+            },
+        }]))
+    }
+
+    fn fills(user: Address, oid: u64, tid: u64, px: Decimal, sz: Decimal) -> Event {
+        let fill = serde_json::from_value(serde_json::json!({
+            "coin": "BTC",
+            "px": px.to_string(),
+            "sz": sz.to_string(),
+            "side": "B",
+            "time": 0,
+            "startPosition": "0",
+            "dir": "Open Long",
+            "closedPnl": "0",
+            "hash": "0x0",
+            "oid": oid,
+            "crossed": true,
+            "fee": "0",
+            "tid": tid,
+            "cloid": null,
+            "feeToken": "USDC",
+        }))
+        .unwrap();
+        Event::Message(Incoming::UserFills {
+            is_snapshot: false,
+            user,
+            fills: vec![fill],
+        })
+    }
+
+    fn manager() -> OrderManager {
+        OrderManager::new(Client::new(crate::hypercore::Chain::Testnet), Address::ZERO)
+    }
+
+    #[test]
+    fn test_lifecycle() {
+        let manager = manager();
+        let cloid = Cloid::with_last_byte(1);
+        manager.track(&request(cloid, dec!(2)));
+        assert!(manager.get(cloid).unwrap().state.is_pending());
+
+        // zero cloids are not tracked
+        manager.track(&request(Cloid::ZERO, dec!(2)));
+        assert_eq!(manager.orders().len(), 1);
+
+        assert!(manager.apply_response(
+            cloid,
+            &OrderResponseStatus::Resting {
+                oid: 7,
+                cloid: Some(cloid)
+            }
+        ));
+        let order = manager.get(cloid).unwrap();
+        assert!(order.state.is_resting());
+        assert_eq!(order.oid, Some(7));
+
+        // fills are matched by oid and deduplicated by tid
+        assert!(manager.handle(&fills(Address::ZERO, 7, 1, dec!(100), dec!(1))));
+        assert!(!manager.handle(&fills(Address::ZERO, 7, 1, dec!(100), dec!(1))));
+        let order = manager.get(cloid).unwrap();
+        assert!(order.state.is_partially_filled());
+        assert_eq!(order.remaining_sz(), dec!(1));
+
+        // an open update does not move the order back to resting
+        manager.handle(&update(cloid, 7, OrderStatus::Open, dec!(1)));
+        assert!(manager.get(cloid).unwrap().state.is_partially_filled());
+
+        assert!(manager.handle(&fills(Address::ZERO, 7, 2, dec!(102), dec!(1))));
+        let order = manager.get(cloid).unwrap();
+        assert!(order.state.is_filled());
+        assert_eq!(order.avg_px(), Some(dec!(101)));
+
+        // finished orders do not change anymore
+        assert!(!manager.handle(&update(cloid, 7, OrderStatus::Canceled, dec!(0))));
+        assert_eq!(manager.prune().len(), 1);
+        assert!(manager.get(cloid).is_none());
+    }
+
+    #[test]
+    fn test_cancel_and_reject() {
+        let manager = manager();
+        let cancelled = Cloid::with_last_byte(1);
+        let rejected = Cloid::with_last_byte(2);
+        manager.track(&request(cancelled, dec!(2)));
+        manager.track(&request(rejected, dec!(2)));
+
+        // websocket update arriving before the REST response
+        assert!(manager.handle(&update(cancelled, 9, OrderStatus::MarginCanceled, dec!(2))));
+        assert!(!manager.apply_response(
+            cancelled,
+            &OrderResponseStatus::Resting {
+                oid: 9,
+                cloid: Some(cancelled)
+            }
+        ));
+        assert!(matches!(
+            manager.get(cancelled).unwrap().state,
+            OrderState::Cancelled(OrderStatus::MarginCanceled)
+        ));
+
+        manager.apply_response(rejected, &OrderResponseStatus::Error("tick".into()));
+        assert!(manager.get(rejected).unwrap().state.is_rejected());
+
+        // fills of other users are ignored
+        assert!(!manager.handle(&fills(Address::repeat_byte(1), 9, 1, dec!(1), dec!(1))));
+    }
+
+    #[test]
+    fn test_fills_reported_twice() {
+        let manager = manager();
+        let cloid = Cloid::with_last_byte(1);
+        manager.track(&request(cloid, dec!(2)));
+
+        // IOC filled in the REST response, then the same fill on the websocket
+        manager.apply_response(
+            cloid,
+            &OrderResponseStatus::Filled {
+                total_sz: dec!(2),
+                avg_px: dec!(100),
+                oid: 7,
+            },
+        );
+        assert!(!manager.handle(&fills(Address::ZERO, 7, 1, dec!(100), dec!(2))));
+        let order = manager.get(cloid).unwrap();
+        assert!(order.state.is_filled());
+        assert_eq!(order.filled_sz, dec!(2));
+        assert_eq!(order.avg_px(), Some(dec!(100)));
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_partial_fill() {
+        let server = MockServer::start().await.unwrap();
+        let signer = PrivateKeySigner::random();
+        server.set_balance(signer.address(), dec!(1000));
+        let manager = OrderManager::new(server.client(), signer.address());
+
+        let cloid = Cloid::with_last_byte(1);
+        let mut order = request(cloid, dec!(0.01));
+        order.limit_px = dec!(50000);
+        let batch = BatchOrder {
+            orders: vec![order],
+            grouping: OrderGrouping::Na,
+            builder: None,
+        };
+        let statuses = manager.place(&signer, batch, 1, None, None).await.unwrap();
+        server
+            .fill(statuses[0].oid().unwrap(), dec!(0.004))
+            .unwrap();
+
+        // open orders report 0.004 filled, and the fills report the same 0.004
+        manager.reconcile().await.unwrap();
+        let order = manager.get(cloid).unwrap();
+        assert!(order.state.is_partially_filled());
+        assert_eq!(order.filled_sz, dec!(0.004));
+        assert_eq!(order.avg_px(), Some(dec!(50000)));
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_in_flight() {
+        let server = MockServer::start().await.unwrap();
+        let signer = PrivateKeySigner::random();
+        server.set_balance(signer.address(), dec!(1000));
+        let manager = OrderManager::new(server.client(), signer.address());
+
+        let batch = |cloid| {
+            let mut order = request(cloid, dec!(0.01));
+            order.limit_px = dec!(50000);
+            BatchOrder {
+                orders: vec![order],
+                grouping: OrderGrouping::Na,
+                builder: None,
+            }
+        };
+
+        // the exchange doesn't know the order before the place request resolves
+        let cloid = Cloid::with_last_byte(1);
+        let place = manager.place(&signer, batch(cloid), 1, None, None);
+        manager.reconcile().await.unwrap();
+        assert!(manager.get(cloid).unwrap().state.is_pending());
+
+        place.await.unwrap();
+        assert!(manager.get(cloid).unwrap().state.is_resting());
+
+        let cloid = Cloid::with_last_byte(2);
+        let (statuses, reconciled) = tokio::join!(
+            manager.place(&signer, batch(cloid), 2, None, None),
+            manager.reconcile(),
+        );
+        statuses.unwrap();
+        reconciled.unwrap();
+        assert!(manager.get(cloid).unwrap().state.is_resting());
+
+        // an abandoned request is no longer in flight
+        let cloid = Cloid::with_last_byte(3);
+        drop(manager.place(&signer, batch(cloid), 3, None, None));
+        manager.reconcile().await.unwrap();
+        assert!(manager.get(cloid).unwrap().state.is_rejected());
+    }
+
+    #[tokio::test]
+    async fn test_wait_filled() {
+        let manager = Arc::new(manager());
+        let cloid = Cloid::with_last_byte(1);
+        let batch = BatchOrder {
+            orders: vec![request(cloid, dec!(1))],
+            grouping: OrderGrouping::Na,
+            builder: None,
+        };
+        manager.track(&batch.orders[0]);
+
+        let waiter = tokio::spawn({
+            let manager = manager.clone();
+            async move { manager.wait_filled(cloid).await }
+        });
+
+        manager.apply_response(
+            cloid,
+            &OrderResponseStatus::Filled {
+                total_sz: dec!(1),
+                avg_px: dec!(99),
+                oid: 3,
+            },
+        );
+
+        let order = waiter.await.unwrap().unwrap();
+        assert_eq!(order.avg_px(), Some(dec!(99)));
+        assert!(manager.wait_filled(Cloid::with_last_byte(9)).await.is_err());
+
+        let rejected = Cloid::with_last_byte(2);
+        manager.track(&request(rejected, dec!(1)));
+        manager.apply_response(
+            rejected,
+            &OrderResponseStatus::Error("Insufficient margin to place order.".into()),
+        );
+        assert!(matches!(
+            manager.wait_filled(rejected).await,
+            Err(Error::InsufficientMargin(_))
+        ));
+    }
+}
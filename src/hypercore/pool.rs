@@ -0,0 +1,362 @@
+//! Websocket connection pool.
+//!
+//! Hyperliquid limits the number of subscriptions a single websocket connection
+//! can carry. [`ConnectionPool`] opens several [`Connection`]s to the same URL and
+//! shards subscriptions across them, always picking the connected shard with the
+//! fewest subscriptions.
+//!
+//! When a connection drops, its subscriptions are moved to the other connected
+//! shards so the feeds keep flowing while it reconnects. Once it is back,
+//! subscriptions are moved to it from the most loaded shards until the load is even.
+//!
+//! Each connection carries at most [`MAX_SUBSCRIPTIONS_PER_IP`] divided by the
+//! number of connections by default, see [`ConnectionPool::with_max_subscriptions`].
+//!
+//! The pool implements `futures::Stream`, merging the events of every connection
+//! into [`PoolEvent`]s tagged with the index of the originating connection.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use futures::StreamExt;
+//! use hypersdk::hypercore::{self, ConnectionPool, types::*, ws::Event};
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let mut pool = ConnectionPool::new(hypercore::mainnet_websocket_url(), 4)
+//!     .with_max_subscriptions(500);
+//!
+//! for market in hypercore::mainnet().perps().await? {
+//!     pool.subscribe(Subscription::l2_book(&market.name))?;
+//!     pool.subscribe(Subscription::Trades { coin: market.name })?;
+//! }
+//!
+//! while let Some(event) = pool.next().await {
+//!     if let Event::Message(Incoming::Trades(trades)) = event.event {
+//!         println!("{} trades from connection {}", trades.len(), event.connection);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::{HashMap, HashSet},
+    pin::Pin,
+    sync::{Mutex, MutexGuard, PoisonError},
+    task::{Context, Poll, ready},
+};
+
+use futures::{
+    StreamExt,
+    stream::{BoxStream, SelectAll},
+};
+use url::Url;
+
+use crate::hypercore::{
+    Error, error,
+    types::Subscription,
+    ws::{
+        Connection, ConnectionConfig, ConnectionHandle, ConnectionStats, Event, SubscriptionHandle,
+        SubscriptionStatus,
+    },
+};
+
+/// Maximum number of websocket subscriptions Hyperliquid accepts from one IP.
+pub const MAX_SUBSCRIPTIONS_PER_IP: usize = 1000;
+
+/// Event emitted by a [`ConnectionPool`].
+#[derive(Debug, Clone)]
+pub struct PoolEvent {
+    /// Index of the connection the event comes from
+    pub connection: usize,
+    /// The event
+    pub event: Event,
+}
+
+#[derive(Debug, Default)]
+struct Shard {
+    connected: bool,
+    /// Whether the connection was established at least once.
+    was_connected: bool,
+    subscriptions: HashSet<Subscription>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    shards: Vec<Shard>,
+    assignments: HashMap<Subscription, usize>,
+}
+
+impl State {
+    /// Returns the shard that should take a new subscription.
+    ///
+    /// Connected shards are preferred; `exclude` is never picked.
+    fn pick(&self, max: usize, exclude: Option<usize>) -> Option<usize> {
+        let candidates =
+            self.shards.iter().enumerate().filter(|(index, shard)| {
+                Some(*index) != exclude && shard.subscriptions.len() < max
+            });
+
+        candidates
+            .clone()
+            .filter(|(_, shard)| shard.connected)
+            .min_by_key(|(_, shard)| shard.subscriptions.len())
+            .or_else(|| candidates.min_by_key(|(_, shard)| shard.subscriptions.len()))
+            .map(|(index, _)| index)
+    }
+}
+
+/// Pool of websocket connections sharing the load of many subscriptions.
+///
+/// Subscriptions persist across reconnections like on a single [`Connection`].
+/// While a disconnected connection is being drained, its subscriptions may be
+/// delivered twice for a short time.
+pub struct ConnectionPool {
+    handles: Vec<ConnectionHandle>,
+    streams: SelectAll<BoxStream<'static, PoolEvent>>,
+    state: Mutex<State>,
+    max_subscriptions: usize,
+}
+
+impl ConnectionPool {
+    /// Opens `size` connections to `url`.
+    ///
+    /// At least one connection is opened. The connections share the subscription
+    /// limit of the IP, see [`with_max_subscriptions`](Self::with_max_subscriptions).
+    pub fn new(url: Url, size: usize) -> Self {
+        Self::with_config(url, size, ConnectionConfig::default())
+    }
+
+    /// Opens `size` connections to `url` with custom keepalive and reconnection settings.
+    pub fn with_config(url: Url, size: usize, config: ConnectionConfig) -> Self {
+        let mut handles = vec![];
+        let mut streams = SelectAll::new();
+        for index in 0..size.max(1) {
+            let (handle, stream) = Connection::with_config(url.clone(), config).split();
+            handles.push(handle);
+            streams.push(
+                stream
+                    .map(move |event| PoolEvent {
+                        connection: index,
+                        event,
+                    })
+                    .boxed(),
+            );
+        }
+
+        let state = State {
+            shards: handles.iter().map(|_| Shard::default()).collect(),
+            assignments: HashMap::new(),
+        };
+
+        Self {
+            max_subscriptions: (MAX_SUBSCRIPTIONS_PER_IP / handles.len()).max(1),
+            handles,
+            streams,
+            state: Mutex::new(state),
+        }
+    }
+
+    /// Limits the number of subscriptions per connection.
+    ///
+    /// Defaults to [`MAX_SUBSCRIPTIONS_PER_IP`] divided by the number of connections.
+    #[must_use]
+    pub fn with_max_subscriptions(mut self, max: usize) -> Self {
+        self.max_subscriptions = max.max(1);
+        self
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the number of connections.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.handles.len()
+    }
+
+    /// Returns true if the pool has no connections.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.handles.is_empty()
+    }
+
+    /// Subscribes to a channel on the least loaded connection.
+    ///
+    /// If you're already subscribed to this channel, this is a no-op and the handle
+    /// tracks the existing subscription. The handle tracks the subscription on the
+    /// connection it is currently assigned to: if that connection drops and the
+    /// subscription is moved, the handle reports it as unsubscribed; use
+    /// [`status`](Self::status) to follow it across connections.
+    ///
+    /// Fails if every connection carries the maximum number of subscriptions.
+    pub fn subscribe(&self, subscription: Subscription) -> error::Result<SubscriptionHandle> {
+        let mut state = self.lock();
+        if let Some(&index) = state.assignments.get(&subscription) {
+            return Ok(self.handles[index].subscribe(subscription));
+        }
+
+        let index = state
+            .pick(self.max_subscriptions, None)
+            .ok_or_else(|| Error::WebSocket("every pooled connection is full".into()))?;
+        state.shards[index]
+            .subscriptions
+            .insert(subscription.clone());
+        state.assignments.insert(subscription.clone(), index);
+        Ok(self.handles[index].subscribe(subscription))
+    }
+
+    /// Unsubscribes from a channel.
+    ///
+    /// Does nothing if you're not currently subscribed to this channel.
+    pub fn unsubscribe(&self, subscription: Subscription) {
+        let mut state = self.lock();
+        if let Some(index) = state.assignments.remove(&subscription) {
+            state.shards[index].subscriptions.remove(&subscription);
+            self.handles[index].unsubscribe(subscription);
+        }
+    }
+
+    /// Returns the status of a subscription on the connection carrying it.
+    #[must_use]
+    pub fn status(&self, subscription: &Subscription) -> Option<SubscriptionStatus> {
+        let index = self.connection_of(subscription)?;
+        self.handles[index].status(subscription)
+    }
+
+    /// Returns the index of the connection carrying a subscription.
+    #[must_use]
+    pub fn connection_of(&self, subscription: &Subscription) -> Option<usize> {
+        self.lock().assignments.get(subscription).copied()
+    }
+
+    /// Returns the number of subscriptions carried by each connection.
+    #[must_use]
+    pub fn loads(&self) -> Vec<usize> {
+        self.lock()
+            .shards
+            .iter()
+            .map(|shard| shard.subscriptions.len())
+            .collect()
+    }
+
+    /// Returns the handle of a connection.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[must_use]
+    pub fn connection(&self, index: usize) -> &ConnectionHandle {
+        &self.handles[index]
+    }
+
+    /// Returns the statistics of a connection.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[must_use]
+    pub fn stats(&self, index: usize) -> ConnectionStats {
+        self.handles[index].stats()
+    }
+
+    /// Moves the subscriptions of a disconnected connection to the connected ones.
+    fn drain(&self, state: &mut State, index: usize) {
+        let subscriptions: Vec<_> = state.shards[index].subscriptions.iter().cloned().collect();
+        for subscription in subscriptions {
+            let Some(target) = state
+                .pick(self.max_subscriptions, Some(index))
+                .filter(|target| state.shards[*target].connected)
+            else {
+                // nowhere to go, resubscribed when the connection is back
+                break;
+            };
+            self.move_subscription(state, subscription, index, target);
+        }
+    }
+
+    /// Spreads the subscriptions over the connected connections.
+    ///
+    /// Subscriptions left on dropped connections are moved first, then
+    /// subscriptions move from the most to the least loaded connection until
+    /// their loads differ by at most one. Connections that haven't connected yet
+    /// keep their subscriptions, so opening the pool doesn't move them around.
+    fn rebalance(&self, state: &mut State) {
+        for index in 0..state.shards.len() {
+            let shard = &state.shards[index];
+            if !shard.connected && shard.was_connected {
+                self.drain(state, index);
+            }
+        }
+
+        loop {
+            let connected = state
+                .shards
+                .iter()
+                .enumerate()
+                .filter(|(_, shard)| shard.connected);
+            let (Some((from, most)), Some((to, least))) = (
+                connected
+                    .clone()
+                    .max_by_key(|(_, shard)| shard.subscriptions.len()),
+                connected.min_by_key(|(_, shard)| shard.subscriptions.len()),
+            ) else {
+                return;
+            };
+            if most.subscriptions.len() <= least.subscriptions.len() + 1 {
+                return;
+            }
+
+            let subscription = most.subscriptions.iter().next().cloned().expect("loaded");
+            self.move_subscription(state, subscription, from, to);
+        }
+    }
+
+    fn move_subscription(
+        &self,
+        state: &mut State,
+        subscription: Subscription,
+        from: usize,
+        to: usize,
+    ) {
+        log::debug!("Moving {subscription} from connection {from} to {to}");
+        state.shards[from].subscriptions.remove(&subscription);
+        state.shards[to].subscriptions.insert(subscription.clone());
+        state.assignments.insert(subscription.clone(), to);
+        self.handles[from].unsubscribe(subscription.clone());
+        self.handles[to].subscribe(subscription);
+    }
+
+    fn on_connected(&self, index: usize) {
+        let mut state = self.lock();
+        state.shards[index].connected = true;
+        state.shards[index].was_connected = true;
+        self.rebalance(&mut state);
+    }
+
+    fn on_disconnected(&self, index: usize) {
+        let mut state = self.lock();
+        state.shards[index].connected = false;
+        self.drain(&mut state, index);
+    }
+}
+
+impl futures::Stream for ConnectionPool {
+    type Item = PoolEvent;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let Some(event) = ready!(this.streams.poll_next_unpin(cx)) else {
+            return Poll::Ready(None);
+        };
+
+        match event.event {
+            Event::Connected => this.on_connected(event.connection),
+            Event::Disconnected => this.on_disconnected(event.connection),
+            _ => {}
+        }
+
+        Poll::Ready(Some(event))
+    }
+}
@@ -0,0 +1,409 @@
+//! Client-side rate limiting and retries.
+//!
+//! Hyperliquid enforces two kinds of limits:
+//!
+//! - **IP weight**: every REST request has a weight and each IP can spend
+//!   1200 weight per minute. Exchange actions weigh `1 + floor(batch_len / 40)`,
+//!   info requests weigh 2, 20 or 60 depending on the request type. Info requests
+//!   returning a list of fills, orders, funding or ledger updates weigh one more per
+//!   20 items returned, and candle snapshots one more per 60 candles.
+//! - **Address budget**: each address can send one action per USDC traded
+//!   (plus an initial buffer). A batch of `n` orders or cancels counts as `n` requests.
+//!   Once exhausted, the address is allowed one action every 10 seconds.
+//!
+//! [`RateLimiter`] keeps track of the IP budget and of one budget per address, and
+//! delays requests that would exceed them. The weight of the items returned by an info
+//! request is only known once the response arrives, so it is charged to the requests
+//! that follow.
+//!
+//! An action is charged to the vault it trades for or, without a vault, to the address
+//! that signed it. When trading through an agent wallet, sync the budget of the account
+//! with [`RateLimiter::sync`] keyed by the agent address.
+//!
+//! [`RetryPolicy`] retries requests that failed with a retryable [`Error`]. Actions
+//! are only retried when they can't have reached the exchange, see
+//! [`Client::with_retry`]. Both are opt-in, see [`Client::with_rate_limiter`] and [`Client::with_retry`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use hypersdk::hypercore::{self, RateLimiter, RetryPolicy};
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let limiter = RateLimiter::default();
+//! let client = hypercore::mainnet()
+//!     .with_rate_limiter(limiter.clone())
+//!     .with_retry(RetryPolicy::default().with_max_retries(5));
+//!
+//! // sync the address budget with the exchange
+//! let user = "0x...".parse()?;
+//! client.sync_rate_limit(user).await?;
+//! println!("requests left: {:?}", limiter.address_budget(user));
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Error`]: crate::hypercore::Error
+//! [`Client::with_rate_limiter`]: crate::hypercore::HttpClient::with_rate_limiter
+//! [`Client::with_retry`]: crate::hypercore::HttpClient::with_retry
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+
+use alloy::primitives::Address;
+use tokio::time::{Instant, sleep_until};
+
+use crate::hypercore::{
+    Chain, Error,
+    types::{Action, ActionRequest, UserRateLimit},
+};
+
+/// Default IP weight budget per minute.
+pub const DEFAULT_WEIGHT_PER_MINUTE: u32 = 1200;
+
+/// Interval between actions once the address budget is exhausted.
+const EXHAUSTED_ACTION_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Client-side accounting of the IP weight and address budgets.
+///
+/// The limiter is cheap to clone; clones share the same budgets, so a single
+/// limiter can be used by several clients running from the same IP, trading
+/// for different addresses.
+///
+/// Requests waiting for budget are served in order.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    inner: Arc<tokio::sync::Mutex<Bucket>>,
+    addresses: Arc<Mutex<HashMap<Address, AddressBudget>>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    capacity: f64,
+    available: f64,
+    per_second: f64,
+    updated_at: Instant,
+}
+
+#[derive(Debug)]
+struct AddressBudget {
+    remaining: u64,
+    last_exhausted_action: Option<Instant>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(DEFAULT_WEIGHT_PER_MINUTE)
+    }
+}
+
+impl RateLimiter {
+    /// Creates a limiter allowing `weight_per_minute` IP weight per minute.
+    ///
+    /// The full budget is available immediately and refills continuously.
+    #[must_use]
+    pub fn new(weight_per_minute: u32) -> Self {
+        let capacity = f64::from(weight_per_minute.max(1));
+        Self {
+            inner: Arc::new(tokio::sync::Mutex::new(Bucket {
+                capacity,
+                available: capacity,
+                per_second: capacity / 60.0,
+                updated_at: Instant::now(),
+            })),
+            addresses: Arc::default(),
+        }
+    }
+
+    /// Waits until `weight` can be spent from the IP budget and spends it.
+    ///
+    /// A weight larger than the whole budget waits for a full budget.
+    pub async fn acquire(&self, weight: u32) {
+        let mut bucket = self.inner.lock().await;
+        let weight = f64::from(weight).min(bucket.capacity);
+
+        bucket.refill();
+        if bucket.available < weight {
+            let missing = weight - bucket.available;
+            let wait = Duration::from_secs_f64(missing / bucket.per_second);
+            sleep_until(bucket.updated_at + wait).await;
+            bucket.refill();
+        }
+        bucket.available -= weight;
+    }
+
+    /// Spends `weight` from the IP budget without waiting.
+    ///
+    /// Used for weight that is only known after the request was sent. The budget
+    /// may go negative, delaying the following requests.
+    pub async fn spend(&self, weight: u32) {
+        let mut bucket = self.inner.lock().await;
+        bucket.refill();
+        bucket.available -= f64::from(weight);
+    }
+
+    /// Returns the IP weight currently available.
+    pub async fn available_weight(&self) -> u32 {
+        let mut bucket = self.inner.lock().await;
+        bucket.refill();
+        bucket.available as u32
+    }
+
+    /// Waits until an action of `requests` requests can be sent by `address`
+    /// and spends them from its budget.
+    ///
+    /// Does nothing until the budget of `address` has been synced with [`sync`](Self::sync).
+    pub async fn acquire_address(&self, address: Address, requests: u64) {
+        let wait_until = {
+            let mut addresses = self
+                .addresses
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            let Some(budget) = addresses.get_mut(&address) else {
+                return;
+            };
+
+            if budget.remaining >= requests {
+                budget.remaining -= requests;
+                return;
+            }
+
+            // exhausted: one action every 10 seconds
+            budget.remaining = 0;
+            let now = Instant::now();
+            let next = budget
+                .last_exhausted_action
+                .map(|last| (last + EXHAUSTED_ACTION_INTERVAL).max(now))
+                .unwrap_or(now);
+            budget.last_exhausted_action = Some(next);
+            next
+        };
+
+        sleep_until(wait_until).await;
+    }
+
+    /// Waits until the signed action can be sent and spends its requests from the
+    /// budget of the vault it trades for or, without a vault, of its signer.
+    pub(crate) async fn acquire_action(&self, req: &ActionRequest, chain: Chain) {
+        let tracked = !self
+            .addresses
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_empty();
+        if !tracked {
+            return;
+        }
+
+        let address = match req.vault_address {
+            Some(vault) => vault,
+            None => match req.recover(chain) {
+                Ok(signer) => signer,
+                Err(_) => return,
+            },
+        };
+        self.acquire_address(address, action_requests(&req.action))
+            .await;
+    }
+
+    /// Syncs the budget of `address` with a [`UserRateLimit`] response.
+    pub fn sync(&self, address: Address, limit: &UserRateLimit) {
+        let mut addresses = self
+            .addresses
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let budget = addresses.entry(address).or_insert(AddressBudget {
+            remaining: 0,
+            last_exhausted_action: None,
+        });
+        budget.remaining = limit.remaining();
+    }
+
+    /// Returns the requests left in the budget of `address`, if it was synced.
+    #[must_use]
+    pub fn address_budget(&self, address: Address) -> Option<u64> {
+        self.addresses
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&address)
+            .map(|budget| budget.remaining)
+    }
+}
+
+impl Bucket {
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.updated_at).as_secs_f64();
+        self.available = (self.available + elapsed * self.per_second).min(self.capacity);
+        self.updated_at = now;
+    }
+}
+
+/// Retry policy for retryable errors.
+///
+/// Requests failing with an error for which [`Error::is_retryable`] returns true
+/// are retried up to `max_retries` times, waiting an exponentially increasing
+/// backoff between attempts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Maximum number of retries after the first attempt
+    pub max_retries: u32,
+    /// Backoff before the first retry
+    pub initial_backoff: Duration,
+    /// Upper bound of the backoff
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Sets the maximum number of retries.
+    #[must_use]
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Sets the backoff before the first retry.
+    #[must_use]
+    pub fn with_initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Sets the upper bound of the backoff.
+    #[must_use]
+    pub fn with_max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Returns the backoff before retry number `attempt` (starting at 0).
+    #[must_use]
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff)
+    }
+
+    /// Returns true if `err` should be retried after `attempt` retries.
+    #[must_use]
+    pub fn should_retry(&self, err: &Error, attempt: u32) -> bool {
+        attempt < self.max_retries && err.is_retryable()
+    }
+}
+
+/// Returns the IP weight of an exchange action.
+pub(crate) fn action_weight(action: &Action) -> u32 {
+    1 + (action_requests(action) / 40) as u32
+}
+
+/// Returns the number of requests an action counts for in the address budget.
+pub(crate) fn action_requests(action: &Action) -> u64 {
+    let len = match action {
+        Action::Order(batch) => batch.orders.len(),
+        Action::BatchModify(batch) => batch.modifies.len(),
+        Action::Cancel(batch) => batch.cancels.len(),
+        Action::CancelByCloid(batch) => batch.cancels.len(),
+        _ => 1,
+    };
+    len.max(1) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit(used: u64, cap: u64) -> UserRateLimit {
+        serde_json::from_value(serde_json::json!({
+            "cumVlm": "1000.5",
+            "nRequestsUsed": used,
+            "nRequestsCap": cap,
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_weight_budget() {
+        let limiter = RateLimiter::new(60);
+        let start = Instant::now();
+
+        limiter.acquire(60).await;
+        assert_eq!(limiter.available_weight().await, 0);
+        assert_eq!(Instant::now(), start);
+
+        // refills at 1 weight per second
+        limiter.acquire(20).await;
+        assert_eq!(Instant::now() - start, Duration::from_secs(20));
+
+        // larger than the budget waits for a full budget
+        limiter.acquire(1000).await;
+        assert_eq!(Instant::now() - start, Duration::from_secs(80));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_spend_weight() {
+        let limiter = RateLimiter::new(60);
+        let start = Instant::now();
+
+        // spent after the fact, delays the next request
+        limiter.spend(70).await;
+        limiter.acquire(10).await;
+        assert_eq!(Instant::now() - start, Duration::from_secs(20));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_address_budget() {
+        let limiter = RateLimiter::default();
+        let alice = Address::repeat_byte(1);
+        let bob = Address::repeat_byte(2);
+        let start = Instant::now();
+
+        // not synced: no limit
+        limiter.acquire_address(alice, 100).await;
+        assert_eq!(limiter.address_budget(alice), None);
+
+        limiter.sync(alice, &limit(8, 10));
+        limiter.sync(bob, &limit(0, 10));
+        assert_eq!(limiter.address_budget(alice), Some(2));
+        limiter.acquire_address(alice, 2).await;
+        assert_eq!(limiter.address_budget(alice), Some(0));
+        assert_eq!(Instant::now(), start);
+
+        // budgets are independent
+        limiter.acquire_address(bob, 5).await;
+        assert_eq!(limiter.address_budget(bob), Some(5));
+
+        // exhausted: one action every 10 seconds
+        limiter.acquire_address(alice, 1).await;
+        assert_eq!(Instant::now(), start);
+        limiter.acquire_address(alice, 1).await;
+        assert_eq!(Instant::now() - start, Duration::from_secs(10));
+        assert_eq!(limiter.address_budget(bob), Some(5));
+    }
+
+    #[test]
+    fn test_retry_backoff() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.backoff(0), Duration::from_millis(250));
+        assert_eq!(policy.backoff(2), Duration::from_secs(1));
+        assert_eq!(policy.backoff(10), Duration::from_secs(5));
+
+        assert!(policy.should_retry(&Error::Timeout, 0));
+        assert!(policy.should_retry(&Error::RateLimited, 2));
+        assert!(!policy.should_retry(&Error::Timeout, 3));
+        assert!(!policy.should_retry(&Error::Api("bad".into()), 0));
+    }
+}
@@ -306,6 +306,7 @@ mod tests {
                 cloid: Default::default(),
             }],
             grouping: OrderGrouping::Na,
+            builder: None,
         };
 
         let action = Action::Order(order.clone());
@@ -324,4 +325,55 @@ mod tests {
             "Recovered address should match the signer's address for RMP-based action"
         );
     }
+
+    #[test]
+    fn test_recover_approve_builder_fee() {
+        let signer = get_signer();
+
+        let approve = types::api::ApproveBuilderFee {
+            signature_chain_id: ARBITRUM_MAINNET_CHAIN_ID.to_owned(),
+            hyperliquid_chain: Chain::Mainnet,
+            max_fee_rate: "0.01%".into(),
+            builder: "0x0D1d9635D0640821d15e323ac8AdADfA9c111414"
+                .parse()
+                .unwrap(),
+            nonce: 1690393044548,
+        };
+
+        let action = Action::ApproveBuilderFee(approve);
+        let nonce = 1690393044548u64;
+        let action_request = action
+            .clone()
+            .sign_sync(&signer, nonce, None, None, Chain::Mainnet)
+            .unwrap();
+
+        let recovered_address = action
+            .recover(&action_request.signature, nonce, None, None, Chain::Mainnet)
+            .unwrap();
+        assert_eq!(recovered_address, signer.address());
+    }
+
+    #[test]
+    fn test_batch_order_builder_hash() {
+        use types::{BatchOrder, Builder, OrderGrouping};
+
+        let mut order = BatchOrder {
+            orders: vec![],
+            grouping: OrderGrouping::Na,
+            builder: None,
+        };
+        let nonce = 1690393044548u64;
+        let without = Action::Order(order.clone())
+            .hash(nonce, None, None)
+            .unwrap();
+
+        order.builder = Some(Builder {
+            address: "0x0D1d9635D0640821d15e323ac8AdADfA9c111414"
+                .parse()
+                .unwrap(),
+            fee: 10,
+        });
+        let with = Action::Order(order).hash(nonce, None, None).unwrap();
+        assert_ne!(without, with, "the builder is part of the signed action");
+    }
 }
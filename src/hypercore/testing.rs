@@ -0,0 +1,1795 @@
+//! In-process mock of the Hyperliquid API.
+//!
+//! [`MockServer`] serves `/info`, `/exchange` and `/ws` on a local port so the
+//! [`HttpClient`] and [`WebSocket`] can be exercised end-to-end without mainnet.
+//!
+//! The mock keeps a small simulated exchange:
+//!
+//! - perpetual markets (BTC and ETH by default, see [`MockServer::add_perp`]),
+//! - a USDC balance per user, used for margin checks and `usdSend`,
+//! - resting orders and fills.
+//!
+//! Exchange actions are authenticated like on Hyperliquid: the signer is recovered
+//! from the signature and must have a balance (see [`MockServer::set_balance`]),
+//! and nonces can't be reused. Orders are validated against the tick size, the
+//! minimum notional and the available margin, and rest on the book until the test
+//! fills them with [`MockServer::fill`] or the user cancels them. IOC orders fill
+//! against the mid price set with [`MockServer::set_mid`].
+//!
+//! Websocket clients receive `orderUpdates` and `userFills` for their subscriptions,
+//! can send post requests, and [`MockServer::disconnect`] drops every connection
+//! to test reconnections. [`MockServer::refuse_connections`] keeps clients disconnected.
+//!
+//! Available with the `testing` feature.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use hypersdk::hypercore::{PrivateKeySigner, testing::MockServer, types::*};
+//! use rust_decimal::dec;
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let server = MockServer::start().await?;
+//! let signer = PrivateKeySigner::random();
+//! server.set_balance(signer.address(), dec!(1000));
+//!
+//! let client = server.client();
+//! let statuses = client
+//!     .place(
+//!         &signer,
+//!         BatchOrder {
+//!             orders: vec![OrderRequest {
+//!                 asset: 0,
+//!                 is_buy: true,
+//!                 limit_px: dec!(50000),
+//!                 sz: dec!(0.01),
+//!                 reduce_only: false,
+//!                 order_type: OrderTypePlacement::Limit { tif: TimeInForce::Gtc },
+//!                 cloid: Default::default(),
+//!             }],
+//!             grouping: OrderGrouping::Na,
+//!             builder: None,
+//!         },
+//!         1,
+//!         None,
+//!         None,
+//!     )
+//!     .await?;
+//!
+//! // script a fill of the resting order
+//! let oid = statuses[0].oid().unwrap();
+//! server.fill(oid, dec!(0.01));
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    convert::Infallible,
+    io,
+    net::SocketAddr,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use alloy::primitives::{Address, B128};
+use chrono::Utc;
+use futures::{SinkExt, StreamExt};
+use http_body_util::{BodyExt, Full};
+use hyper::{
+    Method, Request, Response, StatusCode,
+    body::{Bytes, Incoming as Body},
+    server::conn::http1,
+    service::service_fn,
+};
+use hyper_util::rt::TokioIo;
+use rust_decimal::Decimal;
+use serde_json::{Value, json};
+use tokio::{
+    net::TcpListener,
+    sync::mpsc::{UnboundedSender, unbounded_channel},
+    task::JoinHandle,
+};
+use url::Url;
+use yawc::{Frame, OpCode, Options};
+
+use crate::hypercore::{
+    Chain, HttpClient, OidOrCloid, PriceTick, WebSocket,
+    types::{
+        Action, ActionRequest, BasicOrder, BatchOrder, Fill, Incoming, OrderRequest, OrderStatus,
+        OrderType, OrderTypePlacement, OrderUpdate, Outgoing, PostRequest, PostResponse, Side,
+        Subscription, TimeInForce, WsBasicOrder,
+    },
+    ws::ConnectionConfig,
+};
+
+/// Minimum order value accepted by the mock, in USDC.
+const MIN_NOTIONAL: Decimal = Decimal::TEN;
+
+/// Mock Hyperliquid server.
+///
+/// The server runs in the background until it is dropped.
+pub struct MockServer {
+    addr: SocketAddr,
+    chain: Chain,
+    state: Arc<Mutex<State>>,
+    task: JoinHandle<()>,
+}
+
+/// Perpetual market listed by the mock.
+#[derive(Debug, Clone)]
+struct MockPerp {
+    name: String,
+    sz_decimals: i64,
+    max_leverage: u64,
+}
+
+#[derive(Debug, Clone)]
+struct MockOrder {
+    user: Address,
+    order: BasicOrder,
+    status: OrderStatus,
+    status_timestamp: u64,
+}
+
+struct Connection {
+    id: u64,
+    tx: UnboundedSender<String>,
+    subscriptions: HashSet<Subscription>,
+}
+
+struct State {
+    chain: Chain,
+    perps: Vec<MockPerp>,
+    mids: HashMap<String, Decimal>,
+    balances: HashMap<Address, Decimal>,
+    orders: BTreeMap<u64, MockOrder>,
+    fills: Vec<(Address, Fill)>,
+    nonces: HashSet<(Address, u64)>,
+    actions: Vec<(Address, Action)>,
+    connections: Vec<Connection>,
+    refuse_connections: bool,
+    next_oid: u64,
+    next_tid: u64,
+    next_connection: u64,
+}
+
+impl MockServer {
+    /// Starts a mock mainnet server on a random local port.
+    pub async fn start() -> io::Result<Self> {
+        Self::start_with_chain(Chain::Mainnet).await
+    }
+
+    /// Starts a mock server for `chain` on a random local port.
+    ///
+    /// The chain determines how exchange signatures are verified.
+    pub async fn start_with_chain(chain: Chain) -> io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(State::new(chain)));
+
+        let task = tokio::spawn(serve(listener, state.clone()));
+
+        Ok(Self {
+            addr,
+            chain,
+            state,
+            task,
+        })
+    }
+
+    /// Returns the base URL of the server.
+    #[must_use]
+    pub fn url(&self) -> Url {
+        Url::parse(&format!("http://{}", self.addr)).expect("valid url")
+    }
+
+    /// Returns an HTTP client pointing to the server.
+    #[must_use]
+    pub fn client(&self) -> HttpClient {
+        HttpClient::new(self.chain).with_url(self.url())
+    }
+
+    /// Returns a websocket connection to the server.
+    #[must_use]
+    pub fn websocket(&self) -> WebSocket {
+        self.websocket_with_config(ConnectionConfig::default())
+    }
+
+    /// Returns a websocket connection to the server with custom reconnection settings.
+    #[must_use]
+    pub fn websocket_with_config(&self, config: ConnectionConfig) -> WebSocket {
+        let mut url = self.url();
+        let _ = url.set_scheme("ws");
+        url.set_path("/ws");
+        WebSocket::with_config(url, config)
+    }
+
+    /// Lists a perpetual market. Its asset index is the number of markets listed before it.
+    pub fn add_perp(&self, name: impl Into<String>, sz_decimals: i64, max_leverage: u64) -> usize {
+        let mut state = self.lock();
+        state.perps.push(MockPerp {
+            name: name.into(),
+            sz_decimals,
+            max_leverage,
+        });
+        state.perps.len() - 1
+    }
+
+    /// Sets the USDC balance of `user`.
+    ///
+    /// Only users with a balance can send exchange actions.
+    pub fn set_balance(&self, user: Address, usdc: Decimal) {
+        self.lock().balances.insert(user, usdc);
+    }
+
+    /// Returns the USDC balance of `user`.
+    #[must_use]
+    pub fn balance(&self, user: Address) -> Decimal {
+        self.lock().balances.get(&user).copied().unwrap_or_default()
+    }
+
+    /// Sets the mid price of `coin` and publishes it to `allMids` subscribers.
+    pub fn set_mid(&self, coin: impl Into<String>, px: Decimal) {
+        let mut state = self.lock();
+        state.mids.insert(coin.into(), px);
+        let msg = Incoming::AllMids {
+            dex: None,
+            mids: state.mids.clone(),
+        };
+        state.publish(&Subscription::AllMids { dex: None }, &msg);
+    }
+
+    /// Returns the open orders of `user`.
+    #[must_use]
+    pub fn open_orders(&self, user: Address) -> Vec<BasicOrder> {
+        self.lock().open_orders(user)
+    }
+
+    /// Returns the current state of an order.
+    #[must_use]
+    pub fn order(&self, oid: u64) -> Option<OrderUpdate<BasicOrder>> {
+        self.lock().orders.get(&oid).map(MockOrder::update)
+    }
+
+    /// Fills up to `sz` of a resting order at its limit price.
+    ///
+    /// Publishes the fill and the order update to the websocket subscribers and
+    /// returns the fill, or `None` if the order is not open.
+    pub fn fill(&self, oid: u64, sz: Decimal) -> Option<Fill> {
+        let mut state = self.lock();
+        let px = state.orders.get(&oid)?.order.limit_px;
+        state.fill(oid, sz, px, false)
+    }
+
+    /// Cancels a resting order as if the exchange did it, e.g. because of margin.
+    pub fn cancel(&self, oid: u64, status: OrderStatus) -> bool {
+        self.lock().close(oid, status)
+    }
+
+    /// Publishes a message to every websocket connection, regardless of subscriptions.
+    pub fn broadcast(&self, msg: &Incoming) {
+        let text = serde_json::to_string(msg).expect("serializable message");
+        for conn in &self.lock().connections {
+            let _ = conn.tx.send(text.clone());
+        }
+    }
+
+    /// Closes all websocket connections.
+    ///
+    /// Clients reconnect and subscribe again as they would on mainnet.
+    pub fn disconnect(&self) {
+        self.lock().connections.clear();
+    }
+
+    /// Rejects new websocket connections while `refuse` is true.
+    ///
+    /// Open connections are kept, see [`disconnect`](Self::disconnect) to drop them.
+    pub fn refuse_connections(&self, refuse: bool) {
+        self.lock().refuse_connections = refuse;
+    }
+
+    /// Closes the `index`-th open websocket connection, oldest first.
+    ///
+    /// Returns false if there are fewer open connections.
+    pub fn close_connection(&self, index: usize) -> bool {
+        let mut state = self.lock();
+        if index >= state.connections.len() {
+            return false;
+        }
+        state.connections.remove(index);
+        true
+    }
+
+    /// Returns the number of open websocket connections.
+    #[must_use]
+    pub fn connections(&self) -> usize {
+        self.lock().connections.len()
+    }
+
+    /// Returns the subscriptions of all websocket connections.
+    #[must_use]
+    pub fn subscriptions(&self) -> Vec<Subscription> {
+        self.lock()
+            .connections
+            .iter()
+            .flat_map(|conn| conn.subscriptions.iter().cloned())
+            .collect()
+    }
+
+    /// Returns the exchange actions accepted so far with their signer.
+    #[must_use]
+    pub fn actions(&self) -> Vec<(Address, Action)> {
+        self.lock().actions.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.task.abort();
+        self.lock().connections.clear();
+    }
+}
+
+impl MockOrder {
+    fn update(&self) -> OrderUpdate<BasicOrder> {
+        OrderUpdate {
+            status: self.status,
+            status_timestamp: self.status_timestamp,
+            order: self.order.clone(),
+        }
+    }
+
+    fn ws_update(&self) -> OrderUpdate<WsBasicOrder> {
+        let order = &self.order;
+        OrderUpdate {
+            status: self.status,
+            status_timestamp: self.status_timestamp,
+            order: WsBasicOrder {
+                timestamp: order.timestamp,
+                coin: order.coin.clone(),
+                side: order.side,
+                limit_px: order.limit_px,
+                sz: order.sz,
+                oid: order.oid,
+                orig_sz: order.orig_sz,
+                cloid: order.cloid,
+            },
+        }
+    }
+}
+
+impl State {
+    fn new(chain: Chain) -> Self {
+        Self {
+            chain,
+            perps: vec![
+                MockPerp {
+                    name: "BTC".into(),
+                    sz_decimals: 5,
+                    max_leverage: 40,
+                },
+                MockPerp {
+                    name: "ETH".into(),
+                    sz_decimals: 4,
+                    max_leverage: 25,
+                },
+            ],
+            mids: HashMap::new(),
+            balances: HashMap::new(),
+            orders: BTreeMap::new(),
+            fills: Vec::new(),
+            nonces: HashSet::new(),
+            actions: Vec::new(),
+            connections: Vec::new(),
+            refuse_connections: false,
+            next_oid: 1,
+            next_tid: 1,
+            next_connection: 1,
+        }
+    }
+
+    fn publish(&self, subscription: &Subscription, msg: &Incoming) {
+        let text = serde_json::to_string(msg).expect("serializable message");
+        for conn in &self.connections {
+            if conn.subscriptions.contains(subscription) {
+                let _ = conn.tx.send(text.clone());
+            }
+        }
+    }
+
+    fn publish_order(&self, oid: u64) {
+        if let Some(order) = self.orders.get(&oid) {
+            self.publish(
+                &Subscription::OrderUpdates { user: order.user },
+                &Incoming::OrderUpdates(vec![order.ws_update()]),
+            );
+        }
+    }
+
+    fn open_orders(&self, user: Address) -> Vec<BasicOrder> {
+        self.orders
+            .values()
+            .filter(|order| order.user == user && matches!(order.status, OrderStatus::Open))
+            .map(|order| order.order.clone())
+            .collect()
+    }
+
+    fn margin_used(&self, user: Address) -> Decimal {
+        self.orders
+            .values()
+            .filter(|order| order.user == user && matches!(order.status, OrderStatus::Open))
+            .map(|order| {
+                let leverage = self
+                    .perps
+                    .iter()
+                    .find(|perp| perp.name == order.order.coin)
+                    .map_or(1, |perp| perp.max_leverage);
+                order.order.limit_px * order.order.sz / Decimal::from(leverage)
+            })
+            .sum()
+    }
+
+    fn find(&self, user: Address, id: &OidOrCloid) -> Option<u64> {
+        self.orders
+            .values()
+            .find(|order| {
+                order.user == user
+                    && matches!(order.status, OrderStatus::Open)
+                    && match id {
+                        OidOrCloid::Left(oid) => order.order.oid == *oid,
+                        OidOrCloid::Right(cloid) => order.order.cloid == Some(*cloid),
+                    }
+            })
+            .map(|order| order.order.oid)
+    }
+
+    fn fill(&mut self, oid: u64, sz: Decimal, px: Decimal, crossed: bool) -> Option<Fill> {
+        let tid = self.next_tid;
+        let order = self
+            .orders
+            .get_mut(&oid)
+            .filter(|order| matches!(order.status, OrderStatus::Open))?;
+
+        let sz = sz.min(order.order.sz);
+        order.order.sz -= sz;
+        order.status_timestamp = now();
+        if order.order.sz.is_zero() {
+            order.status = OrderStatus::Filled;
+        }
+
+        let user = order.user;
+        let fill = Fill {
+            coin: order.order.coin.clone(),
+            px,
+            sz,
+            side: order.order.side,
+            time: order.status_timestamp,
+            start_position: Decimal::ZERO,
+            dir: match order.order.side {
+                Side::Bid => "Open Long",
+                Side::Ask => "Open Short",
+            }
+            .into(),
+            closed_pnl: Decimal::ZERO,
+            hash: format!("{:#066x}", tid),
+            oid,
+            crossed,
+            fee: Decimal::ZERO,
+            tid,
+            cloid: order.order.cloid,
+            fee_token: "USDC".into(),
+            liquidation: None,
+        };
+        self.next_tid += 1;
+        self.fills.push((user, fill.clone()));
+
+        self.publish_order(oid);
+        self.publish(
+            &Subscription::UserFills { user },
+            &Incoming::UserFills {
+                is_snapshot: false,
+                user,
+                fills: vec![fill.clone()],
+            },
+        );
+
+        Some(fill)
+    }
+
+    fn close(&mut self, oid: u64, status: OrderStatus) -> bool {
+        let Some(order) = self
+            .orders
+            .get_mut(&oid)
+            .filter(|order| matches!(order.status, OrderStatus::Open))
+        else {
+            return false;
+        };
+
+        order.status = status;
+        order.status_timestamp = now();
+        self.publish_order(oid);
+        true
+    }
+
+    /// Places one order and returns its response status.
+    fn place(&mut self, user: Address, req: &OrderRequest) -> Value {
+        let asset = req.asset;
+        let Some(perp) = self.perps.get(asset).cloned() else {
+            return json!({ "error": format!("Invalid asset. asset={asset}") });
+        };
+
+        let table = PriceTick::for_perp(perp.sz_decimals);
+        if table.round(req.limit_px) != Some(req.limit_px) {
+            return json!({
+                "error": format!("Price must be divisible by tick size. asset={asset}")
+            });
+        }
+        if req.sz.is_zero() || req.sz.scale() > perp.sz_decimals as u32 {
+            return json!({ "error": format!("Order has invalid size. asset={asset}") });
+        }
+        if req.limit_px * req.sz < MIN_NOTIONAL {
+            return json!({
+                "error": format!("Order must have minimum value of $10. asset={asset}")
+            });
+        }
+
+        let required =
+            self.margin_used(user) + req.limit_px * req.sz / Decimal::from(perp.max_leverage);
+        let balance = self.balances.get(&user).copied().unwrap_or_default();
+        if !req.reduce_only && required > balance {
+            return json!({
+                "error": format!("Insufficient margin to place order. asset={asset}")
+            });
+        }
+
+        let (order_type, tif) = match &req.order_type {
+            OrderTypePlacement::Limit { tif } => (OrderType::Limit, Some(*tif)),
+            OrderTypePlacement::Trigger {
+                is_market: true, ..
+            } => (OrderType::StopMarket, None),
+            OrderTypePlacement::Trigger { .. } => (OrderType::StopLimit, None),
+        };
+
+        let cloid = (!req.cloid.is_zero()).then_some(req.cloid);
+        let oid = self.next_oid;
+        self.next_oid += 1;
+
+        let side = if req.is_buy { Side::Bid } else { Side::Ask };
+        let timestamp = now();
+        self.orders.insert(
+            oid,
+            MockOrder {
+                user,
+                order: BasicOrder {
+                    timestamp,
+                    coin: perp.name.clone(),
+                    side,
+                    limit_px: req.limit_px,
+                    sz: req.sz,
+                    oid,
+                    orig_sz: req.sz,
+                    cloid,
+                    order_type,
+                    tif,
+                    reduce_only: req.reduce_only,
+                },
+                status: OrderStatus::Open,
+                status_timestamp: timestamp,
+            },
+        );
+
+        if !matches!(tif, Some(TimeInForce::Ioc | TimeInForce::FrontendMarket)) {
+            self.publish_order(oid);
+            return json!({ "resting": { "oid": oid, "cloid": cloid } });
+        }
+
+        // IOC: fill against the mid price or cancel
+        let mid = self.mids.get(&perp.name).copied().filter(|mid| {
+            if req.is_buy {
+                req.limit_px >= *mid
+            } else {
+                req.limit_px <= *mid
+            }
+        });
+        match mid {
+            Some(mid) => {
+                self.fill(oid, req.sz, mid, true);
+                json!({
+                    "filled": {
+                        "totalSz": req.sz.to_string(),
+                        "avgPx": mid.to_string(),
+                        "oid": oid,
+                    }
+                })
+            }
+            None => {
+                self.close(oid, OrderStatus::IocCancelRejected);
+                json!({
+                    "error": format!(
+                        "Order could not immediately match against any resting orders. asset={asset}"
+                    )
+                })
+            }
+        }
+    }
+
+    fn place_batch(&mut self, user: Address, batch: &BatchOrder) -> Value {
+        let statuses: Vec<_> = batch
+            .orders
+            .iter()
+            .map(|order| self.place(user, order))
+            .collect();
+        ok(json!({ "type": "order", "data": { "statuses": statuses } }))
+    }
+
+    fn cancel_status(&mut self, user: Address, id: OidOrCloid) -> Value {
+        match self.find(user, &id) {
+            Some(oid) => {
+                self.close(oid, OrderStatus::Canceled);
+                json!("success")
+            }
+            None => json!({
+                "error": "Order was never placed, already canceled, or filled."
+            }),
+        }
+    }
+
+    /// Handles a signed exchange request.
+    fn exchange(&mut self, req: ActionRequest) -> Value {
+        let user = match req.recover(self.chain) {
+            Ok(user) => user,
+            Err(err) => return json!({ "status": "err", "response": err.to_string() }),
+        };
+        if !self.balances.contains_key(&user) {
+            return json!({
+                "status": "err",
+                "response": format!("User or API Wallet {user:#x} does not exist."),
+            });
+        }
+        if !self.nonces.insert((user, req.nonce)) {
+            return json!({ "status": "err", "response": "Invalid nonce: duplicate nonce" });
+        }
+
+        // trade on behalf of the vault or subaccount
+        let user = req.vault_address.unwrap_or(user);
+        self.actions.push((user, req.action.clone()));
+
+        match req.action {
+            Action::Order(batch) => self.place_batch(user, &batch),
+            Action::BatchModify(batch) => {
+                let statuses: Vec<_> = batch
+                    .modifies
+                    .iter()
+                    .map(|modify| match self.find(user, &modify.oid) {
+                        Some(oid) => {
+                            self.close(oid, OrderStatus::Canceled);
+                            self.place(user, &modify.order)
+                        }
+                        None => json!({
+                            "error": "Cannot modify canceled or filled order"
+                        }),
+                    })
+                    .collect();
+                ok(json!({ "type": "order", "data": { "statuses": statuses } }))
+            }
+            Action::Cancel(batch) => {
+                let statuses: Vec<_> = batch
+                    .cancels
+                    .iter()
+                    .map(|cancel| self.cancel_status(user, OidOrCloid::Left(cancel.oid)))
+                    .collect();
+                ok(json!({ "type": "cancel", "data": { "statuses": statuses } }))
+            }
+            Action::CancelByCloid(batch) => {
+                let statuses: Vec<_> = batch
+                    .cancels
+                    .iter()
+                    .map(|cancel| self.cancel_status(user, OidOrCloid::Right(cancel.cloid)))
+                    .collect();
+                ok(json!({ "type": "cancel", "data": { "statuses": statuses } }))
+            }
+            Action::UsdSend(send) => {
+                let balance = self.balances.get(&user).copied().unwrap_or_default();
+                if send.amount > balance - self.margin_used(user) {
+                    return json!({
+                        "status": "err",
+                        "response": "Insufficient balance for withdrawal",
+                    });
+                }
+                *self.balances.entry(user).or_default() -= send.amount;
+                *self.balances.entry(send.destination).or_default() += send.amount;
+                ok(json!({ "type": "default" }))
+            }
+            _ => ok(json!({ "type": "default" })),
+        }
+    }
+
+    /// Handles an info request, returning `None` if the request type is not supported.
+    fn info(&self, req: &Value) -> Option<Value> {
+        let user = || {
+            req.get("user")
+                .and_then(|user| user.as_str()?.parse::<Address>().ok())
+        };
+
+        let resp = match req.get("type")?.as_str()? {
+            "meta" => json!({
+                "universe": self.perps.iter().map(|perp| json!({
+                    "name": perp.name,
+                    "szDecimals": perp.sz_decimals,
+                    "maxLeverage": perp.max_leverage,
+                })).collect::<Vec<_>>(),
+                "collateralToken": 0,
+            }),
+            "spotMeta" => json!({
+                "universe": [],
+                "tokens": [{
+                    "name": "USDC",
+                    "index": 0,
+                    "tokenId": "0x6d1e7cde53ba9467b783cb7c530ce054",
+                    "szDecimals": 8,
+                    "weiDecimals": 8,
+                    "evmContract": null,
+                }],
+            }),
+            "allMids" => json!(self.mids),
+            "openOrders" | "frontendOpenOrders" => json!(self.open_orders(user()?)),
+            "orderStatus" => {
+                let user = user()?;
+                let id = req.get("oid")?;
+                let order = self.orders.values().find(|order| {
+                    order.user == user
+                        && match id {
+                            Value::Number(oid) => oid.as_u64() == Some(order.order.oid),
+                            Value::String(cloid) => cloid.parse::<B128>().ok() == order.order.cloid,
+                            _ => false,
+                        }
+                });
+                match order {
+                    Some(order) => json!({ "status": "order", "order": order.update() }),
+                    None => json!({ "status": "unknownOid" }),
+                }
+            }
+            "userFills" | "userFillsByTime" => {
+                let user = user()?;
+                let start = req.get("startTime").and_then(Value::as_u64).unwrap_or(0);
+                let end = req
+                    .get("endTime")
+                    .and_then(Value::as_u64)
+                    .unwrap_or(u64::MAX);
+                json!(
+                    self.fills
+                        .iter()
+                        .rev()
+                        .filter(|(owner, fill)| *owner == user
+                            && (start..=end).contains(&fill.time))
+                        .map(|(_, fill)| fill)
+                        .collect::<Vec<_>>()
+                )
+            }
+            "clearinghouseState" => {
+                let user = user()?;
+                let balance = self.balances.get(&user).copied().unwrap_or_default();
+                let used = self.margin_used(user);
+                let summary = json!({
+                    "accountValue": balance.to_string(),
+                    "totalNtlPos": "0",
+                    "totalRawUsd": balance.to_string(),
+                    "totalMarginUsed": used.to_string(),
+                });
+                json!({
+                    "marginSummary": summary,
+                    "crossMarginSummary": summary,
+                    "crossMaintenanceMarginUsed": "0",
+                    "withdrawable": (balance - used).to_string(),
+                    "assetPositions": [],
+                    "time": now(),
+                })
+            }
+            "spotClearinghouseState" => {
+                let user = user()?;
+                let balance = self.balances.get(&user).copied().unwrap_or_default();
+                json!({
+                    "balances": [{
+                        "coin": "USDC",
+                        "token": 0,
+                        "hold": "0",
+                        "total": balance.to_string(),
+                        "entryNtl": "0",
+                    }],
+                })
+            }
+            "userRateLimit" => {
+                let user = user()?;
+                let used = self
+                    .actions
+                    .iter()
+                    .filter(|(owner, _)| *owner == user)
+                    .count();
+                json!({
+                    "cumVlm": "0",
+                    "nRequestsUsed": used,
+                    "nRequestsCap": 10000,
+                })
+            }
+            _ => return None,
+        };
+
+        Some(resp)
+    }
+}
+
+fn ok(response: Value) -> Value {
+    json!({ "status": "ok", "response": response })
+}
+
+fn now() -> u64 {
+    Utc::now().timestamp_millis() as u64
+}
+
+async fn serve(listener: TcpListener, state: Arc<Mutex<State>>) {
+    loop {
+        let Ok((stream, _)) = listener.accept().await else {
+            continue;
+        };
+
+        let state = state.clone();
+        tokio::spawn(async move {
+            let service = service_fn(move |req| handle(req, state.clone()));
+            if let Err(err) = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .with_upgrades()
+                .await
+            {
+                log::debug!("mock connection error: {err}");
+            }
+        });
+    }
+}
+
+async fn handle(
+    mut req: Request<Body>,
+    state: Arc<Mutex<State>>,
+) -> Result<Response<Full<Bytes>>, Infallible> {
+    if req.uri().path() == "/ws" {
+        if state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .refuse_connections
+        {
+            return Ok(reply(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "refusing connections",
+            ));
+        }
+        return Ok(
+            match yawc::WebSocket::upgrade_with_options(
+                &mut req,
+                Options::default().with_balanced_compression(),
+            ) {
+                Ok((response, upgrade)) => {
+                    tokio::spawn(async move {
+                        if let Ok(ws) = upgrade.await {
+                            websocket(ws, state).await;
+                        }
+                    });
+                    response.map(|_| Full::default())
+                }
+                Err(err) => reply(StatusCode::BAD_REQUEST, err.to_string()),
+            },
+        );
+    }
+
+    if req.method() != Method::POST {
+        return Ok(reply(StatusCode::METHOD_NOT_ALLOWED, "POST only"));
+    }
+
+    let path = req.uri().path().to_owned();
+    let body = match req.into_body().collect().await {
+        Ok(body) => body.to_bytes(),
+        Err(err) => return Ok(reply(StatusCode::BAD_REQUEST, err.to_string())),
+    };
+
+    let resp = match path.as_str() {
+        "/info" => {
+            let Ok(req) = serde_json::from_slice::<Value>(&body) else {
+                return Ok(reply(StatusCode::UNPROCESSABLE_ENTITY, "invalid json"));
+            };
+            let state = state.lock().unwrap_or_else(PoisonError::into_inner);
+            match state.info(&req) {
+                Some(resp) => resp,
+                None => {
+                    return Ok(reply(
+                        StatusCode::UNPROCESSABLE_ENTITY,
+                        format!("unsupported info request: {req}"),
+                    ));
+                }
+            }
+        }
+        "/exchange" => {
+            let req = match serde_json::from_slice::<ActionRequest>(&body) {
+                Ok(req) => req,
+                Err(err) => {
+                    return Ok(reply(
+                        StatusCode::UNPROCESSABLE_ENTITY,
+                        format!("Failed to deserialize the JSON body: {err}"),
+                    ));
+                }
+            };
+            state
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .exchange(req)
+        }
+        _ => return Ok(reply(StatusCode::NOT_FOUND, "not found")),
+    };
+
+    Ok(reply(StatusCode::OK, resp.to_string()))
+}
+
+fn reply(status: StatusCode, body: impl Into<Bytes>) -> Response<Full<Bytes>> {
+    let mut resp = Response::new(Full::new(body.into()));
+    *resp.status_mut() = status;
+    resp
+}
+
+async fn websocket(mut ws: yawc::WebSocket<yawc::HttpStream>, state: Arc<Mutex<State>>) {
+    let (tx, mut rx) = unbounded_channel();
+    let id = {
+        let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+        let id = state.next_connection;
+        state.next_connection += 1;
+        state.connections.push(Connection {
+            id,
+            tx,
+            subscriptions: HashSet::new(),
+        });
+        id
+    };
+
+    loop {
+        tokio::select! {
+            frame = ws.next() => {
+                let Some(frame) = frame else { break };
+                if frame.opcode() != OpCode::Text {
+                    continue;
+                }
+                let Ok(msg) = serde_json::from_slice::<Outgoing>(frame.payload()) else {
+                    continue;
+                };
+
+                let replies = {
+                    let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+                    state.on_message(id, msg)
+                };
+                for reply in replies {
+                    if ws.send(Frame::text(reply)).await.is_err() {
+                        break;
+                    }
+                }
+            }
+            msg = rx.recv() => {
+                // the sender is dropped by `MockServer::disconnect`
+                let Some(msg) = msg else { break };
+                if ws.send(Frame::text(msg)).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
+    let _ = ws.close().await;
+    state
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .connections
+        .retain(|conn| conn.id != id);
+}
+
+impl State {
+    /// Handles a websocket message and returns the replies.
+    fn on_message(&mut self, id: u64, msg: Outgoing) -> Vec<String> {
+        let Some(conn) = self.connections.iter_mut().find(|conn| conn.id == id) else {
+            return vec![];
+        };
+
+        let mut replies = vec![];
+        match &msg {
+            Outgoing::Subscribe { subscription } => {
+                let coin = match subscription {
+                    Subscription::Bbo { coin }
+                    | Subscription::Trades { coin }
+                    | Subscription::L2Book { coin, .. }
+                    | Subscription::Candle { coin, .. }
+                    | Subscription::ActiveAssetCtx { coin } => Some(coin),
+                    _ => None,
+                };
+                if coin.is_some_and(|coin| !self.perps.iter().any(|perp| perp.name == *coin)) {
+                    let json = serde_json::to_string(subscription).expect("serializable");
+                    replies.push(Incoming::Error(format!("Invalid subscription {json}")));
+                } else {
+                    conn.subscriptions.insert(subscription.clone());
+                    replies.push(Incoming::SubscriptionResponse(msg.clone()));
+
+                    if let Subscription::UserFills { user } = subscription {
+                        replies.push(Incoming::UserFills {
+                            is_snapshot: true,
+                            user: *user,
+                            fills: self
+                                .fills
+                                .iter()
+                                .filter(|(owner, _)| owner == user)
+                                .map(|(_, fill)| fill.clone())
+                                .collect(),
+                        });
+                    }
+                }
+            }
+            Outgoing::Unsubscribe { subscription } => {
+                conn.subscriptions.remove(subscription);
+                replies.push(Incoming::SubscriptionResponse(msg.clone()));
+            }
+            Outgoing::Post { id, request } => {
+                let response = match request {
+                    PostRequest::Info(req) => match self.info(req) {
+                        Some(data) => PostResponse::Info(json!({
+                            "type": req.get("type"),
+                            "data": data,
+                        })),
+                        None => PostResponse::Error(format!("unsupported info request: {req}")),
+                    },
+                    PostRequest::Action(req) => {
+                        PostResponse::Action(self.exchange((**req).clone()))
+                    }
+                };
+                replies.push(Incoming::Post { id: *id, response });
+            }
+            Outgoing::Ping => replies.push(Incoming::Pong),
+            Outgoing::Pong => {}
+        }
+
+        replies
+            .iter()
+            .map(|reply| serde_json::to_string(reply).expect("serializable message"))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rust_decimal::dec;
+
+    use super::*;
+    use crate::hypercore::{
+        ConnectionPool, Error, PrivateKeySigner, UserStream, UserStreamEvent,
+        types::{OrderGrouping, Trade},
+        ws::{ChannelStream, Event, SubscriptionStatus},
+    };
+
+    fn order(asset: usize, px: Decimal, sz: Decimal, tif: TimeInForce) -> BatchOrder {
+        BatchOrder {
+            orders: vec![OrderRequest {
+                asset,
+                is_buy: true,
+                limit_px: px,
+                sz,
+                reduce_only: false,
+                order_type: OrderTypePlacement::Limit { tif },
+                cloid: B128::random(),
+            }],
+            grouping: OrderGrouping::Na,
+            builder: None,
+        }
+    }
+
+    async fn next_message(ws: &mut WebSocket) -> Incoming {
+        loop {
+            let event = tokio::time::timeout(Duration::from_secs(5), ws.next())
+                .await
+                .expect("websocket message")
+                .expect("open stream");
+            match event {
+                Event::Message(Incoming::SubscriptionResponse(_)) => {}
+                Event::Message(msg) => return msg,
+                _ => {}
+            }
+        }
+    }
+
+    async fn wait_event(ws: &mut WebSocket, pred: impl Fn(&Event) -> bool) {
+        loop {
+            let event = tokio::time::timeout(Duration::from_secs(5), ws.next())
+                .await
+                .expect("websocket event")
+                .expect("open stream");
+            if pred(&event) {
+                return;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mock_orders() {
+        let server = MockServer::start().await.unwrap();
+        let signer = PrivateKeySigner::random();
+        let user = signer.address();
+        let client = server.client();
+
+        let perps = client.perps().await.unwrap();
+        assert_eq!(perps.len(), 2);
+        assert_eq!(perps[0].name, "BTC");
+
+        // unknown signer
+        let err = client
+            .place(
+                &signer,
+                order(0, dec!(50000), dec!(0.01), TimeInForce::Gtc),
+                1,
+                None,
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert!(err.message().contains("does not exist"));
+
+        server.set_balance(user, dec!(100));
+
+        // 40x leverage: 0.1 BTC at 50000 needs 125 USDC
+        let statuses = client
+            .place(
+                &signer,
+                order(0, dec!(50000), dec!(0.1), TimeInForce::Gtc),
+                2,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        let err = Error::from_api(statuses[0].error().unwrap());
+        assert!(matches!(err, Error::InsufficientMargin(_)));
+
+        let statuses = client
+            .place(
+                &signer,
+                order(0, dec!(50000.5), dec!(0.01), TimeInForce::Gtc),
+                3,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        let err = Error::from_api(statuses[0].error().unwrap());
+        assert!(matches!(err, Error::InvalidTick(_)));
+
+        let statuses = client
+            .place(
+                &signer,
+                order(0, dec!(50000), dec!(0.01), TimeInForce::Gtc),
+                4,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        let oid = statuses[0].oid().unwrap();
+        assert_eq!(client.open_orders(user, None).await.unwrap().len(), 1);
+
+        // nonces can't be reused
+        let err = client
+            .place(
+                &signer,
+                order(0, dec!(50000), dec!(0.01), TimeInForce::Gtc),
+                4,
+                None,
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err.kind(), Error::InvalidNonce(_)));
+
+        let fill = server.fill(oid, dec!(0.004)).unwrap();
+        assert_eq!(fill.sz, dec!(0.004));
+        let status = client
+            .order_status(user, OidOrCloid::Left(oid))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(status.order.sz, dec!(0.006));
+        assert_eq!(client.user_fills(user).await.unwrap().len(), 1);
+
+        // IOC fills against the mid
+        server.set_mid("ETH", dec!(3000));
+        let statuses = client
+            .place(
+                &signer,
+                order(1, dec!(3010), dec!(0.01), TimeInForce::Ioc),
+                5,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(matches!(
+            statuses[0],
+            crate::hypercore::types::OrderResponseStatus::Filled { avg_px, .. } if avg_px == dec!(3000)
+        ));
+        assert_eq!(server.actions().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_mock_post() {
+        let server = MockServer::start().await.unwrap();
+        let signer = PrivateKeySigner::random();
+        let user = signer.address();
+        server.set_balance(user, dec!(1000));
+
+        let ws = server.websocket();
+        let timeout = Duration::from_secs(5);
+
+        let state: crate::hypercore::types::ClearinghouseState = ws
+            .info(
+                json!({ "type": "clearinghouseState", "user": user }),
+                timeout,
+            )
+            .await
+            .unwrap();
+        assert_eq!(state.withdrawable, dec!(1000));
+
+        let req = Action::from(order(0, dec!(50000), dec!(0.01), TimeInForce::Gtc))
+            .sign_sync(&signer, 1, None, None, Chain::Mainnet)
+            .unwrap();
+        let resp = ws.action(req.clone(), timeout).await.unwrap();
+        assert!(matches!(
+            resp,
+            crate::hypercore::types::api::Response::Ok(_)
+        ));
+        assert_eq!(server.open_orders(user).len(), 1);
+
+        // replayed nonce
+        let resp = ws.action(req, timeout).await.unwrap();
+        assert!(matches!(
+            resp,
+            crate::hypercore::types::api::Response::Err(err) if err.contains("nonce")
+        ));
+
+        let err = ws
+            .info::<Value>(json!({ "type": "unknown" }), timeout)
+            .await
+            .unwrap_err();
+        assert!(err.is_api_error());
+    }
+
+    #[tokio::test]
+    async fn test_mock_post_while_disconnected() {
+        let server = MockServer::start().await.unwrap();
+        let signer = PrivateKeySigner::random();
+        server.set_balance(signer.address(), dec!(1000));
+
+        let config = ConnectionConfig::default()
+            .with_initial_backoff(Duration::from_millis(20))
+            .with_jitter(0.0);
+        let mut ws = server.websocket_with_config(config);
+        wait_event(&mut ws, |event| matches!(event, Event::Connected)).await;
+
+        server.refuse_connections(true);
+        server.disconnect();
+        wait_event(&mut ws, |event| matches!(event, Event::Disconnected)).await;
+
+        let req = Action::from(order(0, dec!(50000), dec!(0.01), TimeInForce::Gtc))
+            .sign_sync(&signer, 1, None, None, Chain::Mainnet)
+            .unwrap();
+        let err = ws
+            .action(req, Duration::from_millis(100))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Timeout));
+
+        server.refuse_connections(false);
+        wait_event(&mut ws, |event| matches!(event, Event::Connected)).await;
+
+        // posted after the timed out action, so answered once it was skipped
+        let _: Value = ws
+            .info(json!({ "type": "allMids" }), Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(server.actions().is_empty());
+        assert!(server.open_orders(signer.address()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_mock_websocket() {
+        let server = MockServer::start().await.unwrap();
+        let signer = PrivateKeySigner::random();
+        let user = signer.address();
+        server.set_balance(user, dec!(1000));
+
+        let mut ws = server.websocket();
+        ws.subscribe(Subscription::UserFills { user });
+        assert!(matches!(
+            next_message(&mut ws).await,
+            Incoming::UserFills {
+                is_snapshot: true,
+                ..
+            }
+        ));
+
+        let statuses = server
+            .client()
+            .place(
+                &signer,
+                order(0, dec!(50000), dec!(0.01), TimeInForce::Gtc),
+                1,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        server.fill(statuses[0].oid().unwrap(), dec!(0.01));
+        match next_message(&mut ws).await {
+            Incoming::UserFills {
+                is_snapshot, fills, ..
+            } => {
+                assert!(!is_snapshot);
+                assert_eq!(fills[0].sz, dec!(0.01));
+            }
+            msg => panic!("unexpected message: {msg:?}"),
+        }
+
+        // reconnects and subscribes again
+        server.disconnect();
+        loop {
+            let event = tokio::time::timeout(Duration::from_secs(5), ws.next())
+                .await
+                .unwrap()
+                .unwrap();
+            if matches!(event, Event::Disconnected) {
+                break;
+            }
+        }
+        assert!(matches!(
+            next_message(&mut ws).await,
+            Incoming::UserFills { is_snapshot: true, fills, .. } if fills.len() == 1
+        ));
+        assert_eq!(server.connections(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_mock_subscription_status() {
+        let server = MockServer::start().await.unwrap();
+        let mut ws = server.websocket();
+
+        let trades = Subscription::Trades { coin: "BTC".into() };
+        let mut handle = ws.subscribe(trades.clone());
+        tokio::time::timeout(Duration::from_secs(5), handle.wait())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(ws.status(&trades), Some(SubscriptionStatus::Active));
+
+        // errors not quoting a subscription are not assigned to one
+        server.broadcast(&Incoming::Error("Server overloaded".into()));
+        let bbo = Subscription::Bbo { coin: "ETH".into() };
+        ws.subscribe(bbo.clone());
+        loop {
+            let event = tokio::time::timeout(Duration::from_secs(5), ws.next())
+                .await
+                .unwrap()
+                .unwrap();
+            if let Event::SubscriptionError {
+                subscription,
+                error,
+            } = event
+            {
+                assert_eq!(subscription, None);
+                assert_eq!(error, "Server overloaded");
+                break;
+            }
+        }
+
+        let unknown = Subscription::Trades { coin: "FOO".into() };
+        let mut handle = ws.subscribe(unknown.clone());
+        let err = tokio::time::timeout(Duration::from_secs(5), handle.wait())
+            .await
+            .unwrap()
+            .unwrap_err();
+        assert!(err.is_api_error());
+        assert!(ws.status(&unknown).unwrap().is_rejected());
+
+        loop {
+            let event = tokio::time::timeout(Duration::from_secs(5), ws.next())
+                .await
+                .unwrap()
+                .unwrap();
+            if let Event::SubscriptionError {
+                subscription,
+                error,
+            } = event
+            {
+                assert_eq!(subscription, Some(unknown.clone()));
+                assert!(error.starts_with("Invalid subscription"));
+                break;
+            }
+        }
+
+        assert!(!ws.status(&bbo).unwrap().is_rejected());
+
+        ws.unsubscribe(trades.clone());
+        assert_eq!(ws.status(&trades), Some(SubscriptionStatus::Unsubscribed));
+        assert_eq!(ws.status(&Subscription::l2_book("ETH")), None);
+    }
+
+    #[tokio::test]
+    async fn test_mock_reconnect_stats() {
+        let server = MockServer::start().await.unwrap();
+        let config = ConnectionConfig::default()
+            .with_ping_interval(Duration::from_millis(50))
+            .with_initial_backoff(Duration::from_millis(10))
+            .with_jitter(0.5)
+            .with_max_attempts(3);
+        let mut ws = server.websocket_with_config(config);
+        let stats = ws.stats();
+
+        let trades = Subscription::Trades { coin: "BTC".into() };
+        tokio::time::timeout(Duration::from_secs(5), ws.subscribe(trades).wait())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(ws.is_connected());
+        assert_eq!(stats.message_count("subscriptionResponse"), 1);
+        assert!(stats.last_message_age().is_some());
+
+        while stats.rtt().is_none() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        server.disconnect();
+        let mut connected = 0;
+        while connected < 2 {
+            let event = tokio::time::timeout(Duration::from_secs(5), ws.next())
+                .await
+                .unwrap()
+                .unwrap();
+            if matches!(event, Event::Connected) {
+                connected += 1;
+            }
+        }
+        assert_eq!(stats.reconnects(), 1);
+
+        // gives up once the server is gone
+        drop(server);
+        loop {
+            let event = tokio::time::timeout(Duration::from_secs(5), ws.next())
+                .await
+                .unwrap();
+            if event.is_none() {
+                break;
+            }
+        }
+        assert!(!stats.is_connected());
+    }
+
+    #[tokio::test]
+    async fn test_mock_user_stream_backfill() {
+        let server = MockServer::start().await.unwrap();
+        let signer = PrivateKeySigner::random();
+        let user = signer.address();
+        server.set_balance(user, dec!(1000));
+
+        let mut stream = UserStream::new(server.client(), server.websocket(), user);
+        async fn next_event(stream: &mut UserStream) -> UserStreamEvent {
+            tokio::time::timeout(Duration::from_secs(5), stream.next())
+                .await
+                .expect("user stream event")
+                .expect("open stream")
+        }
+        assert!(matches!(
+            next_event(&mut stream).await,
+            UserStreamEvent::Connected
+        ));
+
+        let mut oids = vec![];
+        for nonce in [1, 2] {
+            let statuses = server
+                .client()
+                .place(
+                    &signer,
+                    order(0, dec!(50000), dec!(0.01), TimeInForce::Gtc),
+                    nonce,
+                    None,
+                    None,
+                )
+                .await
+                .unwrap();
+            oids.push(statuses[0].oid().unwrap());
+        }
+        while stream.open_orders().len() < 2 {
+            next_event(&mut stream).await;
+        }
+
+        // missed by the websocket
+        server.disconnect();
+        let fill = server.fill(oids[0], dec!(0.01)).unwrap();
+        assert!(server.cancel(oids[1], OrderStatus::Canceled));
+
+        let mut events = vec![];
+        while let Ok(Some(event)) =
+            tokio::time::timeout(Duration::from_millis(500), stream.next()).await
+        {
+            events.push(event);
+        }
+
+        let fills: Vec<_> = events
+            .iter()
+            .filter_map(|event| match event {
+                UserStreamEvent::Fill(fill) => Some(fill.tid),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(fills, vec![fill.tid]);
+
+        let orders: Vec<_> = events
+            .iter()
+            .filter_map(|event| match event {
+                UserStreamEvent::Order(update) => Some((update.order.oid, update.status)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(orders.len(), 2);
+        assert!(orders.contains(&(oids[0], OrderStatus::Filled)));
+        assert!(orders.contains(&(oids[1], OrderStatus::Canceled)));
+        assert!(stream.open_orders().is_empty());
+        assert!(
+            !events
+                .iter()
+                .any(|event| matches!(event, UserStreamEvent::BackfillFailed { .. }))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mock_pool() {
+        let server = MockServer::start().await.unwrap();
+        let mut url = server.url();
+        let _ = url.set_scheme("ws");
+        url.set_path("/ws");
+        let mut pool = ConnectionPool::new(url, 3);
+
+        let mut subscriptions = vec![];
+        for coin in ["BTC", "ETH"] {
+            subscriptions.push(Subscription::Trades { coin: coin.into() });
+            subscriptions.push(Subscription::Bbo { coin: coin.into() });
+            subscriptions.push(Subscription::l2_book(coin));
+        }
+        for subscription in &subscriptions {
+            pool.subscribe(subscription.clone()).unwrap();
+        }
+        assert_eq!(pool.loads(), vec![2, 2, 2]);
+        let assignments: Vec<_> = subscriptions
+            .iter()
+            .map(|sub| pool.connection_of(sub))
+            .collect();
+
+        let mut connected = HashSet::new();
+        while connected.len() < 3 {
+            let event = tokio::time::timeout(Duration::from_secs(5), pool.next())
+                .await
+                .unwrap()
+                .unwrap();
+            if matches!(event.event, Event::Connected) {
+                connected.insert(event.connection);
+            }
+        }
+        // connecting for the first time doesn't move anything
+        assert!(
+            subscriptions
+                .iter()
+                .zip(&assignments)
+                .all(|(sub, connection)| pool.connection_of(sub) == *connection)
+        );
+
+        // the drained connection's subscriptions move to the others
+        assert!(server.close_connection(0));
+        let dropped = loop {
+            let event = tokio::time::timeout(Duration::from_secs(5), pool.next())
+                .await
+                .unwrap()
+                .unwrap();
+            if matches!(event.event, Event::Disconnected) {
+                break event.connection;
+            }
+        };
+        let loads = pool.loads();
+        assert_eq!(loads[dropped], 0);
+        assert_eq!(loads.iter().sum::<usize>(), 6);
+        assert!(
+            subscriptions
+                .iter()
+                .all(|sub| pool.connection_of(sub) != Some(dropped))
+        );
+
+        // and some move back once it reconnects
+        loop {
+            let event = tokio::time::timeout(Duration::from_secs(5), pool.next())
+                .await
+                .unwrap()
+                .unwrap();
+            if event.connection == dropped && matches!(event.event, Event::Connected) {
+                break;
+            }
+        }
+        assert_eq!(pool.loads(), vec![2, 2, 2]);
+
+        // full
+        let pool = pool.with_max_subscriptions(3);
+        assert!(
+            pool.subscribe(Subscription::Trades { coin: "SOL".into() })
+                .is_ok()
+        );
+        assert!(
+            pool.subscribe(Subscription::Bbo { coin: "SOL".into() })
+                .is_ok()
+        );
+        assert!(pool.subscribe(Subscription::l2_book("SOL")).is_ok());
+        assert!(pool.subscribe(Subscription::l2_book("DOGE")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_mock_typed_streams() {
+        let server = MockServer::start().await.unwrap();
+        let signer = PrivateKeySigner::random();
+        let user = signer.address();
+        server.set_balance(user, dec!(1000));
+
+        let ws = server.websocket();
+        let mut first = ws.trades("BTC");
+        let mut second = ws.trades("BTC");
+        let mut fills = ws.user_fills(user);
+        for stream in [&mut first, &mut second] {
+            tokio::time::timeout(Duration::from_secs(5), stream.wait())
+                .await
+                .unwrap()
+                .unwrap();
+        }
+
+        let snapshot = tokio::time::timeout(Duration::from_secs(5), fills.next())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(snapshot.is_snapshot);
+
+        let trade = Trade {
+            coin: "BTC".into(),
+            side: Side::Bid,
+            px: dec!(50000),
+            sz: dec!(0.1),
+            time: 1,
+            hash: "0x01".into(),
+            tid: 1,
+            users: [Address::ZERO, user],
+            liquidation: None,
+        };
+        server.broadcast(&Incoming::Trades(vec![trade]));
+        for stream in [&mut first, &mut second] {
+            let trades = tokio::time::timeout(Duration::from_secs(5), stream.next())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(trades[0].tid, 1);
+        }
+
+        // unsubscribed once the last stream is dropped
+        let subscription = Subscription::Trades { coin: "BTC".into() };
+        drop(first);
+        assert!(ws.status(&subscription).unwrap().is_active());
+        drop(second);
+        assert_eq!(
+            ws.status(&subscription),
+            Some(SubscriptionStatus::Unsubscribed)
+        );
+        while server.subscriptions().contains(&subscription) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mock_typed_stream_ends_on_close() {
+        let server = MockServer::start().await.unwrap();
+        let ws = server.websocket();
+        let mut trades = ws.trades("BTC");
+        tokio::time::timeout(Duration::from_secs(5), trades.wait())
+            .await
+            .unwrap()
+            .unwrap();
+
+        // open streams don't keep the connection alive
+        ws.close();
+        let next = tokio::time::timeout(Duration::from_secs(5), trades.next())
+            .await
+            .unwrap();
+        assert!(next.is_none());
+        while server.connections() > 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mock_typed_and_explicit_subscriptions() {
+        fn trade(tid: u64) -> Incoming {
+            Incoming::Trades(vec![Trade {
+                coin: "ETH".into(),
+                side: Side::Ask,
+                px: dec!(3000),
+                sz: dec!(1),
+                time: tid,
+                hash: format!("0x{tid:x}"),
+                tid,
+                users: [Address::ZERO, Address::ZERO],
+                liquidation: None,
+            }])
+        }
+
+        async fn next_tid(stream: &mut ChannelStream<Vec<Trade>>) -> u64 {
+            tokio::time::timeout(Duration::from_secs(5), stream.next())
+                .await
+                .unwrap()
+                .unwrap()[0]
+                .tid
+        }
+
+        let server = MockServer::start().await.unwrap();
+        let mut ws = server.websocket();
+        let subscription = Subscription::Trades { coin: "ETH".into() };
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            ws.subscribe(subscription.clone()).wait(),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        let mut trades = ws.trades("ETH");
+
+        // delivered to the typed stream and the connection stream
+        server.broadcast(&trade(1));
+        assert_eq!(next_tid(&mut trades).await, 1);
+        assert!(matches!(
+            next_message(&mut ws).await,
+            Incoming::Trades(trades) if trades[0].tid == 1
+        ));
+
+        // the typed stream keeps the channel subscribed
+        ws.unsubscribe(subscription.clone());
+        assert!(ws.status(&subscription).unwrap().is_active());
+        server.broadcast(&trade(2));
+        assert_eq!(next_tid(&mut trades).await, 2);
+        drop(trades);
+        assert_eq!(
+            ws.status(&subscription),
+            Some(SubscriptionStatus::Unsubscribed)
+        );
+
+        // and dropping a typed stream keeps an explicit subscription
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            ws.subscribe(subscription.clone()).wait(),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        drop(ws.trades("ETH"));
+        let _: Value = ws
+            .info(json!({ "type": "allMids" }), Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(ws.status(&subscription).unwrap().is_active());
+        assert!(server.subscriptions().contains(&subscription));
+        server.broadcast(&trade(3));
+        assert!(matches!(
+            next_message(&mut ws).await,
+            Incoming::Trades(trades) if trades[0].tid == 3
+        ));
+    }
+}
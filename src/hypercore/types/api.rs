@@ -10,15 +10,16 @@ use alloy::{
     signers::{Signer, SignerSync, k256::ecdsa::RecoveryId},
 };
 use chrono::{DateTime, Utc};
-use rust_decimal::Decimal;
+use rust_decimal::{Decimal, prelude::ToPrimitive};
 use serde::{Deserialize, Serialize};
 
 use super::solidity;
 use crate::hypercore::{
-    Chain,
+    Chain, Error, MarginMode, PerpMarket,
     types::{
         BatchCancel, BatchCancelCloid, BatchModify, BatchOrder, CORE_MAINNET_EIP712_DOMAIN,
-        OrderResponseStatus, ScheduleCancel, Signature,
+        OrderResponseStatus, ScheduleCancel, Signature, TwapCancel, TwapOrder,
+        TwapOrderResponseStatus,
     },
     utils::{self, get_typed_data},
 };
@@ -26,7 +27,7 @@ use crate::hypercore::{
 /// Request for an action.
 ///
 /// Contains the action, a nonce, signature, optional vault address, and optional expiry.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ActionRequest {
     /// Action.
@@ -74,6 +75,10 @@ pub enum Action {
     CancelByCloid(BatchCancelCloid),
     /// Schedule cancellation of all orders.
     ScheduleCancel(ScheduleCancel),
+    /// TWAP order insertion.
+    TwapOrder(TwapOrder),
+    /// TWAP order cancellation.
+    TwapCancel(TwapCancel),
     /// Core USDC transfer.
     UsdSend(UsdSendAction),
     /// Send asset.
@@ -85,10 +90,14 @@ pub enum Action {
         using_big_blocks: bool,
     },
     ApproveAgent(ApproveAgent),
+    /// Approve a builder fee.
+    ApproveBuilderFee(ApproveBuilderFee),
     /// Convert to multi-signature user.
     ConvertToMultiSigUser(ConvertToMultiSigUser),
     /// Update isolated margin.
     UpdateIsolatedMargin(UpdateIsolatedMargin),
+    /// Update leverage and margin mode.
+    UpdateLeverage(UpdateLeverage),
     /// Deposit or withdraw from a vault.
     VaultTransfer(VaultTransfer),
     /// Multi-sig action.
@@ -158,13 +167,15 @@ pub enum Response {
 
 /// Successful API response data.
 ///
-/// Currently supports order responses and a default placeholder.
+/// Currently supports order, TWAP responses and a default placeholder.
 #[derive(Debug, Deserialize)]
 #[serde(tag = "type", content = "data")]
 #[serde(rename_all = "camelCase")]
 pub enum OkResponse {
     Order { statuses: Vec<OrderResponseStatus> },
     Cancel { statuses: Vec<OrderResponseStatus> },
+    TwapOrder { status: TwapOrderResponseStatus },
+    TwapCancel { status: OrderResponseStatus },
     // should be ok?
     Default,
 }
@@ -192,8 +203,11 @@ impl Action {
             | Action::Cancel(_)
             | Action::CancelByCloid(_)
             | Action::ScheduleCancel(_)
+            | Action::TwapOrder(_)
+            | Action::TwapCancel(_)
             | Action::EvmUserModify { .. }
             | Action::UpdateIsolatedMargin(_)
+            | Action::UpdateLeverage(_)
             | Action::VaultTransfer(_)
             | Action::Noop => {
                 let connection_id = self.hash(nonce, maybe_vault_address, expires_after)?;
@@ -220,6 +234,10 @@ impl Action {
                 let typed_data = get_typed_data::<solidity::ApproveAgent>(&inner, chain, None);
                 signer.sign_dynamic_typed_data_sync(&typed_data)?
             }
+            Action::ApproveBuilderFee(inner) => {
+                let typed_data = get_typed_data::<solidity::ApproveBuilderFee>(&inner, chain, None);
+                signer.sign_dynamic_typed_data_sync(&typed_data)?
+            }
             Action::ConvertToMultiSigUser(inner) => {
                 let typed_data =
                     get_typed_data::<solidity::ConvertToMultiSigUser>(&inner, chain, None);
@@ -283,8 +301,11 @@ impl Action {
             | Action::Cancel(_)
             | Action::CancelByCloid(_)
             | Action::ScheduleCancel(_)
+            | Action::TwapOrder(_)
+            | Action::TwapCancel(_)
             | Action::EvmUserModify { .. }
             | Action::UpdateIsolatedMargin(_)
+            | Action::UpdateLeverage(_)
             | Action::VaultTransfer(_)
             | Action::Noop => {
                 let connection_id = self.hash(nonce, maybe_vault_address, expires_after)?;
@@ -313,6 +334,10 @@ impl Action {
                 let typed_data = get_typed_data::<solidity::ApproveAgent>(&inner, chain, None);
                 signer.sign_dynamic_typed_data(&typed_data).await?
             }
+            Action::ApproveBuilderFee(inner) => {
+                let typed_data = get_typed_data::<solidity::ApproveBuilderFee>(&inner, chain, None);
+                signer.sign_dynamic_typed_data(&typed_data).await?
+            }
             Action::ConvertToMultiSigUser(inner) => {
                 let typed_data =
                     get_typed_data::<solidity::ConvertToMultiSigUser>(&inner, chain, None);
@@ -372,8 +397,11 @@ impl Action {
             | Action::Cancel(_)
             | Action::CancelByCloid(_)
             | Action::ScheduleCancel(_)
+            | Action::TwapOrder(_)
+            | Action::TwapCancel(_)
             | Action::EvmUserModify { .. }
             | Action::UpdateIsolatedMargin(_)
+            | Action::UpdateLeverage(_)
             | Action::VaultTransfer(_)
             | Action::Noop => {
                 let expires_after =
@@ -403,6 +431,10 @@ impl Action {
                 let typed_data = get_typed_data::<solidity::ApproveAgent>(&inner, chain, None);
                 Ok(typed_data.eip712_signing_hash()?)
             }
+            Action::ApproveBuilderFee(inner) => {
+                let typed_data = get_typed_data::<solidity::ApproveBuilderFee>(&inner, chain, None);
+                Ok(typed_data.eip712_signing_hash()?)
+            }
             Action::ConvertToMultiSigUser(inner) => {
                 let typed_data =
                     get_typed_data::<solidity::ConvertToMultiSigUser>(&inner, chain, None);
@@ -621,6 +653,33 @@ pub struct ApproveAgent {
     pub nonce: u64,
 }
 
+/// Approve a builder fee
+///
+/// Allows `builder` to charge fees up to `max_fee_rate` on the orders of the
+/// signer, see [`Builder`](crate::hypercore::types::Builder).
+///
+/// <https://hyperliquid.gitbook.io/hyperliquid-docs/for-developers/api/exchange-endpoint#approve-a-builder-fee>
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ApproveBuilderFee {
+    /// Signature chain ID.
+    ///
+    /// For arbitrum use [`crate::hypercore::ARBITRUM_MAINNET_CHAIN_ID`] or [`crate::hypercore::ARBITRUM_TESTNET_CHAIN_ID`].
+    pub signature_chain_id: String,
+    /// The chain this action is being executed on.
+    pub hyperliquid_chain: Chain,
+    /// Maximum fee rate as a percentage (e.g. "0.01%" for 1 basis point).
+    pub max_fee_rate: String,
+    /// The builder address.
+    #[serde(
+        serialize_with = "crate::hypercore::utils::serialize_address_as_hex",
+        deserialize_with = "crate::hypercore::utils::deserialize_address_from_hex"
+    )]
+    pub builder: Address,
+    /// Request nonce
+    pub nonce: u64,
+}
+
 /// Multisig configuration for converting an account to multisig.
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[serde(rename_all = "camelCase")]
@@ -655,15 +714,71 @@ pub struct ConvertToMultiSigUser {
 /// Request to update isolated margin for a position.
 ///
 /// Allows adding or removing margin from an isolated-margin position.
+///
+/// <https://hyperliquid.gitbook.io/hyperliquid-docs/for-developers/api/exchange-endpoint#update-isolated-margin>
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdateIsolatedMargin {
     /// Asset index of the position.
     pub asset: usize,
     /// `true` for a long position, `false` for a short position.
+    ///
+    /// Has no effect until hedge mode is introduced.
     pub is_buy: bool,
-    /// Margin delta in USD (scaled integer representation).
-    pub ntli: u64,
+    /// Margin delta in USD micro-units (1 USD = 1,000,000).
+    ///
+    /// Positive to add margin, negative to remove it.
+    pub ntli: i64,
+}
+
+impl UpdateIsolatedMargin {
+    /// Creates a margin update for `market` from a USD amount.
+    ///
+    /// A positive `usd` adds margin to the position, a negative one removes it.
+    /// The amount is truncated to micro-units (1 USD = 1,000,000).
+    ///
+    /// Fails with [`Error::InvalidParameter`] if the amount is zero after conversion,
+    /// out of range, or if margin is removed from a market in
+    /// [`MarginMode::StrictIsolated`] mode.
+    pub fn from_usd(market: &PerpMarket, usd: Decimal) -> Result<Self, Error> {
+        let invalid = |message: String| Error::InvalidParameter(message);
+        let ntli = (usd * Decimal::from(1_000_000))
+            .trunc()
+            .to_i64()
+            .ok_or_else(|| invalid(format!("usd amount out of range: {usd}")))?;
+        if ntli == 0 {
+            return Err(invalid(format!("usd amount must be non-zero: {usd}")));
+        }
+
+        if ntli < 0 && matches!(market.margin_mode, Some(MarginMode::StrictIsolated)) {
+            return Err(invalid(format!(
+                "{} is strictly isolated, margin cannot be removed",
+                market.name
+            )));
+        }
+
+        Ok(Self {
+            asset: market.index,
+            is_buy: true,
+            ntli,
+        })
+    }
+}
+
+/// Request to update the leverage of an asset.
+///
+/// Also switches the margin mode of the asset between cross and isolated.
+///
+/// <https://hyperliquid.gitbook.io/hyperliquid-docs/for-developers/api/exchange-endpoint#update-leverage>
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateLeverage {
+    /// Asset index.
+    pub asset: usize,
+    /// `true` for cross margin, `false` for isolated margin.
+    pub is_cross: bool,
+    /// New leverage (e.g. `10` for 10x).
+    pub leverage: u32,
 }
 
 /// Deposit or withdraw USDC from a vault.
@@ -858,6 +973,64 @@ mod tests {
         let _data: Response = serde_json::from_str(text).unwrap();
     }
 
+    #[test]
+    fn twap_order_serialization() {
+        use rust_decimal::dec;
+
+        use crate::hypercore::types::TwapRequest;
+
+        let action = Action::TwapOrder(TwapOrder {
+            twap: TwapRequest {
+                asset: 1,
+                is_buy: false,
+                sz: dec!(2.50),
+                reduce_only: true,
+                minutes: 15,
+                randomize: false,
+            },
+        });
+
+        let json = serde_json::to_string(&action).unwrap();
+        assert_eq!(
+            json,
+            r#"{"type":"twapOrder","twap":{"a":1,"b":false,"s":"2.5","r":true,"m":15,"t":false}}"#
+        );
+
+        let action = Action::TwapCancel(TwapCancel {
+            asset: 1,
+            twap_id: 77738308,
+        });
+        let json = serde_json::to_string(&action).unwrap();
+        assert_eq!(json, r#"{"type":"twapCancel","a":1,"t":77738308}"#);
+    }
+
+    #[test]
+    fn twap_response_deser() {
+        let text = r#"{"status":"ok","response":{"type":"twapOrder","data":{"status":{"running":{"twapId":77738308}}}}}"#;
+        let resp: Response = serde_json::from_str(text).unwrap();
+        let Response::Ok(OkResponse::TwapOrder { status }) = resp else {
+            panic!("unexpected response: {resp:?}");
+        };
+        assert_eq!(status.twap_id(), Some(77738308));
+
+        let text = r#"{"status":"ok","response":{"type":"twapOrder","data":{"status":{"error":"Invalid TWAP duration: 1 min(s)"}}}}"#;
+        let resp: Response = serde_json::from_str(text).unwrap();
+        let Response::Ok(OkResponse::TwapOrder { status }) = resp else {
+            panic!("unexpected response: {resp:?}");
+        };
+        assert_eq!(status.error(), Some("Invalid TWAP duration: 1 min(s)"));
+
+        let text =
+            r#"{"status":"ok","response":{"type":"twapCancel","data":{"status":"success"}}}"#;
+        let resp: Response = serde_json::from_str(text).unwrap();
+        assert!(matches!(
+            resp,
+            Response::Ok(OkResponse::TwapCancel {
+                status: OrderResponseStatus::Success
+            })
+        ));
+    }
+
     #[test]
     fn update_isolated_margin() {
         let text = r#"{"action":{"type":"updateIsolatedMargin","asset":173,"isBuy":true,"ntli":2000000},"nonce":1768223623573,"signature":{"r":"0xf85df30c97a4f2cd6b463b5f385d1f93e029791ffc9bb49fdcad2616608350e2","s":"0x3763da7c7ef7a4d7a528815bddff75b854d540487dfb1f1c75e7201f57c2ea6e","v":28}}"#;
@@ -869,6 +1042,100 @@ mod tests {
         );
     }
 
+    fn perp_market(margin_mode: Option<MarginMode>) -> PerpMarket {
+        PerpMarket {
+            name: "BTC".into(),
+            index: 3,
+            sz_decimals: 5,
+            collateral: crate::hypercore::SpotToken {
+                name: "USDC".into(),
+                index: 0,
+                token_id: Default::default(),
+                evm_contract: None,
+                cross_chain_address: None,
+                sz_decimals: 8,
+                wei_decimals: 8,
+                evm_extra_decimals: 0,
+            },
+            max_leverage: 40,
+            isolated_margin: margin_mode.is_some(),
+            margin_mode,
+            growth_mode: false,
+            aligned_quote_token: false,
+            table: crate::hypercore::PriceTick::for_perp(5),
+        }
+    }
+
+    #[test]
+    fn update_isolated_margin_from_usd() {
+        use rust_decimal::dec;
+
+        let market = perp_market(None);
+        let add = UpdateIsolatedMargin::from_usd(&market, dec!(2.5)).unwrap();
+        assert_eq!(add.asset, 3);
+        assert_eq!(add.ntli, 2_500_000);
+
+        let remove = UpdateIsolatedMargin::from_usd(&market, dec!(-1.0000019)).unwrap();
+        assert_eq!(remove.ntli, -1_000_001);
+
+        let json = serde_json::to_string(&Action::UpdateIsolatedMargin(remove)).unwrap();
+        assert_eq!(
+            json,
+            r#"{"type":"updateIsolatedMargin","asset":3,"isBuy":true,"ntli":-1000001}"#
+        );
+
+        assert!(UpdateIsolatedMargin::from_usd(&market, dec!(0.0000001)).is_err());
+
+        let strict = perp_market(Some(MarginMode::StrictIsolated));
+        assert!(UpdateIsolatedMargin::from_usd(&strict, dec!(10)).is_ok());
+        assert!(matches!(
+            UpdateIsolatedMargin::from_usd(&strict, dec!(-10)),
+            Err(Error::InvalidParameter(_))
+        ));
+
+        let no_cross = perp_market(Some(MarginMode::NoCross));
+        assert!(UpdateIsolatedMargin::from_usd(&no_cross, dec!(-10)).is_ok());
+    }
+
+    #[test]
+    fn update_leverage_serialization() {
+        let action = Action::UpdateLeverage(UpdateLeverage {
+            asset: 3,
+            is_cross: false,
+            leverage: 10,
+        });
+
+        let json = serde_json::to_string(&action).unwrap();
+        assert_eq!(
+            json,
+            r#"{"type":"updateLeverage","asset":3,"isCross":false,"leverage":10}"#
+        );
+
+        let deserialized: Action = serde_json::from_str(&json).unwrap();
+        let Action::UpdateLeverage(update) = deserialized else {
+            panic!("wrong variant");
+        };
+        assert!(!update.is_cross);
+        assert_eq!(update.leverage, 10);
+    }
+
+    #[test]
+    fn update_leverage_sign_recover() {
+        use alloy::signers::local::PrivateKeySigner;
+
+        let signer = PrivateKeySigner::random();
+        let action = Action::UpdateLeverage(UpdateLeverage {
+            asset: 0,
+            is_cross: true,
+            leverage: 20,
+        });
+
+        let req = action
+            .sign_sync(&signer, 1_700_000_000_000, None, None, Chain::Mainnet)
+            .unwrap();
+        assert_eq!(req.recover(Chain::Mainnet).unwrap(), signer.address());
+    }
+
     #[test]
     fn vault_transfer_serialization() {
         use alloy::primitives::address;
@@ -82,7 +82,7 @@ use rust_decimal::Decimal;
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_with::{DisplayFromStr, serde_as};
 
-use crate::hypercore::{Chain, Cloid, OidOrCloid, SpotToken};
+use crate::hypercore::{Chain, Cloid, Error, OidOrCloid, PriceTick, SpotToken, error::Result};
 
 pub mod api;
 pub(super) mod solidity;
@@ -236,12 +236,47 @@ pub enum Side {
 #[serde(tag = "method")]
 #[serde(rename_all = "camelCase")]
 pub enum Outgoing {
-    Subscribe { subscription: Subscription },
-    Unsubscribe { subscription: Subscription },
+    Subscribe {
+        subscription: Subscription,
+    },
+    Unsubscribe {
+        subscription: Subscription,
+    },
+    /// Info or action request sent over the websocket.
+    ///
+    /// The response is an [`Incoming::Post`] with the same `id`.
+    Post {
+        id: u64,
+        request: PostRequest,
+    },
     Ping,
     Pong,
 }
 
+/// Payload of a websocket post request.
+///
+/// <https://hyperliquid.gitbook.io/hyperliquid-docs/for-developers/api/websocket/post-requests>
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "payload", rename_all = "camelCase")]
+pub enum PostRequest {
+    /// Info request, same body as the `/info` endpoint.
+    Info(serde_json::Value),
+    /// Signed action, same body as the `/exchange` endpoint.
+    Action(Box<ActionRequest>),
+}
+
+/// Payload of a websocket post response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "payload", rename_all = "camelCase")]
+pub enum PostResponse {
+    /// Info response, the result is in the `data` field.
+    Info(serde_json::Value),
+    /// Action response, same body as the `/exchange` endpoint response.
+    Action(serde_json::Value),
+    /// The request couldn't be processed.
+    Error(String),
+}
+
 /// WebSocket subscription request.
 ///
 /// Each variant corresponds to a subscription type that can be requested from the WebSocket API.
@@ -268,6 +303,10 @@ pub enum Outgoing {
 /// | [`UserTwapHistory`](Self::UserTwapHistory) | [`Incoming::UserTwapHistory`] | TWAP lifecycle history updates |
 /// | [`ActiveAssetData`](Self::ActiveAssetData) | [`Incoming::ActiveAssetData`] | User leverage and trading limits for a perp asset |
 /// | [`WebData2`](Self::WebData2) | [`Incoming::WebData2`] | Frontend-style aggregate account snapshot |
+/// | [`WebData3`](Self::WebData3) | [`Incoming::WebData3`] | Aggregate account state across perp dexes |
+/// | [`Notification`](Self::Notification) | [`Incoming::Notification`] | Frontend notifications |
+/// | [`UserFundings`](Self::UserFundings) | [`Incoming::UserFundings`] | Funding payments |
+/// | [`UserNonFundingLedgerUpdates`](Self::UserNonFundingLedgerUpdates) | [`Incoming::UserNonFundingLedgerUpdates`] | Deposits, withdrawals, transfers and vault moves |
 ///
 /// # Related Types
 ///
@@ -289,7 +328,7 @@ pub enum Outgoing {
 /// ws.subscribe(Subscription::Trades { coin: "ETH".into() });
 /// ws.subscribe(Subscription::Candle {
 ///     coin: "BTC".into(),
-///     interval: "15m".into()
+///     interval: CandleInterval::FifteenMinutes,
 /// });
 ///
 /// // Subscribe to user events
@@ -308,11 +347,23 @@ pub enum Subscription {
     #[display("trades({coin})")]
     Trades { coin: String },
     /// Order book snapshots and updates
+    ///
+    /// `n_sig_figs` and `mantissa` request server-side aggregation of price levels,
+    /// see [`BookAggregation`]. Leave both unset for full precision.
     #[display("l2Book({coin})")]
-    L2Book { coin: String },
+    L2Book {
+        coin: String,
+        #[serde(rename = "nSigFigs", default, skip_serializing_if = "Option::is_none")]
+        n_sig_figs: Option<u32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mantissa: Option<u32>,
+    },
     /// Real-time candlestick updates
     #[display("candle({coin}@{interval})")]
-    Candle { coin: String, interval: String },
+    Candle {
+        coin: String,
+        interval: CandleInterval,
+    },
     /// Mid prices for all markets
     #[display("allMids({dex:?})")]
     AllMids {
@@ -335,6 +386,9 @@ pub enum Subscription {
     #[display("userTwapHistory({user})")]
     UserTwapHistory { user: Address },
     /// Real-time asset context (funding rate, mark price, open interest)
+    ///
+    /// Perp coins deliver [`Incoming::ActiveAssetCtx`], spot coins (e.g. `@107`)
+    /// deliver [`Incoming::ActiveSpotAssetCtx`].
     #[display("activeAssetCtx({coin})")]
     ActiveAssetCtx { coin: String },
     /// User-specific asset limits and leverage information (perps only)
@@ -347,6 +401,43 @@ pub enum Subscription {
         #[serde(skip_serializing_if = "Option::is_none")]
         dex: Option<String>,
     },
+    /// Aggregate user state across all perp dexes
+    #[display("webData3({user})")]
+    WebData3 { user: Address },
+    /// Frontend notifications for user
+    #[display("notification({user})")]
+    Notification { user: Address },
+    /// Funding payments for user
+    #[display("userFundings({user})")]
+    UserFundings { user: Address },
+    /// Deposits, withdrawals, transfers and vault moves for user
+    #[display("userNonFundingLedgerUpdates({user})")]
+    UserNonFundingLedgerUpdates { user: Address },
+}
+
+impl Subscription {
+    /// Full precision order book subscription for `coin`.
+    #[must_use]
+    pub fn l2_book(coin: impl Into<String>) -> Self {
+        Subscription::L2Book {
+            coin: coin.into(),
+            n_sig_figs: None,
+            mantissa: None,
+        }
+    }
+
+    /// Aggregated order book subscription for `coin`.
+    ///
+    /// Validate the aggregation with [`BookAggregation::validate`] first to make sure
+    /// it is supported by the market.
+    #[must_use]
+    pub fn l2_book_aggregated(coin: impl Into<String>, aggregation: BookAggregation) -> Self {
+        Subscription::L2Book {
+            coin: coin.into(),
+            n_sig_figs: Some(aggregation.n_sig_figs),
+            mantissa: aggregation.mantissa,
+        }
+    }
 }
 
 /// Hyperliquid websocket message.
@@ -369,6 +460,13 @@ pub enum Subscription {
 /// - **UserTwapHistory**: TWAP status history updates for a user
 /// - **ActiveAssetData**: User leverage and limits for a specific perp asset
 /// - **WebData2**: Frontend-style aggregate user snapshot
+/// - **WebData3**: Aggregate user state across perp dexes
+/// - **ActiveSpotAssetCtx**: Asset context of a spot market
+/// - **Notification**: Frontend notification for a user
+/// - **UserFundings**: Funding payments for a user
+/// - **UserNonFundingLedgerUpdates**: Deposits, withdrawals, transfers and vault moves for a user
+/// - **Post**: Response to a post request
+/// - **Error**: Error reported by the server, e.g. a rejected subscription
 /// - **Ping/Pong**: Heartbeat messages
 ///
 /// # Example
@@ -437,20 +535,74 @@ pub enum Incoming {
     UserTwapHistory(UserTwapHistory),
     /// Real-time asset context update (funding rate, mark price, etc.)
     ActiveAssetCtx { coin: String, ctx: AssetContext },
+    /// Real-time asset context update of a spot market
+    ActiveSpotAssetCtx { coin: String, ctx: SpotAssetContext },
     /// Real-time user asset limits/leverage for a perp asset
     ActiveAssetData(ActiveAssetData),
-    /// Frontend aggregate user snapshot (dynamic schema)
-    WebData2 {
-        dex: Option<String>,
-        #[serde(flatten)]
-        data: serde_json::Value,
+    /// Frontend aggregate user snapshot
+    WebData2(Box<WebData2>),
+    /// Aggregate user state across perp dexes
+    WebData3(WebData3),
+    /// Frontend notification for a user
+    Notification(Notification),
+    /// Funding payments for a user
+    #[serde(rename_all = "camelCase")]
+    UserFundings {
+        #[serde(default)]
+        is_snapshot: bool,
+        user: Address,
+        fundings: Vec<UserFunding>,
+    },
+    /// Deposits, withdrawals, transfers and vault moves for a user
+    #[serde(rename_all = "camelCase")]
+    UserNonFundingLedgerUpdates {
+        #[serde(default)]
+        is_snapshot: bool,
+        user: Address,
+        non_funding_ledger_updates: Vec<LedgerUpdate>,
     },
+    /// Response to an [`Outgoing::Post`] request
+    Post { id: u64, response: PostResponse },
+    /// Error reported by the server, e.g. an invalid subscription
+    Error(String),
     /// Server heartbeat ping
     Ping,
     /// Server heartbeat pong
     Pong,
 }
 
+impl Incoming {
+    /// Returns the name of the channel the message was received on.
+    #[must_use]
+    pub fn channel(&self) -> &'static str {
+        match self {
+            Incoming::SubscriptionResponse(_) => "subscriptionResponse",
+            Incoming::Bbo(_) => "bbo",
+            Incoming::L2Book(_) => "l2Book",
+            Incoming::Candle(_) => "candle",
+            Incoming::AllMids { .. } => "allMids",
+            Incoming::Trades(_) => "trades",
+            Incoming::OrderUpdates(_) => "orderUpdates",
+            Incoming::UserFills { .. } => "userFills",
+            Incoming::UserEvents(_) => "userEvents",
+            Incoming::UserTwapSliceFills(_) => "userTwapSliceFills",
+            Incoming::UserTwapHistory(_) => "userTwapHistory",
+            Incoming::ActiveAssetCtx { .. } => "activeAssetCtx",
+            Incoming::ActiveSpotAssetCtx { .. } => "activeSpotAssetCtx",
+            Incoming::ActiveAssetData(_) => "activeAssetData",
+            Incoming::WebData2(_) => "webData2",
+            Incoming::WebData3(_) => "webData3",
+            Incoming::Notification(_) => "notification",
+            Incoming::UserFundings { .. } => "userFundings",
+            Incoming::UserNonFundingLedgerUpdates { .. } => "userNonFundingLedgerUpdates",
+            Incoming::Post { .. } => "post",
+            Incoming::Error(_) => "error",
+            Incoming::Ping => "ping",
+            Incoming::Pong => "pong",
+        }
+    }
+}
+
 /// WebSocket order update.
 ///
 /// Contains status, timestamp, and the original order details.
@@ -995,6 +1147,123 @@ impl L2Book {
     }
 }
 
+/// Server-side L2 book aggregation.
+///
+/// Hyperliquid can aggregate book levels to a number of significant figures
+/// (`nSigFigs`, 2 to 5). With 5 significant figures, the levels can be further
+/// grouped by a `mantissa` of 1, 2 or 5.
+///
+/// The aggregation step must not be finer than the market's price tick, use
+/// [`validate`](Self::validate) to check it against a [`PriceTick`].
+///
+/// # Example
+///
+/// ```
+/// use hypersdk::hypercore::{PriceTick, types::BookAggregation};
+/// use rust_decimal::dec;
+///
+/// let aggregation = BookAggregation::sig_figs(5).with_mantissa(2);
+/// let step = aggregation.validate(&PriceTick::for_perp(5), dec!(93231)).unwrap();
+/// assert_eq!(step, dec!(2));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BookAggregation {
+    /// Number of significant figures (2 to 5)
+    pub n_sig_figs: u32,
+    /// Grouping of the last significant figure (1, 2 or 5), only with 5 significant figures
+    pub mantissa: Option<u32>,
+}
+
+impl BookAggregation {
+    /// Aggregates levels to `n_sig_figs` significant figures.
+    #[must_use]
+    pub fn sig_figs(n_sig_figs: u32) -> Self {
+        Self {
+            n_sig_figs,
+            mantissa: None,
+        }
+    }
+
+    /// Sets the mantissa.
+    #[must_use]
+    pub fn with_mantissa(mut self, mantissa: u32) -> Self {
+        self.mantissa = Some(mantissa);
+        self
+    }
+
+    /// Checks that the parameters are accepted by the exchange.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidParameter`] describing the first invalid parameter.
+    pub fn check(&self) -> Result<()> {
+        let invalid = |message: String| Err(Error::InvalidParameter(message));
+        if !(2..=5).contains(&self.n_sig_figs) {
+            return invalid(format!(
+                "nSigFigs must be between 2 and 5, got {}",
+                self.n_sig_figs
+            ));
+        }
+        match self.mantissa {
+            Some(_) if self.n_sig_figs != 5 => {
+                invalid("mantissa is only allowed with 5 significant figures".into())
+            }
+            Some(mantissa) if !matches!(mantissa, 1 | 2 | 5) => {
+                invalid(format!("mantissa must be 1, 2 or 5, got {mantissa}"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the price step between aggregated levels around `price`.
+    ///
+    /// Returns `None` if `price` is not positive.
+    #[must_use]
+    pub fn step(&self, price: Decimal) -> Option<Decimal> {
+        if price <= Decimal::ZERO {
+            return None;
+        }
+
+        // largest power of ten not above the price
+        let mut magnitude = Decimal::ONE;
+        while magnitude * Decimal::TEN <= price {
+            magnitude *= Decimal::TEN;
+        }
+        while magnitude > price {
+            magnitude /= Decimal::TEN;
+        }
+
+        let mut step = magnitude;
+        for _ in 1..self.n_sig_figs {
+            step /= Decimal::TEN;
+        }
+        Some(step * Decimal::from(self.mantissa.unwrap_or(1)))
+    }
+
+    /// Validates the aggregation against a market's price tick around `price`.
+    ///
+    /// Returns the aggregation step, or an error if the parameters are invalid
+    /// or the step is finer than the market's tick.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidParameter`] in both cases.
+    pub fn validate(&self, tick: &PriceTick, price: Decimal) -> Result<Decimal> {
+        self.check()?;
+        let invalid_price = || Error::InvalidParameter(format!("invalid reference price {price}"));
+        let step = self.step(price).ok_or_else(invalid_price)?;
+        let tick = tick.tick_for(price).ok_or_else(invalid_price)?;
+        if step < tick {
+            return Err(Error::InvalidParameter(format!(
+                "aggregation step {} is finer than the price tick {} at {price}",
+                step.normalize(),
+                tick.normalize()
+            )));
+        }
+        Ok(step.normalize())
+    }
+}
+
 /// WebSocket fill.
 ///
 /// Describes a filled order for a user. Contains execution details and position impact.
@@ -1148,11 +1417,17 @@ pub enum UserEvent {
     Unknown(serde_json::Value),
 }
 
-/// Funding payment event in `userEvents`.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Funding payment of a user.
+///
+/// Delivered by `userEvents` and `userFundings`, and returned by
+/// [`Client::user_funding`](crate::hypercore::HttpClient::user_funding).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UserFunding {
     pub time: u64,
+    /// Transaction hash, only known for payments returned by the info endpoint
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
     pub coin: String,
     #[serde(deserialize_with = "deserialize_decimal_from_any")]
     pub usdc: Decimal,
@@ -1160,6 +1435,9 @@ pub struct UserFunding {
     pub szi: Decimal,
     #[serde(deserialize_with = "deserialize_decimal_from_any")]
     pub funding_rate: Decimal,
+    /// Number of samples the funding rate was computed from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub n_samples: Option<u64>,
 }
 
 /// Liquidation event in `userEvents`.
@@ -1183,6 +1461,414 @@ pub struct NonUserCancel {
     pub oid: u64,
 }
 
+/// Frontend notification of the `notification` channel.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Notification {
+    /// Notification text
+    pub notification: String,
+}
+
+/// Non-funding change of a user's balances.
+///
+/// Delivered by the `userNonFundingLedgerUpdates` channel and returned by
+/// [`Client::user_non_funding_ledger_updates`](crate::hypercore::HttpClient::user_non_funding_ledger_updates).
+/// Funding payments are delivered separately, see [`UserFunding`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LedgerUpdate {
+    /// Time of the update in milliseconds
+    pub time: u64,
+    /// Transaction hash
+    pub hash: String,
+    /// What changed
+    pub delta: LedgerDelta,
+}
+
+/// Balance change of a [`LedgerUpdate`].
+///
+/// Amounts are in USDC unless stated otherwise. Update types this SDK doesn't
+/// know yet deserialize to [`LedgerDelta::Unknown`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum LedgerDelta {
+    /// Deposit from the bridge
+    Deposit {
+        #[serde(deserialize_with = "deserialize_decimal_from_any")]
+        usdc: Decimal,
+    },
+    /// Withdrawal to the bridge
+    Withdraw {
+        #[serde(deserialize_with = "deserialize_decimal_from_any")]
+        usdc: Decimal,
+        nonce: u64,
+        #[serde(deserialize_with = "deserialize_decimal_from_any")]
+        fee: Decimal,
+    },
+    /// USDC transfer between perp accounts
+    InternalTransfer {
+        #[serde(deserialize_with = "deserialize_decimal_from_any")]
+        usdc: Decimal,
+        user: Address,
+        destination: Address,
+        #[serde(deserialize_with = "deserialize_decimal_from_any")]
+        fee: Decimal,
+    },
+    /// USDC transfer between a master account and a sub-account
+    SubAccountTransfer {
+        #[serde(deserialize_with = "deserialize_decimal_from_any")]
+        usdc: Decimal,
+        user: Address,
+        destination: Address,
+    },
+    /// Liquidation of the account
+    #[serde(rename_all = "camelCase")]
+    LedgerLiquidation {
+        #[serde(deserialize_with = "deserialize_decimal_from_any")]
+        account_value: Decimal,
+        leverage_type: String,
+        liquidated_positions: Vec<LiquidatedPosition>,
+    },
+    /// Creation of a vault
+    VaultCreate {
+        vault: Address,
+        #[serde(deserialize_with = "deserialize_decimal_from_any")]
+        usdc: Decimal,
+        #[serde(with = "rust_decimal::serde::str_option", default)]
+        fee: Option<Decimal>,
+    },
+    /// Deposit into a vault
+    VaultDeposit {
+        vault: Address,
+        #[serde(deserialize_with = "deserialize_decimal_from_any")]
+        usdc: Decimal,
+    },
+    /// Distribution from a vault
+    VaultDistribution {
+        vault: Address,
+        #[serde(deserialize_with = "deserialize_decimal_from_any")]
+        usdc: Decimal,
+    },
+    /// Withdrawal from a vault
+    #[serde(rename_all = "camelCase")]
+    VaultWithdraw {
+        vault: Address,
+        user: Address,
+        #[serde(deserialize_with = "deserialize_decimal_from_any")]
+        requested_usd: Decimal,
+        #[serde(deserialize_with = "deserialize_decimal_from_any")]
+        commission: Decimal,
+        #[serde(deserialize_with = "deserialize_decimal_from_any")]
+        closing_cost: Decimal,
+        #[serde(deserialize_with = "deserialize_decimal_from_any")]
+        basis: Decimal,
+        #[serde(deserialize_with = "deserialize_decimal_from_any")]
+        net_withdrawn_usd: Decimal,
+    },
+    /// Commission paid to a vault leader
+    VaultLeaderCommission {
+        user: Address,
+        #[serde(deserialize_with = "deserialize_decimal_from_any")]
+        usdc: Decimal,
+    },
+    /// USDC transfer between the spot and perp balances
+    #[serde(rename_all = "camelCase")]
+    AccountClassTransfer {
+        #[serde(deserialize_with = "deserialize_decimal_from_any")]
+        usdc: Decimal,
+        to_perp: bool,
+    },
+    /// Spot token transfer; `amount` and `fee` are in `token`
+    #[serde(rename_all = "camelCase")]
+    SpotTransfer {
+        token: String,
+        #[serde(deserialize_with = "deserialize_decimal_from_any")]
+        amount: Decimal,
+        #[serde(deserialize_with = "deserialize_decimal_from_any")]
+        usdc_value: Decimal,
+        user: Address,
+        destination: Address,
+        #[serde(deserialize_with = "deserialize_decimal_from_any")]
+        fee: Decimal,
+        #[serde(with = "rust_decimal::serde::str_option", default)]
+        native_token_fee: Option<Decimal>,
+        #[serde(default)]
+        nonce: Option<u64>,
+    },
+    /// Spot tokens received at genesis
+    SpotGenesis {
+        token: String,
+        #[serde(deserialize_with = "deserialize_decimal_from_any")]
+        amount: Decimal,
+    },
+    /// Claimed rewards
+    RewardsClaim {
+        #[serde(deserialize_with = "deserialize_decimal_from_any")]
+        amount: Decimal,
+    },
+    /// Token transfer between accounts or dexes; `amount` and `fee` are in `token`
+    #[serde(rename_all = "camelCase")]
+    Send {
+        user: Address,
+        destination: Address,
+        #[serde(default)]
+        source_dex: String,
+        #[serde(default)]
+        destination_dex: String,
+        token: String,
+        #[serde(deserialize_with = "deserialize_decimal_from_any")]
+        amount: Decimal,
+        #[serde(deserialize_with = "deserialize_decimal_from_any")]
+        usdc_value: Decimal,
+        #[serde(deserialize_with = "deserialize_decimal_from_any")]
+        fee: Decimal,
+        #[serde(with = "rust_decimal::serde::str_option", default)]
+        native_token_fee: Option<Decimal>,
+        #[serde(default)]
+        nonce: Option<u64>,
+        #[serde(default)]
+        fee_token: Option<String>,
+    },
+    /// Staking deposit or withdrawal; `amount` is in `token`
+    #[serde(rename_all = "camelCase")]
+    CStakingTransfer {
+        token: String,
+        #[serde(deserialize_with = "deserialize_decimal_from_any")]
+        amount: Decimal,
+        is_deposit: bool,
+    },
+    /// Update type not modeled by this SDK (forward-compatible fallback)
+    #[serde(other)]
+    Unknown,
+}
+
+/// Position closed by a [`LedgerDelta::LedgerLiquidation`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LiquidatedPosition {
+    pub coin: String,
+    #[serde(deserialize_with = "deserialize_decimal_from_any")]
+    pub szi: Decimal,
+}
+
+/// Aggregate user state of the `webData3` channel.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebData3 {
+    /// Account-level state
+    pub user_state: WebData3UserState,
+    /// State of each perp dex, the first one being the main dex
+    #[serde(default)]
+    pub perp_dex_states: Vec<PerpDexState>,
+}
+
+/// Account-level state of a [`WebData3`] message.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebData3UserState {
+    pub user: Address,
+    /// Approved agent, if any
+    #[serde(default)]
+    pub agent_address: Option<Address>,
+    /// Expiry of the agent approval in milliseconds
+    #[serde(default)]
+    pub agent_valid_until: Option<u64>,
+    /// Server time in milliseconds
+    pub server_time: u64,
+    /// Cumulative net deposits
+    #[serde(deserialize_with = "deserialize_decimal_from_any")]
+    pub cum_ledger: Decimal,
+    #[serde(default)]
+    pub is_vault: bool,
+    #[serde(default)]
+    pub opt_out_of_spot_dusting: bool,
+    #[serde(default)]
+    pub dex_abstraction_enabled: bool,
+}
+
+/// Per-dex state of a [`WebData3`] message.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PerpDexState {
+    /// Total equity of the vaults on the dex
+    #[serde(deserialize_with = "deserialize_decimal_from_any")]
+    pub total_vault_equity: Decimal,
+    /// Markets at their open interest cap
+    #[serde(default)]
+    pub perps_at_open_interest_cap: Option<Vec<String>>,
+    /// Largest vaults of the dex
+    #[serde(default)]
+    pub leading_vaults: Option<Vec<LeadingVault>>,
+}
+
+/// Vault listed in [`PerpDexState::leading_vaults`] and [`WebData2::leading_vaults`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LeadingVault {
+    pub address: Address,
+    pub name: String,
+}
+
+/// Aggregate user state of the `webData2` channel.
+///
+/// The snapshot the Hyperliquid frontend renders: perp margin and positions,
+/// open orders, spot balances, TWAPs and the contexts of every market. Fields
+/// not modeled here are kept in [`extra`](Self::extra).
+///
+/// # Example
+///
+/// ```no_run
+/// use futures::StreamExt;
+/// use hypersdk::hypercore::{self, types::*, ws::Event};
+///
+/// # async fn example() -> anyhow::Result<()> {
+/// let mut ws = hypercore::mainnet_ws();
+/// ws.subscribe(Subscription::WebData2 { user: "0x...".parse()?, dex: None });
+///
+/// while let Some(event) = ws.next().await {
+///     let Event::Message(Incoming::WebData2(data)) = event else { continue };
+///     println!("account value: {}", data.account_value());
+///     for position in data.positions() {
+///         println!("{}: {} (uPnL {})", position.coin, position.szi, position.unrealized_pnl);
+///     }
+///     println!("{} open orders", data.open_orders.len());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebData2 {
+    /// HIP-3 dex of the perp state, `None` for the main dex
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dex: Option<String>,
+    pub user: Address,
+    /// Server time in milliseconds
+    pub server_time: u64,
+    /// Perp margin and positions
+    pub clearinghouse_state: ClearinghouseState,
+    /// Open orders
+    #[serde(default)]
+    pub open_orders: Vec<BasicOrder>,
+    /// Perp markets, in the order of [`asset_ctxs`](Self::asset_ctxs)
+    #[serde(default)]
+    pub meta: Option<WebData2Meta>,
+    /// Contexts of the perp markets
+    #[serde(default)]
+    pub asset_ctxs: Vec<AssetContext>,
+    /// Spot balances
+    #[serde(default)]
+    pub spot_state: Option<SpotState>,
+    /// Contexts of the spot markets
+    #[serde(default)]
+    pub spot_asset_ctxs: Vec<SpotAssetContext>,
+    /// Running TWAP orders by TWAP ID
+    #[serde(default)]
+    pub twap_states: Vec<(u64, TwapState)>,
+    /// Approved agent, if any
+    #[serde(default)]
+    pub agent_address: Option<Address>,
+    /// Expiry of the agent approval in milliseconds
+    #[serde(default)]
+    pub agent_valid_until: Option<u64>,
+    /// Cumulative net deposits
+    #[serde(with = "rust_decimal::serde::str_option", default)]
+    pub cum_ledger: Option<Decimal>,
+    #[serde(default)]
+    pub is_vault: bool,
+    /// Total equity of the vaults
+    #[serde(with = "rust_decimal::serde::str_option", default)]
+    pub total_vault_equity: Option<Decimal>,
+    /// Largest vaults
+    #[serde(default)]
+    pub leading_vaults: Option<Vec<LeadingVault>>,
+    /// Markets at their open interest cap
+    #[serde(default)]
+    pub perps_at_open_interest_cap: Option<Vec<String>>,
+    /// Fields not modeled by this SDK
+    #[serde(flatten)]
+    pub extra: serde_json::Value,
+}
+
+/// Perp markets of a [`WebData2`] message.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WebData2Meta {
+    pub universe: Vec<WebData2Asset>,
+}
+
+/// Perp market of a [`WebData2Meta`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebData2Asset {
+    pub name: String,
+    pub sz_decimals: u32,
+    pub max_leverage: u32,
+    #[serde(default)]
+    pub only_isolated: bool,
+    #[serde(default)]
+    pub is_delisted: bool,
+}
+
+impl WebData2 {
+    /// Returns the perp margin summary.
+    #[must_use]
+    pub fn margin_summary(&self) -> &MarginSummary {
+        &self.clearinghouse_state.margin_summary
+    }
+
+    /// Returns the total perp account value.
+    #[must_use]
+    pub fn account_value(&self) -> Decimal {
+        self.clearinghouse_state.margin_summary.account_value
+    }
+
+    /// Returns the amount available for withdrawal.
+    #[must_use]
+    pub fn withdrawable(&self) -> Decimal {
+        self.clearinghouse_state.withdrawable
+    }
+
+    /// Returns the open perp positions.
+    pub fn positions(&self) -> impl Iterator<Item = &PositionData> {
+        self.clearinghouse_state
+            .asset_positions
+            .iter()
+            .map(|position| &position.position)
+            .filter(|position| !position.szi.is_zero())
+    }
+
+    /// Returns the open position on `coin`.
+    #[must_use]
+    pub fn position(&self, coin: &str) -> Option<&PositionData> {
+        self.positions().find(|position| position.coin == coin)
+    }
+
+    /// Returns the open orders on `coin`.
+    pub fn orders<'a>(&'a self, coin: &'a str) -> impl Iterator<Item = &'a BasicOrder> {
+        self.open_orders
+            .iter()
+            .filter(move |order| order.coin == coin)
+    }
+
+    /// Returns the context of the perp market `coin`.
+    #[must_use]
+    pub fn asset_ctx(&self, coin: &str) -> Option<&AssetContext> {
+        let index = self
+            .meta
+            .as_ref()?
+            .universe
+            .iter()
+            .position(|asset| asset.name == coin)?;
+        self.asset_ctxs.get(index)
+    }
+
+    /// Returns the spot balance of `coin`.
+    #[must_use]
+    pub fn spot_balance(&self, coin: &str) -> Option<&UserBalance> {
+        self.spot_state
+            .as_ref()?
+            .balances
+            .iter()
+            .find(|balance| balance.coin == coin)
+    }
+}
+
 /// User leverage information for `activeAssetData`.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -1491,7 +2177,7 @@ pub enum TimeInForce {
 /// let status = OrderStatus::Open;
 /// assert!(!status.is_finished());
 /// ```
-#[derive(Debug, Copy, Clone, Hash, Deserialize, Serialize, derive_more::Display)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize, derive_more::Display)]
 #[serde(rename_all = "camelCase")]
 pub enum OrderStatus {
     /// Order is active on the book
@@ -1842,6 +2528,8 @@ impl SendAsset {
 /// - **Success**: Order was accepted (generic success)
 /// - **Resting**: Order is resting on the book (not immediately filled)
 /// - **Filled**: Order was immediately filled (market or aggressive limit)
+/// - **WaitingForFill**: TP/SL leg waiting for its entry order to fill
+/// - **WaitingForTrigger**: Trigger order waiting for its trigger price
 /// - **Error**: Order was rejected with an error message
 ///
 /// # Example
@@ -1860,6 +2548,9 @@ impl SendAsset {
 ///     OrderResponseStatus::Filled { total_sz, avg_px, oid } => {
 ///         println!("Order {} filled: {} @ avg {}", oid, total_sz, avg_px);
 ///     }
+///     OrderResponseStatus::WaitingForFill | OrderResponseStatus::WaitingForTrigger => {
+///         println!("Trigger order accepted");
+///     }
 ///     OrderResponseStatus::Error(err) => {
 ///         eprintln!("Order rejected: {}", err);
 ///     }
@@ -1889,6 +2580,10 @@ pub enum OrderResponseStatus {
         /// Order ID
         oid: u64,
     },
+    /// TP/SL order of a [`OrderGrouping::NormalTpsl`] group waiting for the entry to fill
+    WaitingForFill,
+    /// Trigger order waiting for its trigger price
+    WaitingForTrigger,
     /// Order rejected with error
     Error(String),
 }
@@ -1942,6 +2637,7 @@ impl OrderResponseStatus {
 ///
 /// - [`OrderRequest`]: Individual order within the batch
 /// - [`OrderGrouping`]: Grouping strategy for the batch
+/// - [`Builder`]: Builder fee charged on the batch
 /// - [`OrderResponseStatus`]: Response status for each order
 /// - [`HttpClient::place`](crate::hypercore::http::Client::place): Method to submit orders
 ///
@@ -1966,6 +2662,7 @@ impl OrderResponseStatus {
 ///         }
 ///     ],
 ///     grouping: OrderGrouping::Na,
+///     builder: None,
 /// };
 /// ```
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -1973,6 +2670,9 @@ impl OrderResponseStatus {
 pub struct BatchOrder {
     pub orders: Vec<OrderRequest>,
     pub grouping: OrderGrouping,
+    /// Builder receiving a fee on the orders, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub builder: Option<Builder>,
 }
 
 /// Order grouping strategy.
@@ -1992,6 +2692,39 @@ pub enum OrderGrouping {
     PositionTpsl,
 }
 
+/// Builder fee attached to a [`BatchOrder`].
+///
+/// The builder receives `fee` on every fill of the orders. The user must first
+/// approve a maximum fee for the builder with
+/// [`HttpClient::approve_builder_fee`](crate::hypercore::http::Client::approve_builder_fee);
+/// see [`HttpClient::max_builder_fee`](crate::hypercore::http::Client::max_builder_fee).
+///
+/// # Example
+///
+/// ```
+/// use hypersdk::hypercore::types::Builder;
+/// use alloy::primitives::address;
+///
+/// // 1 basis point
+/// let builder = Builder {
+///     address: address!("0x97271b6b7f3b23a2f4700ae671b05515ae5c3319"),
+///     fee: 10,
+/// };
+/// ```
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct Builder {
+    /// Builder address
+    #[serde(
+        rename = "b",
+        serialize_with = "super::utils::serialize_address_as_hex",
+        deserialize_with = "super::utils::deserialize_address_from_hex"
+    )]
+    pub address: Address,
+    /// Fee in tenths of a basis point (10 is 0.01%)
+    #[serde(rename = "f")]
+    pub fee: u64,
+}
+
 /// A single order to be placed on the exchange.
 ///
 /// Used as an element of [`BatchOrder::orders`] when submitting one or more
@@ -2138,6 +2871,109 @@ pub struct ScheduleCancel {
     pub time: Option<u64>,
 }
 
+/// TWAP order placement.
+///
+/// Wraps a single [`TwapRequest`]. The exchange splits the order into slices
+/// executed every 30 seconds over the requested duration.
+///
+/// # Example
+///
+/// ```
+/// use hypersdk::hypercore::types::*;
+/// use rust_decimal::dec;
+///
+/// let twap = TwapOrder {
+///     twap: TwapRequest {
+///         asset: 0, // BTC
+///         is_buy: true,
+///         sz: dec!(1),
+///         reduce_only: false,
+///         minutes: 30,
+///         randomize: false,
+///     },
+/// };
+/// ```
+///
+/// <https://hyperliquid.gitbook.io/hyperliquid-docs/for-developers/api/exchange-endpoint#place-a-twap-order>
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TwapOrder {
+    pub twap: TwapRequest,
+}
+
+/// Parameters of a TWAP order.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TwapRequest {
+    /// Asset index identifying the trading pair.
+    #[serde(rename = "a")]
+    pub asset: usize,
+    /// `true` for a buy (bid), `false` for a sell (ask).
+    #[serde(rename = "b")]
+    pub is_buy: bool,
+    /// Total size in base asset units.
+    /// Uses normalized serialization (removes trailing zeros) for consistent hashing.
+    #[serde(rename = "s", with = "super::utils::decimal_normalized")]
+    pub sz: Decimal,
+    /// When `true`, the TWAP can only reduce an existing position.
+    #[serde(rename = "r")]
+    pub reduce_only: bool,
+    /// Duration of the TWAP in minutes.
+    #[serde(rename = "m")]
+    pub minutes: u64,
+    /// Randomize the slice sizes.
+    #[serde(rename = "t")]
+    pub randomize: bool,
+}
+
+/// TWAP cancellation.
+///
+/// <https://hyperliquid.gitbook.io/hyperliquid-docs/for-developers/api/exchange-endpoint#cancel-a-twap-order>
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TwapCancel {
+    /// Asset index the TWAP belongs to.
+    #[serde(rename = "a")]
+    pub asset: usize,
+    /// TWAP ID returned when the TWAP was placed.
+    #[serde(rename = "t")]
+    pub twap_id: u64,
+}
+
+/// Status returned for a TWAP order placement.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TwapOrderResponseStatus {
+    /// TWAP accepted and running.
+    Running {
+        /// TWAP ID, used for cancellation.
+        #[serde(rename = "twapId")]
+        twap_id: u64,
+    },
+    /// TWAP rejected with error.
+    Error(String),
+}
+
+impl TwapOrderResponseStatus {
+    /// Returns the TWAP ID if the TWAP is running.
+    #[must_use]
+    pub fn twap_id(&self) -> Option<u64> {
+        match self {
+            TwapOrderResponseStatus::Running { twap_id } => Some(*twap_id),
+            TwapOrderResponseStatus::Error(_) => None,
+        }
+    }
+
+    /// Returns the error message if this is an error response.
+    #[must_use]
+    pub fn error(&self) -> Option<&str> {
+        match self {
+            TwapOrderResponseStatus::Error(err) => Some(err),
+            TwapOrderResponseStatus::Running { .. } => None,
+        }
+    }
+}
+
 /// Clearinghouse state for a user's perpetual positions.
 ///
 /// # Example
@@ -2164,7 +3000,7 @@ pub struct ScheduleCancel {
 /// # Ok(())
 /// # }
 /// ```
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ClearinghouseState {
     /// Margin summary for isolated positions
@@ -2184,7 +3020,7 @@ pub struct ClearinghouseState {
 /// Margin summary for an account.
 ///
 /// Contains aggregate margin information for either isolated or cross-margin positions.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MarginSummary {
     /// Total account value (equity)
@@ -2218,7 +3054,7 @@ impl MarginSummary {
 }
 
 /// Position type for perpetual positions.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, derive_more::Display)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, derive_more::Display)]
 #[serde(rename_all = "camelCase")]
 pub enum PositionType {
     /// One-way position mode (single position per market)
@@ -2229,7 +3065,7 @@ pub enum PositionType {
 /// A user's position in a specific asset.
 ///
 /// Wraps the position details along with cumulative funding information.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AssetPosition {
     /// Type of position
@@ -2242,7 +3078,7 @@ pub struct AssetPosition {
 /// Detailed position data for an asset.
 ///
 /// Contains all information about a single perpetual position.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PositionData {
     /// Asset/coin symbol (e.g., "BTC", "ETH")
@@ -2296,7 +3132,7 @@ impl PositionData {
 }
 
 /// Leverage type for positions.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, derive_more::Display)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, derive_more::Display)]
 #[serde(rename_all = "camelCase")]
 pub enum LeverageType {
     /// Cross-margin mode (shared margin across positions)
@@ -2308,7 +3144,7 @@ pub enum LeverageType {
 }
 
 /// Leverage configuration for a position.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Leverage {
     /// Leverage type
@@ -2339,7 +3175,7 @@ impl Leverage {
 /// Cumulative funding payments for a position.
 ///
 /// Tracks funding payments over different time periods.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CumulativeFunding {
     /// Total funding payments since position opened
@@ -2502,6 +3338,54 @@ impl AssetContext {
     }
 }
 
+/// Spot asset context.
+///
+/// Market data for a spot pair, as returned by `spotMetaAndAssetCtxs`.
+///
+/// # Example
+///
+/// ```no_run
+/// use hypersdk::hypercore;
+///
+/// # async fn example() -> anyhow::Result<()> {
+/// let client = hypercore::mainnet();
+/// for (market, ctx) in client.spot_with_ctxs().await? {
+///     println!("{}: 24h volume {}", market.symbol(), ctx.day_ntl_vlm);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpotAssetContext {
+    /// Market name (e.g. "PURR/USDC" or "@1")
+    ///
+    /// Empty in [`Incoming::ActiveSpotAssetCtx`] messages that omit it.
+    #[serde(default)]
+    pub coin: String,
+    /// Mark price
+    #[serde(with = "rust_decimal::serde::str_option", default)]
+    pub mark_px: Option<Decimal>,
+    /// Mid price between best bid/ask
+    #[serde(with = "rust_decimal::serde::str_option", default)]
+    pub mid_px: Option<Decimal>,
+    /// Previous day closing price
+    #[serde(with = "rust_decimal::serde::str")]
+    pub prev_day_px: Decimal,
+    /// 24h notional volume
+    #[serde(with = "rust_decimal::serde::str")]
+    pub day_ntl_vlm: Decimal,
+    /// 24h volume in base token
+    #[serde(with = "rust_decimal::serde::str_option", default)]
+    pub day_base_vlm: Option<Decimal>,
+    /// Circulating supply of the base token
+    #[serde(with = "rust_decimal::serde::str_option", default)]
+    pub circulating_supply: Option<Decimal>,
+    /// Total supply of the base token
+    #[serde(with = "rust_decimal::serde::str_option", default)]
+    pub total_supply: Option<Decimal>,
+}
+
 /// User balance.
 ///
 /// Represents the balance of a specific token in a user's account.
@@ -2534,7 +3418,7 @@ impl AssetContext {
 /// }
 /// # }
 /// ```
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct UserBalance {
     /// Token symbol
@@ -2570,6 +3454,32 @@ pub struct UserFees {
     pub referral_discount: Decimal,
 }
 
+/// Address-based request budget.
+///
+/// Returned by the `userRateLimit` info endpoint. Each address can send one
+/// action per USDC traded, on top of an initial buffer.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserRateLimit {
+    /// Cumulative traded volume
+    pub cum_vlm: Decimal,
+    /// Requests already used
+    pub n_requests_used: u64,
+    /// Request cap derived from the traded volume
+    pub n_requests_cap: u64,
+    /// Additional requests reserved for the address
+    #[serde(default)]
+    pub n_requests_surplus: u64,
+}
+
+impl UserRateLimit {
+    /// Returns the number of requests left before being rate limited.
+    #[must_use]
+    pub fn remaining(&self) -> u64 {
+        (self.n_requests_cap + self.n_requests_surplus).saturating_sub(self.n_requests_used)
+    }
+}
+
 impl UserBalance {
     /// Returns the available balance (total - hold).
     ///
@@ -2940,7 +3850,7 @@ pub struct SubAccount {
 /// Spot trading state for an account.
 ///
 /// Contains the spot balances for an account.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SpotState {
     /// List of spot balances
@@ -3082,6 +3992,11 @@ pub(super) enum InfoRequest {
         dex: Option<String>,
     },
     SpotMeta,
+    MetaAndAssetCtxs {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        dex: Option<String>,
+    },
+    SpotMetaAndAssetCtxs,
     PerpDexs,
     FrontendOpenOrders {
         user: Address,
@@ -3118,6 +4033,13 @@ pub(super) enum InfoRequest {
         #[serde(skip_serializing_if = "Option::is_none")]
         dex: Option<String>,
     },
+    L2Book {
+        coin: String,
+        #[serde(rename = "nSigFigs", skip_serializing_if = "Option::is_none")]
+        n_sig_figs: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        mantissa: Option<u32>,
+    },
     CandleSnapshot {
         req: CandleSnapshotRequest,
     },
@@ -3127,6 +4049,10 @@ pub(super) enum InfoRequest {
     ExtraAgents {
         user: Address,
     },
+    MaxBuilderFee {
+        user: Address,
+        builder: Address,
+    },
     FundingHistory {
         coin: String,
         #[serde(rename = "startTime")]
@@ -3134,6 +4060,20 @@ pub(super) enum InfoRequest {
         #[serde(rename = "endTime", skip_serializing_if = "Option::is_none")]
         end_time: Option<u64>,
     },
+    UserFunding {
+        user: Address,
+        #[serde(rename = "startTime")]
+        start_time: u64,
+        #[serde(rename = "endTime", skip_serializing_if = "Option::is_none")]
+        end_time: Option<u64>,
+    },
+    UserNonFundingLedgerUpdates {
+        user: Address,
+        #[serde(rename = "startTime")]
+        start_time: u64,
+        #[serde(rename = "endTime", skip_serializing_if = "Option::is_none")]
+        end_time: Option<u64>,
+    },
     /// Retrieve details for a vault.
     VaultDetails {
         #[serde(rename = "vaultAddress")]
@@ -3156,12 +4096,49 @@ pub(super) enum InfoRequest {
     UserFees {
         user: Address,
     },
+    UserRateLimit {
+        user: Address,
+    },
+}
+
+impl InfoRequest {
+    /// Returns the IP weight of the request.
+    ///
+    /// <https://hyperliquid.gitbook.io/hyperliquid-docs/for-developers/api/rate-limits-and-user-limits>
+    pub(super) fn weight(&self) -> u32 {
+        match self {
+            InfoRequest::L2Book { .. }
+            | InfoRequest::AllMids { .. }
+            | InfoRequest::ClearinghouseState { .. }
+            | InfoRequest::OrderStatus { .. }
+            | InfoRequest::SpotClearinghouseState { .. } => 2,
+            InfoRequest::UserRole { .. } => 60,
+            _ => 20,
+        }
+    }
+
+    /// Returns the number of returned items that weigh one more, for requests
+    /// whose weight grows with the response.
+    pub(super) fn items_per_weight(&self) -> Option<usize> {
+        match self {
+            InfoRequest::HistoricalOrders { .. }
+            | InfoRequest::UserFills { .. }
+            | InfoRequest::UserFillsByTime { .. }
+            | InfoRequest::FundingHistory { .. }
+            | InfoRequest::UserFunding { .. }
+            | InfoRequest::UserNonFundingLedgerUpdates { .. } => Some(20),
+            InfoRequest::CandleSnapshot { .. } => Some(60),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use rust_decimal::dec;
+
     use super::*;
-    use crate::hypercore::types::api::Response;
+    use crate::hypercore::types::api::{OkResponse, Response};
 
     #[test]
     fn test_api_error_response() {
@@ -3203,6 +4180,34 @@ mod tests {
         assert!(res.is_ok());
     }
 
+    #[test]
+    fn test_api_bracket_order_response() {
+        let text = r#"{
+           "status":"ok",
+           "response":{
+              "type":"order",
+              "data":{
+                 "statuses":[
+                    {"resting":{"oid":77738308}},
+                    "waitingForFill",
+                    "waitingForTrigger"
+                 ]
+              }
+           }
+        }"#;
+        let Response::Ok(OkResponse::Order { statuses }) =
+            serde_json::from_str::<Response>(text).unwrap()
+        else {
+            panic!("expected an order response");
+        };
+        assert_eq!(statuses[0].oid(), Some(77738308));
+        assert!(matches!(statuses[1], OrderResponseStatus::WaitingForFill));
+        assert!(matches!(
+            statuses[2],
+            OrderResponseStatus::WaitingForTrigger
+        ));
+    }
+
     #[test]
     fn test_signature_from_str_with_0x_prefix() {
         let hex_sig = "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef1b";
@@ -3331,10 +4336,11 @@ mod tests {
     fn test_candle_subscription() {
         let sub = Subscription::Candle {
             coin: "BTC".to_string(),
-            interval: "1m".to_string(),
+            interval: CandleInterval::OneMinute,
         };
 
         let json = serde_json::to_string(&sub).unwrap();
+        assert_eq!(json, r#"{"type":"candle","coin":"BTC","interval":"1m"}"#);
         let deserialized: Subscription = serde_json::from_str(&json).unwrap();
         assert_eq!(sub, deserialized);
     }
@@ -3624,25 +4630,239 @@ mod tests {
     }
 
     #[test]
-    fn test_incoming_web_data2_raw_payload() {
+    fn test_batch_order_builder() {
+        let mut batch = BatchOrder {
+            orders: vec![],
+            grouping: OrderGrouping::Na,
+            builder: None,
+        };
+        assert_eq!(
+            serde_json::to_string(&batch).unwrap(),
+            r#"{"orders":[],"grouping":"na"}"#
+        );
+
+        batch.builder = Some(Builder {
+            address: "0x97271B6B7F3B23A2F4700AE671B05515AE5C3319"
+                .parse()
+                .unwrap(),
+            fee: 10,
+        });
+        let json = serde_json::to_string(&batch).unwrap();
+        assert_eq!(
+            json,
+            r#"{"orders":[],"grouping":"na","builder":{"b":"0x97271b6b7f3b23a2f4700ae671b05515ae5c3319","f":10}}"#
+        );
+        let batch: BatchOrder = serde_json::from_str(&json).unwrap();
+        assert_eq!(batch.builder.unwrap().fee, 10);
+    }
+
+    #[test]
+    fn test_user_ledger_subscriptions() {
+        let user: Address = "0x1234567890abcdef1234567890abcdef12345678"
+            .parse()
+            .unwrap();
+        let sub = Subscription::UserNonFundingLedgerUpdates { user };
+        let json = serde_json::to_string(&sub).unwrap();
+        assert_eq!(
+            json,
+            r#"{"type":"userNonFundingLedgerUpdates","user":"0x1234567890abcdef1234567890abcdef12345678"}"#
+        );
+        assert_eq!(serde_json::from_str::<Subscription>(&json).unwrap(), sub);
+        assert_eq!(
+            serde_json::to_value(Subscription::WebData3 { user }).unwrap()["type"],
+            "webData3"
+        );
+        assert_eq!(
+            serde_json::to_value(Subscription::UserFundings { user }).unwrap()["type"],
+            "userFundings"
+        );
+        assert_eq!(
+            serde_json::to_value(Subscription::Notification { user }).unwrap()["type"],
+            "notification"
+        );
+    }
+
+    #[test]
+    fn test_incoming_ledger_updates() {
         let json = r#"{
-            "channel":"webData2",
+            "channel":"userNonFundingLedgerUpdates",
             "data":{
-                "clearinghouseState":{"time":1710002000000},
-                "openOrders":[{"oid":1234}]
+                "isSnapshot":true,
+                "user":"0x1234567890abcdef1234567890abcdef12345678",
+                "nonFundingLedgerUpdates":[
+                    {"time":1710000000000,"hash":"0xaa","delta":{"type":"deposit","usdc":"1000.0"}},
+                    {"time":1710000000001,"hash":"0xab","delta":{"type":"withdraw","usdc":"99.0","nonce":1710000000001,"fee":"1.0"}},
+                    {"time":1710000000002,"hash":"0xac","delta":{"type":"accountClassTransfer","usdc":"50.0","toPerp":false}},
+                    {"time":1710000000003,"hash":"0xad","delta":{"type":"vaultDeposit","vault":"0xdfc24b077bc1425ad1dea75bcb6f8158e10df303","usdc":"10.0"}},
+                    {"time":1710000000004,"hash":"0xae","delta":{"type":"somethingNew","value":"1"}}
+                ]
             }
         }"#;
 
         let incoming: Incoming = serde_json::from_str(json).unwrap();
-        match incoming {
-            Incoming::WebData2 { data: payload, .. } => {
-                assert_eq!(payload["clearinghouseState"]["time"], 1710002000000u64);
-                assert_eq!(payload["openOrders"][0]["oid"], 1234u64);
+        assert_eq!(incoming.channel(), "userNonFundingLedgerUpdates");
+        let Incoming::UserNonFundingLedgerUpdates {
+            is_snapshot,
+            non_funding_ledger_updates: updates,
+            ..
+        } = incoming
+        else {
+            panic!("Expected Incoming::UserNonFundingLedgerUpdates");
+        };
+        assert!(is_snapshot);
+        assert_eq!(updates.len(), 5);
+        assert!(matches!(
+            updates[0].delta,
+            LedgerDelta::Deposit { usdc } if usdc == dec!(1000)
+        ));
+        assert!(matches!(
+            updates[1].delta,
+            LedgerDelta::Withdraw { usdc, fee, .. } if usdc == dec!(99) && fee == dec!(1)
+        ));
+        assert!(matches!(
+            updates[2].delta,
+            LedgerDelta::AccountClassTransfer { to_perp: false, .. }
+        ));
+        assert!(matches!(updates[3].delta, LedgerDelta::VaultDeposit { .. }));
+        assert!(matches!(updates[4].delta, LedgerDelta::Unknown));
+
+        let json = r#"{
+            "channel":"userFundings",
+            "data":{
+                "user":"0x1234567890abcdef1234567890abcdef12345678",
+                "fundings":[{"time":1710000000000,"coin":"BTC","usdc":"-0.5","szi":"0.1","fundingRate":"0.0000125","nSamples":24}]
+            }
+        }"#;
+        match serde_json::from_str(json).unwrap() {
+            Incoming::UserFundings {
+                is_snapshot,
+                fundings,
+                ..
+            } => {
+                assert!(!is_snapshot);
+                assert_eq!(fundings[0].usdc, dec!(-0.5));
+                assert_eq!(fundings[0].n_samples, Some(24));
+            }
+            _ => panic!("Expected Incoming::UserFundings"),
+        }
+    }
+
+    #[test]
+    fn test_incoming_web_data3() {
+        let json = r#"{
+            "channel":"webData3",
+            "data":{
+                "userState":{
+                    "agentAddress":null,
+                    "agentValidUntil":null,
+                    "serverTime":1710000000000,
+                    "cumLedger":"1234.5",
+                    "isVault":false,
+                    "user":"0x1234567890abcdef1234567890abcdef12345678"
+                },
+                "perpDexStates":[{"totalVaultEquity":"100.0","perpsAtOpenInterestCap":["BTC"]}]
             }
-            _ => panic!("Expected Incoming::WebData2"),
+        }"#;
+
+        match serde_json::from_str(json).unwrap() {
+            Incoming::WebData3(data) => {
+                assert_eq!(data.user_state.cum_ledger, dec!(1234.5));
+                assert!(data.user_state.agent_address.is_none());
+                assert_eq!(data.perp_dex_states.len(), 1);
+                assert_eq!(
+                    data.perp_dex_states[0].perps_at_open_interest_cap,
+                    Some(vec!["BTC".to_string()])
+                );
+            }
+            _ => panic!("Expected Incoming::WebData3"),
+        }
+
+        let json = r#"{"channel":"notification","data":{"notification":"Order filled"}}"#;
+        match serde_json::from_str(json).unwrap() {
+            Incoming::Notification(notification) => {
+                assert_eq!(notification.notification, "Order filled");
+            }
+            _ => panic!("Expected Incoming::Notification"),
+        }
+
+        let json = r#"{
+            "channel":"activeSpotAssetCtx",
+            "data":{"coin":"@107","ctx":{"prevDayPx":"20.0","dayNtlVlm":"1000.0","markPx":"21.0","midPx":"21.1","circulatingSupply":"100","totalSupply":"200","dayBaseVlm":"50"}}
+        }"#;
+        match serde_json::from_str(json).unwrap() {
+            Incoming::ActiveSpotAssetCtx { coin, ctx } => {
+                assert_eq!(coin, "@107");
+                assert_eq!(ctx.mark_px, Some(dec!(21)));
+            }
+            _ => panic!("Expected Incoming::ActiveSpotAssetCtx"),
         }
     }
 
+    #[test]
+    fn test_incoming_web_data2() {
+        let json = r#"{
+            "channel":"webData2",
+            "data":{
+                "clearinghouseState":{
+                    "marginSummary":{"accountValue":"1000.5","totalNtlPos":"500.0","totalRawUsd":"500.5","totalMarginUsed":"50.0"},
+                    "crossMarginSummary":{"accountValue":"1000.5","totalNtlPos":"500.0","totalRawUsd":"500.5","totalMarginUsed":"50.0"},
+                    "crossMaintenanceMarginUsed":"10.0",
+                    "withdrawable":"950.5",
+                    "assetPositions":[{
+                        "type":"oneWay",
+                        "position":{
+                            "coin":"ETH","szi":"-0.25","leverage":{"type":"cross","value":10},
+                            "entryPx":"2000.0","positionValue":"500.0","unrealizedPnl":"0.0",
+                            "returnOnEquity":"0.0","liquidationPx":null,"marginUsed":"50.0","maxLeverage":25,
+                            "cumFunding":{"allTime":"1.0","sinceOpen":"0.5","sinceChange":"0.1"}
+                        }
+                    }],
+                    "time":1710002000000
+                },
+                "openOrders":[{
+                    "timestamp":1710001000000,"coin":"BTC","side":"B","limitPx":"50000.0","sz":"0.01",
+                    "oid":1234,"origSz":"0.01","cloid":null,"orderType":"Limit","tif":"Gtc","reduceOnly":false
+                }],
+                "meta":{"universe":[{"name":"BTC","szDecimals":5,"maxLeverage":40},{"name":"ETH","szDecimals":4,"maxLeverage":25}]},
+                "assetCtxs":[
+                    {"funding":"0.0000125","openInterest":"100.0","markPx":"50100.0","oraclePx":"50090.0","midPx":"50100.5","premium":"0.0","prevDayPx":"49000.0","dayNtlVlm":"1000000.0"},
+                    {"funding":"0.00001","openInterest":"1000.0","markPx":"2000.0","oraclePx":"2000.0","midPx":"2000.5","premium":"0.0","prevDayPx":"1900.0","dayNtlVlm":"500000.0"}
+                ],
+                "spotState":{"balances":[{"coin":"USDC","token":0,"hold":"0.0","total":"25.0","entryNtl":"0.0"}]},
+                "twapStates":[],
+                "serverTime":1710002000001,
+                "cumLedger":"900.0",
+                "isVault":false,
+                "user":"0x1234567890abcdef1234567890abcdef12345678",
+                "agentAddress":null,
+                "agentValidUntil":null,
+                "somethingNew":{"a":1}
+            }
+        }"#;
+
+        let incoming: Incoming = serde_json::from_str(json).unwrap();
+        let Incoming::WebData2(data) = incoming else {
+            panic!("Expected Incoming::WebData2");
+        };
+        assert_eq!(data.account_value(), dec!(1000.5));
+        assert_eq!(data.withdrawable(), dec!(950.5));
+        assert_eq!(data.positions().count(), 1);
+        assert!(data.position("ETH").unwrap().is_short());
+        assert!(data.position("BTC").is_none());
+        assert_eq!(data.orders("BTC").next().unwrap().oid, 1234);
+        assert_eq!(data.asset_ctx("ETH").unwrap().mark_px, Some(dec!(2000)));
+        assert_eq!(data.spot_balance("USDC").unwrap().total, dec!(25));
+        assert_eq!(data.cum_ledger, Some(dec!(900)));
+        assert_eq!(data.extra["somethingNew"]["a"], 1);
+
+        // round trip
+        let json = serde_json::to_string(&Incoming::WebData2(data)).unwrap();
+        let Incoming::WebData2(data) = serde_json::from_str(&json).unwrap() else {
+            panic!("Expected Incoming::WebData2");
+        };
+        assert_eq!(data.extra["somethingNew"]["a"], 1);
+    }
+
     #[test]
     fn test_signature_from_str_invalid_length() {
         let hex_sig = "0x1234"; // Too short
@@ -3895,4 +5115,128 @@ mod tests {
             .unwrap();
         assert_eq!(trade.maker_address(), buyer);
     }
+
+    #[test]
+    fn l2_book_subscription_serialization() {
+        let sub = Subscription::l2_book("BTC");
+        assert_eq!(
+            serde_json::to_string(&sub).unwrap(),
+            r#"{"type":"l2Book","coin":"BTC"}"#
+        );
+
+        let sub =
+            Subscription::l2_book_aggregated("BTC", BookAggregation::sig_figs(5).with_mantissa(2));
+        assert_eq!(
+            serde_json::to_string(&sub).unwrap(),
+            r#"{"type":"l2Book","coin":"BTC","nSigFigs":5,"mantissa":2}"#
+        );
+
+        // the server echoes null parameters in subscription responses
+        let echoed: Subscription = serde_json::from_str(
+            r#"{"type":"l2Book","coin":"BTC","nSigFigs":null,"mantissa":null}"#,
+        )
+        .unwrap();
+        assert_eq!(echoed, Subscription::l2_book("BTC"));
+    }
+
+    #[test]
+    fn post_serialization() {
+        let msg = Outgoing::Post {
+            id: 7,
+            request: PostRequest::Info(serde_json::json!({ "type": "allMids" })),
+        };
+        assert_eq!(
+            serde_json::to_string(&msg).unwrap(),
+            r#"{"method":"post","id":7,"request":{"type":"info","payload":{"type":"allMids"}}}"#
+        );
+
+        let msg: Incoming = serde_json::from_str(
+            r#"{"channel":"post","data":{"id":7,"response":{"type":"action","payload":{"status":"ok","response":{"type":"default"}}}}}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            msg,
+            Incoming::Post {
+                id: 7,
+                response: PostResponse::Action(_)
+            }
+        ));
+
+        let msg: Incoming = serde_json::from_str(
+            r#"{"channel":"post","data":{"id":8,"response":{"type":"error","payload":"bad request"}}}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            msg,
+            Incoming::Post {
+                id: 8,
+                response: PostResponse::Error(err)
+            } if err == "bad request"
+        ));
+    }
+
+    #[test]
+    fn error_deserialization() {
+        let msg: Incoming = serde_json::from_str(
+            r#"{"channel":"error","data":"Invalid subscription {\"type\":\"trades\",\"coin\":\"FOO\"}"}"#,
+        )
+        .unwrap();
+        assert!(matches!(msg, Incoming::Error(err) if err.starts_with("Invalid subscription")));
+    }
+
+    #[test]
+    fn book_aggregation_validation() {
+        assert!(matches!(
+            BookAggregation::sig_figs(1).check(),
+            Err(Error::InvalidParameter(_))
+        ));
+        assert!(BookAggregation::sig_figs(6).check().is_err());
+        assert!(
+            BookAggregation::sig_figs(4)
+                .with_mantissa(2)
+                .check()
+                .is_err()
+        );
+        assert!(
+            BookAggregation::sig_figs(5)
+                .with_mantissa(3)
+                .check()
+                .is_err()
+        );
+        assert!(
+            BookAggregation::sig_figs(5)
+                .with_mantissa(5)
+                .check()
+                .is_ok()
+        );
+
+        let btc = PriceTick::for_perp(5);
+        assert_eq!(
+            BookAggregation::sig_figs(3)
+                .validate(&btc, dec!(93231))
+                .unwrap(),
+            dec!(100)
+        );
+        assert_eq!(
+            BookAggregation::sig_figs(5)
+                .with_mantissa(5)
+                .validate(&btc, dec!(100000))
+                .unwrap(),
+            dec!(50)
+        );
+
+        // 5 significant figures at 99.98 is a 0.001 step, finer than the 0.1 tick
+        assert!(
+            BookAggregation::sig_figs(5)
+                .validate(&btc, dec!(99.98))
+                .is_err()
+        );
+        assert_eq!(
+            BookAggregation::sig_figs(5)
+                .validate(&PriceTick::for_perp(2), dec!(99.98))
+                .unwrap(),
+            dec!(0.001)
+        );
+        assert_eq!(BookAggregation::sig_figs(2).step(dec!(0)), None);
+    }
 }
@@ -0,0 +1,516 @@
+//! Gap-free user feed.
+//!
+//! A websocket connection re-subscribes after a reconnection, but the fills, order
+//! updates and user events published while it was down are lost. [`UserStream`]
+//! subscribes to the user channels and, after every reconnection, backfills what
+//! was missed using the REST API:
+//!
+//! - fills since the last one received, with [`Client::user_fills_by_time_stream`],
+//! - the state of every order open before the gap, with [`Client::open_orders`]
+//!   and [`Client::order_status`].
+//!
+//! Fills are deduplicated by trade ID and hash and order updates by status, so the
+//! stream yields every fill and order state change exactly once, whether it came
+//! from the live feed, a `userFills` snapshot or the backfill.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use hypersdk::hypercore::{self, UserStream, UserStreamEvent};
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let user = "0x...".parse()?;
+//! let mut stream = UserStream::new(hypercore::mainnet(), hypercore::mainnet_ws(), user);
+//!
+//! while let Some(event) = stream.next().await {
+//!     match event {
+//!         UserStreamEvent::Fill(fill) => println!("fill {} {} @ {}", fill.coin, fill.sz, fill.px),
+//!         UserStreamEvent::Order(update) => println!("order {}: {}", update.order.oid, update.status),
+//!         UserStreamEvent::BackfillFailed { error, .. } => eprintln!("gap not recovered: {error}"),
+//!         _ => {}
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    hash::Hash,
+};
+
+use chrono::Utc;
+use either::Either;
+use futures::{Stream, StreamExt, TryStreamExt};
+
+use crate::{
+    Address,
+    hypercore::{
+        error,
+        http::Client,
+        types::{
+            BasicOrder, Fill, Incoming, NonUserCancel, OrderStatus, OrderUpdate, Subscription,
+            UserEvent, UserFunding, UserLiquidation, WsBasicOrder,
+        },
+        ws::{Connection, Event},
+    },
+};
+
+/// Number of fills and finished orders remembered for deduplication.
+const DEDUP_CAPACITY: usize = 10_000;
+
+/// Event yielded by a [`UserStream`].
+#[derive(Debug, Clone)]
+pub enum UserStreamEvent {
+    /// The websocket connection was established.
+    Connected,
+    /// The websocket connection was lost; missed updates are backfilled on reconnection.
+    Disconnected,
+    /// A new fill.
+    Fill(Fill),
+    /// A change of the status or remaining size of an order.
+    Order(OrderUpdate<WsBasicOrder>),
+    /// A funding payment.
+    ///
+    /// Funding payments are not backfilled.
+    Funding(UserFunding),
+    /// A liquidation of the user.
+    Liquidation(UserLiquidation),
+    /// Orders cancelled by the exchange.
+    NonUserCancel(Vec<NonUserCancel>),
+    /// The REST backfill after a reconnection failed.
+    ///
+    /// Updates since `since` (in milliseconds) may be missing. The backfill is
+    /// retried on the next reconnection.
+    BackfillFailed { since: u64, error: String },
+}
+
+/// Bounded set of recently seen keys.
+#[derive(Debug)]
+struct Seen<K> {
+    keys: HashSet<K>,
+    order: VecDeque<K>,
+}
+
+impl<K: Hash + Eq + Clone> Seen<K> {
+    fn new() -> Self {
+        Self {
+            keys: HashSet::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Records `key`, returning false if it was already seen.
+    fn insert(&mut self, key: K) -> bool {
+        if !self.keys.insert(key.clone()) {
+            return false;
+        }
+
+        self.order.push_back(key);
+        if self.order.len() > DEDUP_CAPACITY
+            && let Some(oldest) = self.order.pop_front()
+        {
+            self.keys.remove(&oldest);
+        }
+        true
+    }
+}
+
+/// Gap-free stream of the fills, order updates and events of a user.
+///
+/// The stream owns its websocket connection and subscribes to `orderUpdates`,
+/// `userFills` and `userEvents`. Fills from the first `userFills` snapshot are
+/// history and are not yielded; fills from later snapshots are yielded if they
+/// were missed.
+pub struct UserStream {
+    client: Client,
+    ws: Connection,
+    user: Address,
+    fills: Seen<(u64, String)>,
+    finished: Seen<u64>,
+    open: HashMap<u64, OrderUpdate<WsBasicOrder>>,
+    last_fill_time: Option<u64>,
+    // start of the current gap, in milliseconds
+    gap: Option<u64>,
+    connected_once: bool,
+    snapshot_seen: bool,
+    queue: VecDeque<UserStreamEvent>,
+}
+
+impl UserStream {
+    /// Creates a stream for `user` over `ws`, backfilling with `client`.
+    pub fn new(client: Client, ws: Connection, user: Address) -> Self {
+        for subscription in [
+            Subscription::OrderUpdates { user },
+            Subscription::UserFills { user },
+            Subscription::UserEvents { user },
+        ] {
+            ws.subscribe(subscription);
+        }
+
+        Self {
+            client,
+            ws,
+            user,
+            fills: Seen::new(),
+            finished: Seen::new(),
+            open: HashMap::new(),
+            last_fill_time: None,
+            gap: None,
+            connected_once: false,
+            snapshot_seen: false,
+            queue: VecDeque::new(),
+        }
+    }
+
+    /// Returns the user of the stream.
+    #[must_use]
+    pub fn user(&self) -> Address {
+        self.user
+    }
+
+    /// Returns the underlying websocket connection.
+    #[must_use]
+    pub fn connection(&self) -> &Connection {
+        &self.ws
+    }
+
+    /// Returns the orders known to be open.
+    #[must_use]
+    pub fn open_orders(&self) -> Vec<OrderUpdate<WsBasicOrder>> {
+        self.open.values().cloned().collect()
+    }
+
+    /// Returns the next event, or `None` once the connection is closed.
+    pub async fn next(&mut self) -> Option<UserStreamEvent> {
+        loop {
+            if let Some(event) = self.queue.pop_front() {
+                return Some(event);
+            }
+
+            match self.ws.next().await? {
+                Event::Connected => {
+                    self.queue.push_back(UserStreamEvent::Connected);
+                    if !self.connected_once {
+                        self.connected_once = true;
+                        // orders placed before the stream was created
+                        if let Err(err) = self.sync_open_orders(false).await {
+                            log::warn!("Unable to load open orders: {err}");
+                        }
+                    } else if let Some(since) = self.gap
+                        && let Err(err) = self.backfill(since).await
+                    {
+                        self.queue.push_back(UserStreamEvent::BackfillFailed {
+                            since,
+                            error: err.to_string(),
+                        });
+                        continue;
+                    }
+                    self.gap = None;
+                }
+                Event::Disconnected => {
+                    let now = Utc::now().timestamp_millis() as u64;
+                    let since = self.last_fill_time.map_or(now, |time| time.min(now));
+                    self.gap = Some(self.gap.map_or(since, |gap| gap.min(since)));
+                    self.queue.push_back(UserStreamEvent::Disconnected);
+                }
+                Event::SubscriptionError { error, .. } => {
+                    log::error!("User stream subscription rejected: {error}");
+                }
+                Event::Message(msg) => self.handle(msg),
+            }
+        }
+    }
+
+    /// Converts the stream into a [`Stream`].
+    pub fn into_stream(self) -> impl Stream<Item = UserStreamEvent> + Send {
+        futures::stream::unfold(self, |mut stream| async move {
+            let event = stream.next().await?;
+            Some((event, stream))
+        })
+    }
+
+    fn handle(&mut self, msg: Incoming) {
+        match msg {
+            Incoming::OrderUpdates(updates) => {
+                for update in updates {
+                    self.apply_order(update);
+                }
+            }
+            Incoming::UserFills {
+                is_snapshot,
+                user,
+                fills,
+            } if user == self.user => {
+                // the first snapshot is history
+                let emit = !is_snapshot || self.snapshot_seen;
+                self.snapshot_seen |= is_snapshot;
+                for fill in fills {
+                    self.apply_fill(fill, emit);
+                }
+            }
+            Incoming::UserEvents(event) => match event {
+                UserEvent::Fills { fills } => {
+                    for fill in fills {
+                        self.apply_fill(fill, true);
+                    }
+                }
+                UserEvent::Funding { funding } => {
+                    self.queue.push_back(UserStreamEvent::Funding(funding));
+                }
+                UserEvent::Liquidation { liquidation } => {
+                    self.queue
+                        .push_back(UserStreamEvent::Liquidation(liquidation));
+                }
+                UserEvent::NonUserCancel { non_user_cancel } => {
+                    self.queue
+                        .push_back(UserStreamEvent::NonUserCancel(non_user_cancel));
+                }
+                UserEvent::Unknown(_) => {}
+            },
+            _ => {}
+        }
+    }
+
+    fn apply_fill(&mut self, fill: Fill, emit: bool) {
+        if !self.fills.insert((fill.tid, fill.hash.clone())) {
+            return;
+        }
+
+        self.last_fill_time = Some(self.last_fill_time.unwrap_or(0).max(fill.time));
+        if emit {
+            self.queue.push_back(UserStreamEvent::Fill(fill));
+        }
+    }
+
+    fn apply_order(&mut self, update: OrderUpdate<WsBasicOrder>) {
+        let oid = update.order.oid;
+        if update.status.is_finished() {
+            self.open.remove(&oid);
+            if self.finished.insert(oid) {
+                self.queue.push_back(UserStreamEvent::Order(update));
+            }
+            return;
+        }
+
+        let changed = self
+            .open
+            .get(&oid)
+            .is_none_or(|known| known.status != update.status || known.order.sz != update.order.sz);
+        if changed && !self.finished.keys.contains(&oid) {
+            self.open.insert(oid, update.clone());
+            self.queue.push_back(UserStreamEvent::Order(update));
+        }
+    }
+
+    /// Loads the open orders, yielding the changes if `emit` is set.
+    ///
+    /// Returns the oids of the open orders.
+    async fn sync_open_orders(&mut self, emit: bool) -> error::Result<HashSet<u64>> {
+        let orders = self.client.open_orders(self.user, None).await?;
+        let mut open = HashSet::new();
+        for order in orders {
+            open.insert(order.oid);
+            let update = OrderUpdate {
+                status: OrderStatus::Open,
+                status_timestamp: order.timestamp,
+                order: ws_order(order),
+            };
+            if emit {
+                self.apply_order(update);
+            } else {
+                self.open.insert(update.order.oid, update);
+            }
+        }
+        Ok(open)
+    }
+
+    /// Recovers the fills and order updates missed since `since`.
+    async fn backfill(&mut self, since: u64) -> error::Result<()> {
+        // a single request is capped at 2000 fills
+        let fills: Vec<Fill> = self
+            .client
+            .user_fills_by_time_stream(self.user, since, None)
+            .try_collect()
+            .await?;
+        for fill in fills {
+            self.apply_fill(fill, true);
+        }
+
+        let known: Vec<u64> = self.open.keys().copied().collect();
+        let open = self.sync_open_orders(true).await?;
+        for oid in known.into_iter().filter(|oid| !open.contains(oid)) {
+            match self
+                .client
+                .order_status(self.user, Either::Left(oid))
+                .await?
+            {
+                Some(update) => self.apply_order(OrderUpdate {
+                    status: update.status,
+                    status_timestamp: update.status_timestamp,
+                    order: ws_order(update.order),
+                }),
+                None => {
+                    self.open.remove(&oid);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn ws_order(order: BasicOrder) -> WsBasicOrder {
+    WsBasicOrder {
+        timestamp: order.timestamp,
+        coin: order.coin,
+        side: order.side,
+        limit_px: order.limit_px,
+        sz: order.sz,
+        oid: order.oid,
+        orig_sz: order.orig_sz,
+        cloid: order.cloid,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::{Decimal, dec};
+
+    use super::*;
+    use crate::hypercore::{Chain, types::Side};
+
+    fn fill(tid: u64, time: u64) -> Fill {
+        serde_json::from_value(serde_json::json!({
+            "coin": "BTC",
+            "px": "100",
+            "sz": "1",
+            "side": "B",
+            "time": time,
+            "startPosition": "0",
+            "dir": "Open Long",
+            "closedPnl": "0",
+            "hash": format!("0x{tid:x}"),
+            "oid": 1,
+            "crossed": true,
+            "fee": "0",
+            "tid": tid,
+            "cloid": null,
+            "feeToken": "USDC",
+        }))
+        .unwrap()
+    }
+
+    fn update(oid: u64, status: OrderStatus, sz: Decimal) -> OrderUpdate<WsBasicOrder> {
+        OrderUpdate {
+            status,
+            status_timestamp: 0,
+            order: WsBasicOrder {
+                timestamp: 0,
+                coin: "BTC".into(),
+                side: Side::Bid,
+                limit_px: dec!(100),
+                sz,
+                oid,
+                orig_sz: dec!(1),
+                cloid: None,
+            },
+        }
+    }
+
+    fn stream() -> UserStream {
+        // never connects, messages are fed with `handle`
+        let ws = Connection::new("ws://127.0.0.1:1/ws".parse().unwrap());
+        UserStream::new(Client::new(Chain::Testnet), ws, Address::ZERO)
+    }
+
+    fn fill_tids(stream: &mut UserStream) -> Vec<u64> {
+        stream
+            .queue
+            .drain(..)
+            .filter_map(|event| match event {
+                UserStreamEvent::Fill(fill) => Some(fill.tid),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_seen() {
+        let mut seen = Seen::new();
+        assert!(seen.insert(0));
+        assert!(!seen.insert(0));
+
+        // the oldest keys are forgotten past the capacity
+        for key in 1..=DEDUP_CAPACITY {
+            assert!(seen.insert(key));
+        }
+        assert_eq!(seen.keys.len(), DEDUP_CAPACITY);
+        assert!(seen.insert(0));
+        assert!(!seen.insert(DEDUP_CAPACITY));
+    }
+
+    #[tokio::test]
+    async fn test_fills_dedup() {
+        let mut stream = stream();
+        let user = stream.user();
+
+        // the first snapshot is history
+        stream.handle(Incoming::UserFills {
+            is_snapshot: true,
+            user,
+            fills: vec![fill(1, 10)],
+        });
+        assert!(fill_tids(&mut stream).is_empty());
+
+        stream.handle(Incoming::UserFills {
+            is_snapshot: false,
+            user,
+            fills: vec![fill(1, 10), fill(2, 20)],
+        });
+        stream.handle(Incoming::UserEvents(UserEvent::Fills {
+            fills: vec![fill(2, 20)],
+        }));
+        assert_eq!(fill_tids(&mut stream), [2]);
+
+        // later snapshots yield the fills that were missed
+        stream.handle(Incoming::UserFills {
+            is_snapshot: true,
+            user,
+            fills: vec![fill(1, 10), fill(2, 20), fill(3, 30)],
+        });
+        assert_eq!(fill_tids(&mut stream), [3]);
+        assert_eq!(stream.last_fill_time, Some(30));
+
+        // other users are ignored
+        stream.handle(Incoming::UserFills {
+            is_snapshot: false,
+            user: Address::repeat_byte(1),
+            fills: vec![fill(4, 40)],
+        });
+        assert!(fill_tids(&mut stream).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_orders_dedup() {
+        let mut stream = stream();
+
+        stream.apply_order(update(1, OrderStatus::Open, dec!(1)));
+        stream.apply_order(update(1, OrderStatus::Open, dec!(1)));
+        assert_eq!(stream.queue.len(), 1);
+
+        // partially filled
+        stream.apply_order(update(1, OrderStatus::Open, dec!(0.5)));
+        assert_eq!(stream.queue.len(), 2);
+
+        stream.apply_order(update(1, OrderStatus::Filled, dec!(0)));
+        stream.apply_order(update(1, OrderStatus::Filled, dec!(0)));
+        assert_eq!(stream.queue.len(), 3);
+        assert!(stream.open_orders().is_empty());
+
+        // a stale open update after the order finished
+        stream.apply_order(update(1, OrderStatus::Open, dec!(0.5)));
+        assert_eq!(stream.queue.len(), 3);
+        assert!(stream.open_orders().is_empty());
+    }
+}
@@ -9,10 +9,87 @@
 //!
 //! - [`Event::Connected`] - Connection established (including after reconnection)
 //! - [`Event::Disconnected`] - Connection lost (will auto-reconnect)
+//! - [`Event::SubscriptionError`] - The server rejected a subscription
 //! - [`Event::Message`] - Contains an [`Incoming`] data message
 //!
 //! You can also check the current connection status using [`Connection::is_connected()`].
 //!
+//! # Typed Streams
+//!
+//! Instead of matching on [`Incoming`], a single channel can be consumed as a typed
+//! [`ChannelStream`]. Several streams can be opened from the same connection; the
+//! subscription is removed when the last stream for a channel is dropped.
+//!
+//! ```no_run
+//! use futures::StreamExt;
+//! use hypersdk::hypercore::{self, types::CandleInterval};
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let ws = hypercore::mainnet_ws();
+//! let mut trades = ws.trades("BTC");
+//! let mut candles = ws.candles("ETH", CandleInterval::OneMinute);
+//!
+//! loop {
+//!     tokio::select! {
+//!         Some(trades) = trades.next() => println!("{} BTC trades", trades.len()),
+//!         Some(candle) = candles.next() => println!("ETH close {}", candle.close),
+//!     }
+//! }
+//! # }
+//! ```
+//!
+//! # Reconnection
+//!
+//! Keepalive and reconnection are configured with [`ConnectionConfig`]. By default the
+//! connection pings every 5 seconds, reconnects after 2 missed pongs and retries
+//! forever with a 500ms to 5s exponential backoff.
+//!
+//! [`Connection::stats`] returns a [`ConnectionStats`] handle reporting the number of
+//! reconnections, the age of the last message, per-channel message rates and the
+//! ping/pong round-trip time.
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use hypersdk::hypercore::{
+//!     self,
+//!     ws::{Connection, ConnectionConfig},
+//! };
+//!
+//! # async fn example() {
+//! let config = ConnectionConfig::default()
+//!     .with_ping_interval(Duration::from_secs(2))
+//!     .with_jitter(0.2)
+//!     .with_max_attempts(10);
+//! let ws = Connection::with_config(hypercore::mainnet_websocket_url(), config);
+//!
+//! let stats = ws.stats();
+//! println!("reconnects: {}", stats.reconnects());
+//! println!("rtt: {:?}", stats.rtt());
+//! println!("rates: {:?}", stats.message_rates());
+//! # }
+//! ```
+//!
+//! # Subscription Status
+//!
+//! [`Connection::subscribe`] returns a [`SubscriptionHandle`] that resolves once the
+//! server acknowledges or rejects the subscription. The status of any subscription
+//! can also be queried with [`Connection::status`]; subscriptions go back to
+//! [`SubscriptionStatus::Pending`] while disconnected until they are acknowledged again.
+//!
+//! ```no_run
+//! use hypersdk::hypercore::{self, types::*};
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let ws = hypercore::mainnet_ws();
+//! let user = "0x...".parse()?;
+//!
+//! // don't trade until the fills feed is live
+//! ws.subscribe(Subscription::UserFills { user }).wait().await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
 //! # Examples
 //!
 //! ## Handle Connection Status
@@ -33,6 +110,9 @@
 //!         Event::Disconnected => {
 //!             println!("Disconnected");
 //!         }
+//!         Event::SubscriptionError { subscription, error } => {
+//!             println!("Subscription {subscription:?} rejected: {error}");
+//!         }
 //!         Event::Message(msg) => match msg {
 //!             Incoming::Trades(trades) => {
 //!                 for trade in trades {
@@ -58,7 +138,7 @@
 //!
 //! // Subscribe to trades and orderbook
 //! ws.subscribe(Subscription::Trades { coin: "BTC".into() });
-//! ws.subscribe(Subscription::L2Book { coin: "BTC".into() });
+//! ws.subscribe(Subscription::l2_book("BTC"));
 //!
 //! while let Some(event) = ws.next().await {
 //!     let Event::Message(msg) = event else { continue };
@@ -112,24 +192,67 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! ## Post Requests
+//!
+//! Info requests and signed actions can be sent over the websocket instead of HTTP,
+//! see [`Connection::info`] and [`Connection::action`].
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use hypersdk::hypercore::{self, Chain, PrivateKeySigner, types::*};
+//! use serde_json::json;
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let ws = hypercore::mainnet_ws();
+//! let signer: PrivateKeySigner = "your_key".parse()?;
+//!
+//! let book: L2Book = ws
+//!     .info(json!({ "type": "l2Book", "coin": "BTC" }), Duration::from_secs(5))
+//!     .await?;
+//! println!("{} bids", book.levels[0].len());
+//!
+//! let cancel = Action::from(ScheduleCancel { time: None });
+//! let request = cancel.sign_sync(&signer, 1, None, None, Chain::Mainnet)?;
+//! let response = ws.action(request, Duration::from_secs(5)).await?;
+//! println!("{response:?}");
+//! # Ok(())
+//! # }
+//! ```
 
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet, VecDeque, hash_map::RandomState},
+    hash::{BuildHasher, Hasher},
     pin::Pin,
+    sync::{Arc, Mutex, PoisonError},
     task::{Context, Poll, ready},
     time::Duration,
 };
 
 use anyhow::Result;
 use futures::{SinkExt, StreamExt};
+use serde::{Serialize, de::DeserializeOwned};
 use tokio::{
-    sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel},
-    time::{interval, sleep, timeout},
+    sync::{
+        mpsc::{UnboundedReceiver, UnboundedSender, WeakUnboundedSender, unbounded_channel},
+        oneshot, watch,
+    },
+    time::{Instant, interval, sleep, timeout},
 };
 use url::Url;
 use yawc::{Frame, OpCode, Options, TcpWebSocket};
 
-use crate::hypercore::types::{Incoming, Outgoing, Subscription};
+use crate::{
+    Address,
+    hypercore::{
+        Error, error,
+        types::{
+            ActionRequest, Bbo, Candle, CandleInterval, Fill, Incoming, Outgoing, PostRequest,
+            PostResponse, Subscription, Trade, api::Response,
+        },
+    },
+};
 
 struct Stream {
     stream: TcpWebSocket,
@@ -164,6 +287,13 @@ impl Stream {
         Ok(())
     }
 
+    /// Sends a post request.
+    async fn post(&mut self, id: u64, request: PostRequest) -> anyhow::Result<()> {
+        let text = serde_json::to_string(&Outgoing::Post { id, request })?;
+        self.stream.send(Frame::text(text)).await?;
+        Ok(())
+    }
+
     /// Send a ping
     async fn ping(&mut self) -> anyhow::Result<()> {
         let text = serde_json::to_string(&Outgoing::Ping)?;
@@ -206,7 +336,555 @@ impl futures::Stream for Stream {
     }
 }
 
-type SubChannelData = (bool, Subscription);
+enum Command {
+    Subscribe(Subscription),
+    Unsubscribe(Subscription),
+    Post(PostRequest, oneshot::Sender<error::Result<PostResponse>>),
+}
+
+/// Status of every subscription, shared with the connection task.
+type Statuses = Arc<Mutex<HashMap<Subscription, watch::Sender<SubscriptionStatus>>>>;
+
+/// Status of a subscription.
+#[derive(Debug, Clone, PartialEq, Eq, derive_more::IsVariant)]
+pub enum SubscriptionStatus {
+    /// Waiting for the server to acknowledge the subscription.
+    ///
+    /// Subscriptions are pending while disconnected, until they are acknowledged
+    /// again after reconnecting.
+    Pending,
+    /// The server acknowledged the subscription.
+    Active,
+    /// The server rejected the subscription. It won't be retried on reconnection.
+    Rejected(String),
+    /// The subscription was removed with `unsubscribe`.
+    Unsubscribed,
+}
+
+/// Handle to a subscription returned by [`Connection::subscribe`].
+///
+/// Dropping the handle doesn't unsubscribe.
+#[derive(Debug, Clone)]
+pub struct SubscriptionHandle {
+    subscription: Subscription,
+    rx: watch::Receiver<SubscriptionStatus>,
+}
+
+impl SubscriptionHandle {
+    /// Returns the subscription.
+    #[must_use]
+    pub fn subscription(&self) -> &Subscription {
+        &self.subscription
+    }
+
+    /// Returns the current status of the subscription.
+    #[must_use]
+    pub fn status(&self) -> SubscriptionStatus {
+        self.rx.borrow().clone()
+    }
+
+    /// Waits until the server acknowledges or rejects the subscription.
+    ///
+    /// Returns an error if the subscription was rejected, removed or the
+    /// connection was closed.
+    pub async fn wait(&mut self) -> error::Result<()> {
+        let status = self
+            .rx
+            .wait_for(|status| !status.is_pending())
+            .await
+            .map_err(|_| Error::WebSocket("connection closed".into()))?;
+
+        match &*status {
+            SubscriptionStatus::Active => Ok(()),
+            SubscriptionStatus::Rejected(err) => Err(Error::from_api(err.as_str())),
+            _ => Err(Error::WebSocket(format!(
+                "{} was unsubscribed",
+                self.subscription
+            ))),
+        }
+    }
+}
+
+/// Registers a subscription and returns its handle.
+fn subscribe(
+    tx: &UnboundedSender<Command>,
+    statuses: &Statuses,
+    subscription: Subscription,
+) -> SubscriptionHandle {
+    let rx = {
+        let mut statuses = statuses.lock().unwrap_or_else(PoisonError::into_inner);
+        let status = statuses
+            .entry(subscription.clone())
+            .or_insert_with(|| watch::channel(SubscriptionStatus::Pending).0);
+        // already subscribed otherwise
+        if status.borrow().is_rejected() || status.borrow().is_unsubscribed() {
+            status.send_replace(SubscriptionStatus::Pending);
+        }
+        status.subscribe()
+    };
+
+    let _ = tx.send(Command::Subscribe(subscription.clone()));
+    SubscriptionHandle { subscription, rx }
+}
+
+/// Removes a subscription, resolving its handles as unsubscribed.
+fn unsubscribe(tx: &UnboundedSender<Command>, statuses: &Statuses, subscription: Subscription) {
+    set_status(statuses, &subscription, SubscriptionStatus::Unsubscribed);
+    let _ = tx.send(Command::Unsubscribe(subscription));
+}
+
+/// Returns the status of a subscription.
+fn status(statuses: &Statuses, subscription: &Subscription) -> Option<SubscriptionStatus> {
+    statuses
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(subscription)
+        .map(|status| status.borrow().clone())
+}
+
+/// Updates the status of a subscription, if it's known.
+fn set_status(statuses: &Statuses, subscription: &Subscription, new: SubscriptionStatus) {
+    if let Some(status) = statuses
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(subscription)
+    {
+        status.send_replace(new);
+    }
+}
+
+/// Returns the subscription quoted in a server error, like
+/// `Invalid subscription {"type":"trades","coin":"FOO"}`.
+fn subscription_in_error(error: &str) -> Option<Subscription> {
+    let start = error.find('{')?;
+    let end = error.rfind('}')?;
+    serde_json::from_str(error.get(start..=end)?).ok()
+}
+
+/// Typed streams listening to each subscription.
+///
+/// A subscription stays on the server while it was subscribed explicitly or
+/// has typed streams.
+#[derive(Debug, Default)]
+struct Routes {
+    next_id: u64,
+    channels: HashMap<Subscription, Vec<(u64, UnboundedSender<Incoming>)>>,
+    // subscribed with `subscribe`, until `unsubscribe`
+    explicit: HashSet<Subscription>,
+}
+
+type SharedRoutes = Arc<Mutex<Routes>>;
+
+/// Returns the subscription a message belongs to, for the channels with typed streams.
+fn route_of(msg: &Incoming) -> Option<Subscription> {
+    match msg {
+        Incoming::Trades(trades) => trades.first().map(|trade| Subscription::Trades {
+            coin: trade.coin.clone(),
+        }),
+        Incoming::Bbo(bbo) => Some(Subscription::Bbo {
+            coin: bbo.coin.clone(),
+        }),
+        Incoming::Candle(candle) => Some(Subscription::Candle {
+            coin: candle.coin.clone(),
+            interval: candle.interval.parse().ok()?,
+        }),
+        Incoming::UserFills { user, .. } => Some(Subscription::UserFills { user: *user }),
+        _ => None,
+    }
+}
+
+/// Delivers a message to the typed streams of its subscription.
+///
+/// Returns true if the message is only for typed streams; messages of explicit
+/// subscriptions are also emitted as [`Event::Message`].
+fn route(routes: &SharedRoutes, msg: &Incoming) -> bool {
+    let Some(subscription) = route_of(msg) else {
+        return false;
+    };
+
+    let mut routes = routes.lock().unwrap_or_else(PoisonError::into_inner);
+    let explicit = routes.explicit.contains(&subscription);
+    let Some(listeners) = routes.channels.get_mut(&subscription) else {
+        return false;
+    };
+    listeners.retain(|(_, tx)| tx.send(msg.clone()).is_ok());
+    !explicit
+}
+
+/// Fills received on the `userFills` channel.
+#[derive(Debug, Clone)]
+pub struct FillsUpdate {
+    /// True for the snapshot of recent fills sent when subscribing
+    pub is_snapshot: bool,
+    /// The fills
+    pub fills: Vec<Fill>,
+}
+
+/// Typed stream of the messages of one subscription.
+///
+/// Created by [`Connection::trades`], [`Connection::bbo`], [`Connection::candles`]
+/// and [`Connection::user_fills`]. Messages delivered to typed streams are not
+/// emitted as [`Event::Message`] on the connection stream, unless the channel was
+/// also subscribed with [`Connection::subscribe`].
+///
+/// Several streams can listen to the same subscription; it is unsubscribed when
+/// the last one is dropped and it wasn't subscribed explicitly. The stream ends
+/// when the connection is closed.
+#[derive(Debug)]
+pub struct ChannelStream<T> {
+    rx: UnboundedReceiver<Incoming>,
+    extract: fn(Incoming) -> Option<T>,
+    handle: SubscriptionHandle,
+    _listener: Listener,
+}
+
+impl<T> ChannelStream<T> {
+    /// Returns the subscription of the stream.
+    #[must_use]
+    pub fn subscription(&self) -> &Subscription {
+        self.handle.subscription()
+    }
+
+    /// Returns the current status of the subscription.
+    #[must_use]
+    pub fn status(&self) -> SubscriptionStatus {
+        self.handle.status()
+    }
+
+    /// Waits until the server acknowledges or rejects the subscription.
+    ///
+    /// See [`SubscriptionHandle::wait`].
+    pub async fn wait(&mut self) -> error::Result<()> {
+        self.handle.wait().await
+    }
+}
+
+impl<T> futures::Stream for ChannelStream<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            let Some(msg) = ready!(this.rx.poll_recv(cx)) else {
+                return Poll::Ready(None);
+            };
+            if let Some(item) = (this.extract)(msg) {
+                return Poll::Ready(Some(item));
+            }
+        }
+    }
+}
+
+/// Registration of a typed stream, removed on drop.
+#[derive(Debug)]
+struct Listener {
+    id: u64,
+    subscription: Subscription,
+    /// Weak so that open streams don't keep a closed connection alive.
+    tx: WeakUnboundedSender<Command>,
+    statuses: Statuses,
+    routes: SharedRoutes,
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        let mut routes = self.routes.lock().unwrap_or_else(PoisonError::into_inner);
+        let Some(listeners) = routes.channels.get_mut(&self.subscription) else {
+            return;
+        };
+
+        listeners.retain(|(id, _)| *id != self.id);
+        if listeners.is_empty() {
+            routes.channels.remove(&self.subscription);
+            if routes.explicit.contains(&self.subscription) {
+                return;
+            }
+            drop(routes);
+            if let Some(tx) = self.tx.upgrade() {
+                unsubscribe(&tx, &self.statuses, self.subscription.clone());
+            }
+        }
+    }
+}
+
+fn extract_trades(msg: Incoming) -> Option<Vec<Trade>> {
+    match msg {
+        Incoming::Trades(trades) => Some(trades),
+        _ => None,
+    }
+}
+
+fn extract_bbo(msg: Incoming) -> Option<Bbo> {
+    match msg {
+        Incoming::Bbo(bbo) => Some(bbo),
+        _ => None,
+    }
+}
+
+fn extract_candle(msg: Incoming) -> Option<Candle> {
+    match msg {
+        Incoming::Candle(candle) => Some(candle),
+        _ => None,
+    }
+}
+
+fn extract_fills(msg: Incoming) -> Option<FillsUpdate> {
+    match msg {
+        Incoming::UserFills {
+            is_snapshot, fills, ..
+        } => Some(FillsUpdate { is_snapshot, fills }),
+        _ => None,
+    }
+}
+
+/// Keepalive and reconnection settings of a [`Connection`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConnectionConfig {
+    /// Interval between pings
+    pub ping_interval: Duration,
+    /// Number of unanswered pings after which the connection is dropped
+    pub max_missed_pongs: u8,
+    /// Timeout of a connection attempt
+    pub connect_timeout: Duration,
+    /// Backoff before the first reconnection attempt
+    pub initial_backoff: Duration,
+    /// Upper bound of the backoff
+    pub max_backoff: Duration,
+    /// Fraction of the backoff randomly added or removed, between 0 and 1
+    pub jitter: f64,
+    /// Number of consecutive failed connection attempts before giving up
+    pub max_attempts: Option<u32>,
+    /// Time without a connection after which to give up
+    pub give_up_after: Option<Duration>,
+}
+
+impl Default for ConnectionConfig {
+    fn default() -> Self {
+        Self {
+            ping_interval: Duration::from_secs(5),
+            max_missed_pongs: 2,
+            connect_timeout: Duration::from_secs(10),
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(5),
+            jitter: 0.0,
+            max_attempts: None,
+            give_up_after: None,
+        }
+    }
+}
+
+impl ConnectionConfig {
+    /// Sets the interval between pings.
+    #[must_use]
+    pub fn with_ping_interval(mut self, interval: Duration) -> Self {
+        self.ping_interval = interval;
+        self
+    }
+
+    /// Sets the number of unanswered pings after which the connection is dropped.
+    #[must_use]
+    pub fn with_max_missed_pongs(mut self, max_missed_pongs: u8) -> Self {
+        self.max_missed_pongs = max_missed_pongs;
+        self
+    }
+
+    /// Sets the timeout of a connection attempt.
+    #[must_use]
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// Sets the backoff before the first reconnection attempt.
+    #[must_use]
+    pub fn with_initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Sets the upper bound of the backoff.
+    #[must_use]
+    pub fn with_max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Sets the fraction of the backoff randomly added or removed, clamped between 0 and 1.
+    #[must_use]
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Gives up after `attempts` consecutive failed connection attempts.
+    #[must_use]
+    pub fn with_max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = Some(attempts);
+        self
+    }
+
+    /// Gives up when no connection could be established for `duration`.
+    #[must_use]
+    pub fn with_give_up_after(mut self, duration: Duration) -> Self {
+        self.give_up_after = Some(duration);
+        self
+    }
+
+    /// Returns the backoff before reconnection attempt number `attempt` (starting at 0),
+    /// without jitter.
+    #[must_use]
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff)
+    }
+
+    /// Returns true if reconnecting should stop after `attempts` failed attempts
+    /// and `elapsed` time without a connection.
+    fn should_give_up(&self, attempts: u32, elapsed: Duration) -> bool {
+        self.max_attempts.is_some_and(|max| attempts >= max)
+            || self.give_up_after.is_some_and(|after| elapsed >= after)
+    }
+}
+
+/// Randomly spreads `delay` by up to `jitter` of its value in both directions.
+fn jittered(delay: Duration, jitter: f64) -> Duration {
+    if jitter <= 0.0 {
+        return delay;
+    }
+
+    // uniform in [0, 1]
+    let random = RandomState::new().build_hasher().finish() as f64 / u64::MAX as f64;
+    delay.mul_f64((1.0 + jitter.min(1.0) * (2.0 * random - 1.0)).max(0.0))
+}
+
+/// Time constant of the message rates.
+const RATE_WINDOW: Duration = Duration::from_secs(10);
+
+/// Live statistics of a [`Connection`].
+///
+/// The handle is cheap to clone; clones share the same statistics.
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionStats {
+    inner: Arc<Mutex<StatsInner>>,
+}
+
+#[derive(Debug, Default)]
+struct StatsInner {
+    connected: bool,
+    connections: u64,
+    last_message: Option<Instant>,
+    ping_sent: Option<Instant>,
+    rtt: Option<Duration>,
+    channels: HashMap<&'static str, ChannelRate>,
+}
+
+#[derive(Debug)]
+struct ChannelRate {
+    count: u64,
+    rate: f64,
+    updated_at: Instant,
+}
+
+impl ChannelRate {
+    /// Returns the rate decayed up to `now`.
+    fn rate_at(&self, now: Instant) -> f64 {
+        let elapsed = now.duration_since(self.updated_at).as_secs_f64();
+        self.rate * (-elapsed / RATE_WINDOW.as_secs_f64()).exp()
+    }
+}
+
+impl ConnectionStats {
+    fn lock(&self) -> std::sync::MutexGuard<'_, StatsInner> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns true if the connection is currently established.
+    #[must_use]
+    pub fn is_connected(&self) -> bool {
+        self.lock().connected
+    }
+
+    /// Returns the number of times the connection was re-established.
+    #[must_use]
+    pub fn reconnects(&self) -> u64 {
+        self.lock().connections.saturating_sub(1)
+    }
+
+    /// Returns the time elapsed since the last message, heartbeats excluded.
+    #[must_use]
+    pub fn last_message_age(&self) -> Option<Duration> {
+        self.lock().last_message.map(|at| at.elapsed())
+    }
+
+    /// Returns the last round-trip time measured between a ping and its pong.
+    #[must_use]
+    pub fn rtt(&self) -> Option<Duration> {
+        self.lock().rtt
+    }
+
+    /// Returns the number of messages received on `channel`.
+    ///
+    /// Channels are named like the `channel` field of the messages, e.g. `"l2Book"`.
+    #[must_use]
+    pub fn message_count(&self, channel: &str) -> u64 {
+        self.lock()
+            .channels
+            .get(channel)
+            .map_or(0, |channel| channel.count)
+    }
+
+    /// Returns the messages per second received on each channel.
+    ///
+    /// Rates are exponentially weighted over about 10 seconds.
+    #[must_use]
+    pub fn message_rates(&self) -> HashMap<&'static str, f64> {
+        let now = Instant::now();
+        self.lock()
+            .channels
+            .iter()
+            .map(|(name, channel)| (*name, channel.rate_at(now)))
+            .collect()
+    }
+
+    fn on_connected(&self) {
+        let mut inner = self.lock();
+        inner.connected = true;
+        inner.connections += 1;
+    }
+
+    fn on_disconnected(&self) {
+        let mut inner = self.lock();
+        inner.connected = false;
+        inner.ping_sent = None;
+    }
+
+    fn on_ping(&self) {
+        self.lock().ping_sent = Some(Instant::now());
+    }
+
+    fn on_pong(&self) {
+        let mut inner = self.lock();
+        if let Some(sent) = inner.ping_sent.take() {
+            inner.rtt = Some(sent.elapsed());
+        }
+    }
+
+    fn on_message(&self, channel: &'static str) {
+        let now = Instant::now();
+        let mut inner = self.lock();
+        inner.last_message = Some(now);
+
+        let channel = inner.channels.entry(channel).or_insert(ChannelRate {
+            count: 0,
+            rate: 0.0,
+            updated_at: now,
+        });
+        channel.count += 1;
+        channel.rate = channel.rate_at(now) + 1.0 / RATE_WINDOW.as_secs_f64();
+        channel.updated_at = now;
+    }
+}
 
 /// WebSocket event representing either a connection state change or a data message.
 ///
@@ -227,6 +905,7 @@ type SubChannelData = (bool, Subscription);
 ///     match event {
 ///         Event::Connected => println!("Connected!"),
 ///         Event::Disconnected => println!("Disconnected"),
+///         Event::SubscriptionError { error, .. } => println!("Rejected: {error}"),
 ///         Event::Message(msg) => {
 ///             // Handle data messages
 ///         }
@@ -246,6 +925,14 @@ pub enum Event {
     /// Sent when the connection is unexpectedly closed. The connection will
     /// automatically attempt to reconnect.
     Disconnected,
+    /// The server rejected a subscription or reported an error.
+    ///
+    /// `subscription` is the subscription quoted in the error, if any. Pending
+    /// subscriptions rejected this way are not retried on reconnection.
+    SubscriptionError {
+        subscription: Option<Subscription>,
+        error: String,
+    },
     /// A data message received from the WebSocket.
     Message(Incoming),
 }
@@ -296,7 +983,7 @@ pub enum Event {
 /// ```
 pub struct Connection {
     rx: UnboundedReceiver<Event>,
-    tx: UnboundedSender<SubChannelData>,
+    handle: ConnectionHandle,
 }
 
 /// A handle for managing subscriptions to a WebSocket connection.
@@ -323,7 +1010,7 @@ pub struct Connection {
 /// // Manage subscriptions in a separate task
 /// spawn(async move {
 ///     handle.subscribe(Subscription::Trades { coin: "BTC".into() });
-///     handle.subscribe(Subscription::L2Book { coin: "ETH".into() });
+///     handle.subscribe(Subscription::l2_book("ETH"));
 ///
 ///     // Later, unsubscribe
 ///     tokio::time::sleep(std::time::Duration::from_secs(60)).await;
@@ -344,7 +1031,10 @@ pub struct Connection {
 /// ```
 #[derive(Clone, Debug)]
 pub struct ConnectionHandle {
-    tx: UnboundedSender<SubChannelData>,
+    tx: UnboundedSender<Command>,
+    statuses: Statuses,
+    stats: ConnectionStats,
+    routes: SharedRoutes,
 }
 
 /// A stream of events from a WebSocket connection.
@@ -397,10 +1087,48 @@ impl Connection {
     /// Create a new WebSocket connection:
     /// `WebSocket::new(hypercore::mainnet_websocket_url())`
     pub fn new(url: Url) -> Self {
+        Self::with_config(url, ConnectionConfig::default())
+    }
+
+    /// Creates a new WebSocket connection with custom keepalive and reconnection settings.
+    ///
+    /// If the connection gives up reconnecting, the stream ends.
+    pub fn with_config(url: Url, config: ConnectionConfig) -> Self {
         let (tx, rx) = unbounded_channel();
         let (stx, srx) = unbounded_channel();
-        tokio::spawn(connection(url, tx, srx));
-        Self { rx, tx: stx }
+        let statuses = Statuses::default();
+        let stats = ConnectionStats::default();
+        let routes = SharedRoutes::default();
+        tokio::spawn(connection(
+            url,
+            config,
+            tx,
+            srx,
+            statuses.clone(),
+            stats.clone(),
+            routes.clone(),
+        ));
+        Self {
+            rx,
+            handle: ConnectionHandle {
+                tx: stx,
+                statuses,
+                stats,
+                routes,
+            },
+        }
+    }
+
+    /// Returns true if the connection is currently established.
+    #[must_use]
+    pub fn is_connected(&self) -> bool {
+        self.handle.is_connected()
+    }
+
+    /// Returns a handle to the statistics of the connection.
+    #[must_use]
+    pub fn stats(&self) -> ConnectionStats {
+        self.handle.stats()
     }
 
     /// Subscribes to a WebSocket channel.
@@ -408,13 +1136,46 @@ impl Connection {
     /// The subscription will persist across reconnections. If you're already
     /// subscribed to this channel, this is a no-op.
     ///
+    /// The returned handle can be used to wait for the server to acknowledge
+    /// the subscription.
+    ///
     /// # Example
     ///
     /// Subscribe to market data:
     /// - `ws.subscribe(Subscription::Trades { coin: "BTC".into() })`
-    /// - `ws.subscribe(Subscription::L2Book { coin: "ETH".into() })`
-    pub fn subscribe(&self, subscription: Subscription) {
-        let _ = self.tx.send((true, subscription));
+    /// - `ws.subscribe(Subscription::l2_book("ETH")).wait().await?`
+    pub fn subscribe(&self, subscription: Subscription) -> SubscriptionHandle {
+        self.handle.subscribe(subscription)
+    }
+
+    /// Returns the status of a subscription, or `None` if it was never subscribed.
+    #[must_use]
+    pub fn status(&self, subscription: &Subscription) -> Option<SubscriptionStatus> {
+        self.handle.status(subscription)
+    }
+
+    /// Streams the trades of `coin`.
+    pub fn trades(&self, coin: impl Into<String>) -> ChannelStream<Vec<Trade>> {
+        self.handle.trades(coin)
+    }
+
+    /// Streams the best bid and offer of `coin`.
+    pub fn bbo(&self, coin: impl Into<String>) -> ChannelStream<Bbo> {
+        self.handle.bbo(coin)
+    }
+
+    /// Streams the candles of `coin` at `interval`.
+    pub fn candles(
+        &self,
+        coin: impl Into<String>,
+        interval: CandleInterval,
+    ) -> ChannelStream<Candle> {
+        self.handle.candles(coin, interval)
+    }
+
+    /// Streams the fills of `user`, starting with a snapshot of the recent fills.
+    pub fn user_fills(&self, user: Address) -> ChannelStream<FillsUpdate> {
+        self.handle.user_fills(user)
     }
 
     /// Unsubscribes from a WebSocket channel.
@@ -427,7 +1188,7 @@ impl Connection {
     /// Unsubscribe from a channel:
     /// `ws.unsubscribe(Subscription::Trades { coin: "BTC".into() })`
     pub fn unsubscribe(&self, subscription: Subscription) {
-        let _ = self.tx.send((false, subscription));
+        self.handle.unsubscribe(subscription);
     }
 
     /// Closes the WebSocket connection.
@@ -442,15 +1203,48 @@ impl Connection {
         drop(self);
     }
 
+    /// Sends a post request and waits up to `timeout` for its response.
+    ///
+    /// Requests sent while disconnected are sent once the connection is back.
+    /// If the connection drops before the response arrives the request fails
+    /// with [`Error::WebSocket`]: an action may or may not have been executed.
+    pub async fn post(
+        &self,
+        request: PostRequest,
+        timeout: Duration,
+    ) -> error::Result<PostResponse> {
+        self.handle.post(request, timeout).await
+    }
+
+    /// Sends an info request over the websocket and decodes its `data`.
+    ///
+    /// `request` is the body that would be sent to the `/info` endpoint.
+    pub async fn info<T: DeserializeOwned>(
+        &self,
+        request: impl Serialize,
+        timeout: Duration,
+    ) -> error::Result<T> {
+        self.handle.info(request, timeout).await
+    }
+
+    /// Sends a signed action over the websocket.
+    ///
+    /// The request is built with [`Action::sign_sync`](crate::hypercore::types::Action::sign_sync)
+    /// or [`Action::sign`](crate::hypercore::types::Action::sign), like for the HTTP client.
+    pub async fn action(
+        &self,
+        request: ActionRequest,
+        timeout: Duration,
+    ) -> error::Result<Response> {
+        self.handle.action(request, timeout).await
+    }
+
     /// Splits the connection into a subscription handle and an event stream.
     ///
     /// This is useful when you want to drive the stream in one task and
     /// manage subscriptions from another.
     pub fn split(self) -> (ConnectionHandle, ConnectionStream) {
-        (
-            ConnectionHandle { tx: self.tx },
-            ConnectionStream { rx: self.rx },
-        )
+        (self.handle, ConnectionStream { rx: self.rx })
     }
 }
 
@@ -464,31 +1258,131 @@ impl futures::Stream for Connection {
 }
 
 impl ConnectionHandle {
+    /// Returns true if the connection is currently established.
+    #[must_use]
+    pub fn is_connected(&self) -> bool {
+        self.stats.is_connected()
+    }
+
+    /// Returns a handle to the statistics of the connection.
+    #[must_use]
+    pub fn stats(&self) -> ConnectionStats {
+        self.stats.clone()
+    }
+
     /// Subscribes to a WebSocket channel.
     ///
     /// The subscription will persist across reconnections. If you're already
     /// subscribed to this channel, this is a no-op.
     ///
+    /// The returned handle can be used to wait for the server to acknowledge
+    /// the subscription.
+    ///
     /// # Example
     ///
     /// Subscribe to market data:
     /// - `ws.subscribe(Subscription::Trades { coin: "BTC".into() })`
-    /// - `ws.subscribe(Subscription::L2Book { coin: "ETH".into() })`
-    pub fn subscribe(&self, subscription: Subscription) {
-        let _ = self.tx.send((true, subscription));
+    /// - `ws.subscribe(Subscription::l2_book("ETH")).wait().await?`
+    pub fn subscribe(&self, subscription: Subscription) -> SubscriptionHandle {
+        self.routes
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .explicit
+            .insert(subscription.clone());
+        subscribe(&self.tx, &self.statuses, subscription)
+    }
+
+    /// Returns the status of a subscription, or `None` if it was never subscribed.
+    #[must_use]
+    pub fn status(&self, subscription: &Subscription) -> Option<SubscriptionStatus> {
+        status(&self.statuses, subscription)
+    }
+
+    /// Streams the trades of `coin`.
+    pub fn trades(&self, coin: impl Into<String>) -> ChannelStream<Vec<Trade>> {
+        let subscription = Subscription::Trades { coin: coin.into() };
+        self.listen(subscription, extract_trades)
+    }
+
+    /// Streams the best bid and offer of `coin`.
+    pub fn bbo(&self, coin: impl Into<String>) -> ChannelStream<Bbo> {
+        let subscription = Subscription::Bbo { coin: coin.into() };
+        self.listen(subscription, extract_bbo)
+    }
+
+    /// Streams the candles of `coin` at `interval`.
+    pub fn candles(
+        &self,
+        coin: impl Into<String>,
+        interval: CandleInterval,
+    ) -> ChannelStream<Candle> {
+        let subscription = Subscription::Candle {
+            coin: coin.into(),
+            interval,
+        };
+        self.listen(subscription, extract_candle)
+    }
+
+    /// Streams the fills of `user`, starting with a snapshot of the recent fills.
+    pub fn user_fills(&self, user: Address) -> ChannelStream<FillsUpdate> {
+        let subscription = Subscription::UserFills { user };
+        self.listen(subscription, extract_fills)
     }
 
     /// Unsubscribes from a WebSocket channel.
     ///
     /// Stops receiving updates for this subscription. Does nothing if you're
-    /// not currently subscribed to this channel.
+    /// not currently subscribed to this channel. Typed streams of the channel
+    /// keep receiving its messages until they are dropped.
     ///
     /// # Example
     ///
     /// Unsubscribe from a channel:
     /// `ws.unsubscribe(Subscription::Trades { coin: "BTC".into() })`
     pub fn unsubscribe(&self, subscription: Subscription) {
-        let _ = self.tx.send((false, subscription));
+        let mut routes = self.routes.lock().unwrap_or_else(PoisonError::into_inner);
+        routes.explicit.remove(&subscription);
+        if routes.channels.contains_key(&subscription) {
+            return;
+        }
+        drop(routes);
+        unsubscribe(&self.tx, &self.statuses, subscription);
+    }
+
+    /// Sends a post request and waits up to `timeout` for its response.
+    ///
+    /// Requests sent while disconnected are sent once the connection is back.
+    /// If the connection drops before the response arrives the request fails
+    /// with [`Error::WebSocket`]: an action may or may not have been executed.
+    pub async fn post(
+        &self,
+        request: PostRequest,
+        timeout: Duration,
+    ) -> error::Result<PostResponse> {
+        post(&self.tx, request, timeout).await
+    }
+
+    /// Sends an info request over the websocket and decodes its `data`.
+    ///
+    /// `request` is the body that would be sent to the `/info` endpoint.
+    pub async fn info<T: DeserializeOwned>(
+        &self,
+        request: impl Serialize,
+        timeout: Duration,
+    ) -> error::Result<T> {
+        info(&self.tx, request, timeout).await
+    }
+
+    /// Sends a signed action over the websocket.
+    ///
+    /// The request is built with [`Action::sign_sync`](crate::hypercore::types::Action::sign_sync)
+    /// or [`Action::sign`](crate::hypercore::types::Action::sign), like for the HTTP client.
+    pub async fn action(
+        &self,
+        request: ActionRequest,
+        timeout: Duration,
+    ) -> error::Result<Response> {
+        action(&self.tx, request, timeout).await
     }
 
     /// Closes the WebSocket connection.
@@ -502,6 +1396,39 @@ impl ConnectionHandle {
     pub fn close(self) {
         drop(self);
     }
+
+    /// Subscribes and opens a typed stream on the subscription.
+    fn listen<T>(
+        &self,
+        subscription: Subscription,
+        extract: fn(Incoming) -> Option<T>,
+    ) -> ChannelStream<T> {
+        let (listener_tx, rx) = unbounded_channel();
+        let id = {
+            let mut routes = self.routes.lock().unwrap_or_else(PoisonError::into_inner);
+            routes.next_id += 1;
+            let id = routes.next_id;
+            routes
+                .channels
+                .entry(subscription.clone())
+                .or_default()
+                .push((id, listener_tx));
+            id
+        };
+
+        ChannelStream {
+            rx,
+            extract,
+            handle: subscribe(&self.tx, &self.statuses, subscription.clone()),
+            _listener: Listener {
+                id,
+                subscription,
+                tx: self.tx.downgrade(),
+                statuses: self.statuses.clone(),
+                routes: self.routes.clone(),
+            },
+        }
+    }
 }
 
 impl futures::Stream for ConnectionStream {
@@ -513,54 +1440,107 @@ impl futures::Stream for ConnectionStream {
     }
 }
 
+async fn post(
+    tx: &UnboundedSender<Command>,
+    request: PostRequest,
+    duration: Duration,
+) -> error::Result<PostResponse> {
+    let (reply_tx, reply_rx) = oneshot::channel();
+    tx.send(Command::Post(request, reply_tx))
+        .map_err(|_| Error::WebSocket("connection closed".into()))?;
+
+    match timeout(duration, reply_rx).await {
+        Ok(Ok(res)) => res,
+        Ok(Err(_)) => Err(Error::WebSocket("connection closed".into())),
+        Err(_) => Err(Error::Timeout),
+    }
+}
+
+async fn info<T: DeserializeOwned>(
+    tx: &UnboundedSender<Command>,
+    request: impl Serialize,
+    timeout: Duration,
+) -> error::Result<T> {
+    let request = PostRequest::Info(serde_json::to_value(request)?);
+    match post(tx, request, timeout).await? {
+        PostResponse::Info(mut payload) => Ok(serde_json::from_value(payload["data"].take())?),
+        PostResponse::Error(err) => Err(Error::from_api(err)),
+        resp => Err(Error::Other(format!(
+            "info: unexpected response type: {resp:?}"
+        ))),
+    }
+}
+
+async fn action(
+    tx: &UnboundedSender<Command>,
+    request: ActionRequest,
+    timeout: Duration,
+) -> error::Result<Response> {
+    match post(tx, PostRequest::Action(Box::new(request)), timeout).await? {
+        PostResponse::Action(payload) => Ok(serde_json::from_value(payload)?),
+        PostResponse::Error(err) => Err(Error::from_api(err)),
+        resp => Err(Error::Other(format!(
+            "action: unexpected response type: {resp:?}"
+        ))),
+    }
+}
+
+/// Ends the subscription handles and typed streams of a closed connection.
+fn close(statuses: &Statuses, routes: &SharedRoutes) {
+    statuses
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clear();
+    routes
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .channels
+        .clear();
+}
+
 async fn connection(
     url: Url,
+    config: ConnectionConfig,
     tx: UnboundedSender<Event>,
-    mut srx: UnboundedReceiver<SubChannelData>,
+    mut srx: UnboundedReceiver<Command>,
+    statuses: Statuses,
+    stats: ConnectionStats,
+    routes: SharedRoutes,
 ) {
-    const MAX_MISSED_PONGS: u8 = 2;
-    const MAX_RECONNECT_DELAY_MS: u64 = 5_000; // 5 seconds max
-    const INITIAL_RECONNECT_DELAY_MS: u64 = 500;
-
     let mut subs: HashSet<Subscription> = HashSet::new();
     let mut reconnect_attempts = 0u32;
+    let mut disconnected_at = Instant::now();
+    // post requests waiting for a response
+    let mut pending: HashMap<u64, oneshot::Sender<error::Result<PostResponse>>> = HashMap::new();
+    let mut next_id = 1u64;
+    // subscriptions waiting for an acknowledgement, in the order they were sent
+    let mut unacked: VecDeque<Subscription> = VecDeque::new();
 
     loop {
-        let mut stream = match timeout(Duration::from_secs(10), Stream::connect(url.clone())).await
-        {
-            Ok(ok) => match ok {
-                Ok(ok) => ok,
-                Err(err) => {
-                    log::error!("Unable to connect to {url}: {err:?}");
-
-                    // Exponential backoff: 500ms, 1s, 2s, 4s, 5s (capped)
-                    let delay_ms = (INITIAL_RECONNECT_DELAY_MS * (1u64 << reconnect_attempts))
-                        .min(MAX_RECONNECT_DELAY_MS);
-                    reconnect_attempts = reconnect_attempts.saturating_add(1);
-
-                    log::debug!(
-                        "Reconnecting in {}ms (attempt {})",
-                        delay_ms,
-                        reconnect_attempts
-                    );
-                    sleep(Duration::from_millis(delay_ms)).await;
-
-                    continue;
+        let mut stream = match timeout(config.connect_timeout, Stream::connect(url.clone())).await {
+            Ok(Ok(ok)) => ok,
+            res => {
+                match res {
+                    Ok(Err(err)) => log::error!("Unable to connect to {url}: {err:?}"),
+                    _ => log::error!("Connection timeout to {url}"),
                 }
-            },
-            Err(err) => {
-                log::error!("Connection timeout to {url}: {err:?}");
 
-                let delay_ms = (INITIAL_RECONNECT_DELAY_MS * (1u64 << reconnect_attempts))
-                    .min(MAX_RECONNECT_DELAY_MS);
+                let delay = jittered(config.backoff(reconnect_attempts), config.jitter);
                 reconnect_attempts = reconnect_attempts.saturating_add(1);
+                if config.should_give_up(reconnect_attempts, disconnected_at.elapsed()) {
+                    log::error!(
+                        "Giving up connecting to {url} after {reconnect_attempts} attempts"
+                    );
+                    close(&statuses, &routes);
+                    return;
+                }
 
                 log::debug!(
-                    "Reconnecting in {}ms (attempt {})",
-                    delay_ms,
+                    "Reconnecting in {:?} (attempt {})",
+                    delay,
                     reconnect_attempts
                 );
-                sleep(Duration::from_millis(delay_ms)).await;
+                sleep(delay).await;
 
                 continue;
             }
@@ -568,9 +1548,11 @@ async fn connection(
 
         log::debug!("Connected to {url}");
         reconnect_attempts = 0; // Reset on successful connection
+        stats.on_connected();
         let _ = tx.send(Event::Connected);
 
         // Re-subscribe to all active subscriptions after reconnection
+        unacked.clear();
         if !subs.is_empty() {
             log::debug!("Re-subscribing to {} channels", subs.len());
             for sub in subs.iter() {
@@ -578,61 +1560,219 @@ async fn connection(
                 if let Err(err) = stream.subscribe(sub.clone()).await {
                     log::error!("Failed to re-subscribe to {sub}: {err:?}");
                 }
+                unacked.push_back(sub.clone());
             }
         }
 
-        let mut ping_interval = interval(Duration::from_secs(5));
+        let mut ping_interval = interval(config.ping_interval);
         let mut missed_pongs: u8 = 0;
 
         loop {
             tokio::select! {
                 _ = ping_interval.tick() => {
-                    if missed_pongs >= MAX_MISSED_PONGS {
+                    if missed_pongs >= config.max_missed_pongs {
                         log::warn!("Missed {missed_pongs} pongs, reconnecting...");
                         break;
                     }
 
                     if stream.ping().await.is_ok() {
-                        missed_pongs += 1;
+                        missed_pongs = missed_pongs.saturating_add(1);
+                        stats.on_ping();
                     }
                 }
                 maybe_item = stream.next() => {
                     let Some(item) = maybe_item else { break; };
+                    if !matches!(item, Incoming::Ping | Incoming::Pong) {
+                        stats.on_message(item.channel());
+                    }
+
                     match item {
                         Incoming::Pong => {
                             missed_pongs = 0;
+                            stats.on_pong();
                         }
                         Incoming::Ping => {
                             let _ = stream.pong().await;
                         }
+                        Incoming::Post { id, response } => {
+                            if let Some(reply) = pending.remove(&id) {
+                                let _ = reply.send(Ok(response));
+                            }
+                        }
+                        Incoming::SubscriptionResponse(Outgoing::Subscribe { subscription }) => {
+                            if let Some(pos) = unacked.iter().position(|sub| *sub == subscription) {
+                                unacked.remove(pos);
+                            }
+                            set_status(&statuses, &subscription, SubscriptionStatus::Active);
+                            let _ = tx.send(Event::Message(Incoming::SubscriptionResponse(
+                                Outgoing::Subscribe { subscription },
+                            )));
+                        }
+                        Incoming::Error(error) => {
+                            // the server quotes the subscription it rejects
+                            let subscription = subscription_in_error(&error);
+                            let pos = subscription
+                                .as_ref()
+                                .and_then(|sub| unacked.iter().position(|pending| pending == sub));
+                            if let Some(sub) = pos.and_then(|pos| unacked.remove(pos)) {
+                                log::error!("Subscription {sub} rejected: {error}");
+                                subs.remove(&sub);
+                                set_status(
+                                    &statuses,
+                                    &sub,
+                                    SubscriptionStatus::Rejected(error.clone()),
+                                );
+                            } else {
+                                log::error!("Server error: {error}");
+                            }
+                            let _ = tx.send(Event::SubscriptionError { subscription, error });
+                        }
                         _ => {
-                            let _ = tx.send(Event::Message(item));
+                            if !route(&routes, &item) {
+                                let _ = tx.send(Event::Message(item));
+                            }
                         }
                     }
                 }
                 item = srx.recv() => {
-                    let Some((is_sub, sub)) = item else { return };
-                    if is_sub {
-                        if !subs.insert(sub.clone()) {
-                            log::debug!("Already subscribed to {sub:?}");
-                            continue;
-                        }
+                    let Some(command) = item else {
+                        close(&statuses, &routes);
+                        return;
+                    };
+                    match command {
+                        Command::Subscribe(sub) => {
+                            if !subs.insert(sub.clone()) {
+                                log::debug!("Already subscribed to {sub:?}");
+                                continue;
+                            }
 
-                        if let Err(err) = stream.subscribe(sub).await {
-                            log::error!("Subscribing: {err:?}");
-                            break;
+                            unacked.push_back(sub.clone());
+                            if let Err(err) = stream.subscribe(sub).await {
+                                log::error!("Subscribing: {err:?}");
+                                break;
+                            }
                         }
-                    } else if subs.remove(&sub) {
-                        if let Err(err) = stream.unsubscribe(sub).await {
-                            log::error!("Unsubscribing: {err:?}");
-                            break;
+                        Command::Unsubscribe(sub) => {
+                            unacked.retain(|pending| *pending != sub);
+                            if subs.remove(&sub) {
+                                if let Err(err) = stream.unsubscribe(sub).await {
+                                    log::error!("Unsubscribing: {err:?}");
+                                    break;
+                                }
+                            }
+                        }
+                        Command::Post(request, reply) => {
+                            // drop the requests that timed out
+                            pending.retain(|_, reply| !reply.is_closed());
+                            // queued while disconnected and timed out since, never send it
+                            if reply.is_closed() {
+                                continue;
+                            }
+
+                            let id = next_id;
+                            next_id += 1;
+                            if let Err(err) = stream.post(id, request).await {
+                                log::error!("Posting: {err:?}");
+                                let _ = reply.send(Err(Error::WebSocket(err.to_string())));
+                                break;
+                            }
+                            pending.insert(id, reply);
                         }
                     }
                 }
             }
         }
 
+        // acknowledged again after reconnecting
+        for status in statuses
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+        {
+            if status.borrow().is_active() {
+                status.send_replace(SubscriptionStatus::Pending);
+            }
+        }
+
+        // the responses are lost with the connection
+        for (_, reply) in pending.drain() {
+            let _ = reply.send(Err(Error::WebSocket(
+                "disconnected before the response was received".into(),
+            )));
+        }
+
         log::warn!("Disconnected from {url}, attempting to reconnect...");
+        stats.on_disconnected();
+        disconnected_at = Instant::now();
         let _ = tx.send(Event::Disconnected);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_backoff() {
+        let config = ConnectionConfig::default();
+        assert_eq!(config.backoff(0), Duration::from_millis(500));
+        assert_eq!(config.backoff(3), Duration::from_secs(4));
+        assert_eq!(config.backoff(30), Duration::from_secs(5));
+
+        for _ in 0..100 {
+            let delay = jittered(Duration::from_secs(1), 0.2);
+            assert!(delay >= Duration::from_millis(800) && delay <= Duration::from_millis(1200));
+        }
+        assert_eq!(
+            jittered(Duration::from_secs(1), 0.0),
+            Duration::from_secs(1)
+        );
+
+        let config = config
+            .with_max_attempts(3)
+            .with_give_up_after(Duration::from_secs(60));
+        assert!(!config.should_give_up(2, Duration::from_secs(10)));
+        assert!(config.should_give_up(3, Duration::from_secs(10)));
+        assert!(config.should_give_up(1, Duration::from_secs(60)));
+        assert!(!ConnectionConfig::default().should_give_up(u32::MAX, Duration::MAX));
+    }
+
+    #[test]
+    fn test_subscription_in_error() {
+        assert_eq!(
+            subscription_in_error(r#"Invalid subscription {"type":"trades","coin":"FOO"}"#),
+            Some(Subscription::Trades { coin: "FOO".into() })
+        );
+        assert_eq!(subscription_in_error("Server overloaded"), None);
+        assert_eq!(subscription_in_error("Invalid json {oops}"), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stats() {
+        let stats = ConnectionStats::default();
+        assert!(!stats.is_connected());
+        assert_eq!(stats.last_message_age(), None);
+
+        stats.on_connected();
+        stats.on_ping();
+        tokio::time::advance(Duration::from_millis(40)).await;
+        stats.on_pong();
+        assert_eq!(stats.rtt(), Some(Duration::from_millis(40)));
+
+        // 10 messages per second
+        for _ in 0..300 {
+            stats.on_message("trades");
+            tokio::time::advance(Duration::from_millis(100)).await;
+        }
+        assert_eq!(stats.message_count("trades"), 300);
+        assert_eq!(stats.message_count("l2Book"), 0);
+        let rate = stats.message_rates()["trades"];
+        assert!((9.0..=11.0).contains(&rate), "{rate}");
+        assert_eq!(stats.last_message_age(), Some(Duration::from_millis(100)));
+
+        stats.on_disconnected();
+        stats.on_connected();
+        assert_eq!(stats.reconnects(), 1);
+        assert!(stats.is_connected());
+    }
+}
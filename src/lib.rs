@@ -83,6 +83,7 @@
 //!         cloid: Default::default(),
 //!     }],
 //!     grouping: OrderGrouping::Na,
+//!     builder: None,
 //! };
 //!
 //! let nonce = chrono::Utc::now().timestamp_millis() as u64;
@@ -103,10 +104,10 @@
 //!
 //! // Subscribe to market data
 //! ws.subscribe(Subscription::Trades { coin: "BTC".into() });
-//! ws.subscribe(Subscription::L2Book { coin: "ETH".into() });
+//! ws.subscribe(Subscription::l2_book("ETH"));
 //! ws.subscribe(Subscription::Candle {
 //!     coin: "BTC".into(),
-//!     interval: "15m".into()
+//!     interval: CandleInterval::FifteenMinutes,
 //! });
 //!
 //! // Optional: user streams